@@ -11,12 +11,10 @@
 //! 2. Mutating an entity's `Volume` triggers reconciliation automatically.
 //! 3. Despawning the entity removes the underlying graph node + commits.
 //!
-//! The bare oscillator used here (`sine_hz`) doesn't expose a `set_gain`
-//! setter at the AudioUnit level, so the `Volume` change is logged rather
-//! than physically attenuating the output. To attenuate audio, swap the
-//! `NodeKind::Generator` for `NodeKind::Sampler` and use a `SamplerUnit`
-//! source; the existing `reconcile_params` Sampler arm will route the
-//! gain change through `SamplerUnit::set_gain`.
+//! The bare oscillator used here (`sine_hz`) has no gain setter of its
+//! own, so `Volume` builds a `ChannelStrip` after it and the fade is
+//! applied there. Samplers skip the strip and route the gain change
+//! through `SamplerUnit::set_gain` instead.
 //!
 //! Run with:
 //!
@@ -33,7 +31,8 @@ use bevy_ecs::prelude::*;
 use bevy_log::LogPlugin;
 
 use bevy_tutti::{
-    AudioNode, MasterMeterLevels, NodeKind, SpawnAudioNode, TuttiGraphRes, TuttiPlugin, Volume,
+    pipe_audio_node_output, AudioNode, ChannelStrip, MasterMeterLevels, NodeKind, SpawnAudioNode,
+    TuttiPlugin, Volume,
 };
 use tutti::dsp::sine_hz;
 
@@ -46,7 +45,7 @@ fn main() {
         .add_plugins(AssetPlugin::default())
         .add_plugins(TuttiPlugin::default())
         .add_systems(Startup, (start_transport, spawn_sine).chain())
-        .add_systems(Update, (fade_volume, log_state))
+        .add_systems(Update, (pipe_strip, fade_volume, log_state))
         .run();
 }
 
@@ -55,22 +54,18 @@ fn start_transport(transport: Res<bevy_tutti::TransportRes>) {
 }
 
 fn spawn_sine(mut commands: Commands) {
-    let entity = commands
+    commands
         .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
-        .insert(Volume(1.0))
-        .id();
+        .insert(Volume(1.0));
+}
 
-    // The graph mutation in `spawn_audio_node` is queued as a deferred
-    // world command. We queue a second command to wire the new node into
-    // the output bus once that one has applied.
-    commands.queue(move |world: &mut World| {
-        let Some(node) = world.get::<AudioNode>(entity).copied() else {
-            return;
-        };
-        let mut graph = world.resource_mut::<TuttiGraphRes>();
-        graph.0.pipe_output(node.0);
-        graph.0.commit();
-    });
+/// The channel strip is built by the reconcile pipeline on the first
+/// `Update`; pipe it (not the bare oscillator) to the output bus so the
+/// `Volume` fade is audible.
+fn pipe_strip(mut commands: Commands, q: Query<Entity, Added<ChannelStrip>>) {
+    for entity in &q {
+        pipe_audio_node_output(&mut commands, entity);
+    }
 }
 
 fn fade_volume(mut q: Query<&mut Volume, With<AudioNode>>) {
@@ -1,6 +1,6 @@
 //! Audio input device selection + monitoring + peak-level mirror.
 
-use bevy_app::{App, Plugin, Startup, Update};
+use bevy_app::{App, Plugin, Update};
 use bevy_ecs::prelude::*;
 use bevy_reflect::prelude::*;
 
@@ -118,7 +118,8 @@ pub fn audio_input_sync_system(
     state.peak_level = sampler.0.audio_input().peak_level();
 }
 
-/// One-shot startup: enumerate input devices once.
+/// Enumerates input devices whenever the sampler is (re)inserted — on
+/// the first frame, and again after a `RetryAudioEngine` rebuild.
 pub fn audio_input_init_system(
     sampler: Option<Res<SamplerRes>>,
     mut state: ResMut<AudioInputState>,
@@ -143,11 +144,14 @@ impl Plugin for TuttiAudioInputPlugin {
             .register_type::<DisableAudioInput>()
             .register_type::<AudioInputState>()
             .register_type::<AudioInputDeviceInfo>();
-        app.init_resource::<AudioInputState>()
-            .add_systems(Startup, audio_input_init_system)
-            .add_systems(
-                Update,
-                (audio_input_control_system, audio_input_sync_system),
-            );
+        app.init_resource::<AudioInputState>().add_systems(
+            Update,
+            (
+                audio_input_init_system.run_if(resource_added::<SamplerRes>),
+                audio_input_control_system,
+                audio_input_sync_system,
+            )
+                .chain(),
+        );
     }
 }
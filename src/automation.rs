@@ -15,9 +15,11 @@ use bevy_ecs::prelude::*;
 use bevy_reflect::prelude::*;
 
 use tutti::automation::LiveAutomationLane;
-use tutti::core::ecs::{AudioNode, PluginParam, Volume};
+use tutti::core::ecs::{AudioNode, Pan, PluginParam, Volume};
 
+use crate::graph::bus::{reconcile_send_levels, SendTo};
 use crate::graph::reconcile::{reconcile_params, GraphReconcileSystems};
+use crate::graph::strip::reconcile_strip_levels;
 use crate::resources::{TransportRes, TuttiGraphRes};
 
 /// Trigger component: spawn an entity with this to create an automation lane.
@@ -85,12 +87,16 @@ pub struct AutomationLaneNode;
 pub enum AutomationParam {
     /// Writes into the target's [`Volume`] component.
     Volume,
-    /// Writes into the target's [`tutti::core::ecs::Pan`] component.
+    /// Writes into the target's [`Pan`] component (`-1.0` left … `1.0`
+    /// right); the target's `ChannelStrip` applies it to the audio.
     Pan,
     /// Writes into the target's [`PluginParam`] component matching the
     /// given plugin parameter id. The component is updated in place
     /// (`PluginParam::value` is overwritten).
     PluginParam(u32),
+    /// Writes into the target's [`SendTo::level`]. Target the send entity,
+    /// not the track it belongs to.
+    SendLevel,
 }
 
 /// "This automation lane drives a parameter on `target`."
@@ -143,13 +149,24 @@ pub fn automation_lane_system(
 /// target entity's parameter component.
 ///
 /// Runs in [`GraphReconcileSystems::Params`]. The downstream parameter
-/// reconcilers (`reconcile_params`, `reconcile_plugin_params`, …) pick up
-/// the resulting `Changed<Volume>` / `Changed<PluginParam>` later in the
-/// same set and route it to the audio thread.
+/// reconcilers (`reconcile_params`, `reconcile_strip_levels`,
+/// `reconcile_plugin_params`, `reconcile_send_levels`, …) pick up the
+/// resulting `Changed<Volume>` / `Changed<Pan>` / `Changed<PluginParam>` /
+/// `Changed<SendTo>` later in the same set and
+/// route it to the audio thread.
+#[allow(
+    clippy::type_complexity,
+    reason = "Bevy queries are tuple-shaped by design"
+)]
 pub fn reconcile_automation_writes(
     graph: Option<Res<TuttiGraphRes>>,
     drivers: Query<(&AudioNode, &AutomationDrivesParam)>,
-    mut targets: Query<(Option<&mut Volume>, Option<&mut PluginParam>)>,
+    mut targets: Query<(
+        Option<&mut Volume>,
+        Option<&mut Pan>,
+        Option<&mut PluginParam>,
+        Option<&mut SendTo>,
+    )>,
 ) {
     let Some(graph) = graph else { return };
 
@@ -163,7 +180,9 @@ pub fn reconcile_automation_writes(
         };
         let value = lane.last_value();
 
-        let Ok((mut maybe_vol, mut maybe_param)) = targets.get_mut(drives.target) else {
+        let Ok((mut maybe_vol, mut maybe_pan, mut maybe_param, mut maybe_send)) =
+            targets.get_mut(drives.target)
+        else {
             continue;
         };
 
@@ -176,11 +195,11 @@ pub fn reconcile_automation_writes(
                 }
             }
             AutomationParam::Pan => {
-                // Pan needs its own component reference; route through a
-                // separate query when callers need it. Skipped here to
-                // keep the query borrow set narrow — Pan automation lands
-                // in a follow-up commit alongside dawai's Pan reconcile.
-                let _ = value;
+                if let Some(p) = maybe_pan.as_deref_mut() {
+                    if (p.0 - value).abs() > f32::EPSILON {
+                        p.0 = value;
+                    }
+                }
             }
             AutomationParam::PluginParam(id) => {
                 if let Some(p) = maybe_param.as_deref_mut() {
@@ -189,6 +208,13 @@ pub fn reconcile_automation_writes(
                     }
                 }
             }
+            AutomationParam::SendLevel => {
+                if let Some(s) = maybe_send.as_deref_mut() {
+                    if (s.level - value).abs() > f32::EPSILON {
+                        s.level = value;
+                    }
+                }
+            }
         }
     }
 }
@@ -205,7 +231,9 @@ impl Plugin for TuttiAutomationPlugin {
             Update,
             reconcile_automation_writes
                 .in_set(GraphReconcileSystems::Params)
-                .before(reconcile_params),
+                .before(reconcile_params)
+                .before(reconcile_strip_levels)
+                .before(reconcile_send_levels),
         );
     }
 }
@@ -0,0 +1,586 @@
+//! Gameplay callbacks on beats and subdivisions, timed by the audio clock.
+//!
+//! Add a [`BeatTrigger`] to an entity and it receives a [`BeatTriggered`]
+//! event each time the *heard* playhead crosses one of its grid lines.
+//! The heard playhead is the audio thread's transport position (read
+//! straight from [`TransportRes`], not the frame-old `TransportState`)
+//! minus [`OutputLatency`].
+//!
+//! Events are still delivered in `Update`, so they arrive up to a frame
+//! after the beat was heard. Each one carries [`BeatTriggered::heard_at`],
+//! the instant the grid line reached the speakers, so hit judgement can
+//! measure against the beat itself rather than the frame that reported
+//! it.
+//!
+//! `heard_at` is timed from the audio thread: [`beat_anchor_system`] keeps
+//! a small node in the graph that records the transport beat and the
+//! `Instant` at the start of every rendered block. A grid line is heard
+//! that block's instant, plus the time from the block's beat to the line,
+//! plus [`OutputLatency`] — independent of when in the frame
+//! [`beat_trigger_system`] happened to run. Until the first block is
+//! anchored, it falls back to backdating from the frame's own clock.
+
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bevy_ecs::message::{Message, MessageWriter};
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use tutti::dsp::{An, AudioNode as DspNode, Frame, U0};
+use tutti::NodeId;
+
+use crate::graph::GraphDirty;
+use crate::resources::{AudioConfig, OutputLatency, TransportRes, TuttiGraphRes};
+use crate::tempo_map::TempoMap;
+use crate::transport::{Meter, TransportState};
+
+/// Grid spacing for a [`BeatTrigger`]. Note values are relative to a
+/// quarter-note beat, independent of the meter; [`Bar`](Self::Bar)
+/// follows the meter (and the [`TempoMap`]'s meter changes).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub enum Subdivision {
+    Bar,
+    Whole,
+    Half,
+    #[default]
+    Quarter,
+    Eighth,
+    Sixteenth,
+    QuarterTriplet,
+    EighthTriplet,
+    SixteenthTriplet,
+    /// Any spacing, in quarter-note beats.
+    Beats(f64),
+}
+
+impl Subdivision {
+    /// Spacing in quarter-note beats, or `None` for [`Bar`](Self::Bar).
+    pub fn beats(&self) -> Option<f64> {
+        match *self {
+            Subdivision::Bar => None,
+            Subdivision::Whole => Some(4.0),
+            Subdivision::Half => Some(2.0),
+            Subdivision::Quarter => Some(1.0),
+            Subdivision::Eighth => Some(0.5),
+            Subdivision::Sixteenth => Some(0.25),
+            Subdivision::QuarterTriplet => Some(2.0 / 3.0),
+            Subdivision::EighthTriplet => Some(1.0 / 3.0),
+            Subdivision::SixteenthTriplet => Some(1.0 / 6.0),
+            Subdivision::Beats(beats) => Some(beats),
+        }
+    }
+}
+
+/// Most [`BeatTriggered`] events one trigger fires in a frame.
+pub const MAX_LINES_PER_FRAME: usize = 64;
+
+/// Fires [`BeatTriggered`] on this entity every `every`, shifted by
+/// `offset_beats` (e.g. `0.5` for off-beats).
+///
+/// Grid lines crossed in the frame the component is added are skipped.
+/// Spacing is at least one sample, and a frame that crosses more than
+/// [`MAX_LINES_PER_FRAME`] lines fires only the latest of them.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Default, Clone)]
+pub struct BeatTrigger {
+    pub every: Subdivision,
+    pub offset_beats: f64,
+}
+
+impl BeatTrigger {
+    pub fn new(every: Subdivision) -> Self {
+        Self {
+            every,
+            offset_beats: 0.0,
+        }
+    }
+
+    pub fn offset(mut self, beats: f64) -> Self {
+        self.offset_beats = beats;
+        self
+    }
+
+    /// Grid lines in `(from, to]` (or `[from, to]` with `include_from`),
+    /// as `(beat, index)`. The index counts grid lines from beat 0; for
+    /// [`Subdivision::Bar`] it's the 0-based bar number.
+    ///
+    /// Steps finer than `min_step` (one sample at the current tempo) are
+    /// widened to it, and a span crossing more than
+    /// [`MAX_LINES_PER_FRAME`] lines (a frame hitch) yields only the
+    /// latest ones.
+    fn lines(
+        &self,
+        from: f64,
+        to: f64,
+        include_from: bool,
+        meter: &Meter,
+        min_step: f64,
+    ) -> Vec<(f64, u64)> {
+        let crossed = |at: f64| at <= to && (at > from || (include_from && at == from));
+        let mut lines = Vec::new();
+        match self.every.beats() {
+            None => {
+                let last = meter.bar_at(to - self.offset_beats);
+                let first = meter
+                    .bar_at(from - self.offset_beats)
+                    .max(last.saturating_sub(MAX_LINES_PER_FRAME as u32));
+                for bar in first..=last {
+                    let at = meter.bar_start(bar) + self.offset_beats;
+                    if crossed(at) {
+                        lines.push((at, bar as u64));
+                    }
+                }
+            }
+            Some(step) if step > 0.0 => {
+                let step = step.max(min_step);
+                let last = ((to - self.offset_beats) / step).floor();
+                let mut k = ((from - self.offset_beats) / step)
+                    .ceil()
+                    .max(last - MAX_LINES_PER_FRAME as f64)
+                    .max(0.0);
+                loop {
+                    let at = k * step + self.offset_beats;
+                    if at > to {
+                        break;
+                    }
+                    if crossed(at) {
+                        lines.push((at, k as u64));
+                    }
+                    k += 1.0;
+                }
+            }
+            Some(_) => {}
+        }
+        let excess = lines.len().saturating_sub(MAX_LINES_PER_FRAME);
+        lines.drain(..excess);
+        lines
+    }
+}
+
+/// Sent to a [`BeatTrigger`] entity when the heard playhead crosses one
+/// of its grid lines. Observe it on the entity, or read it as a message.
+#[derive(EntityEvent, Message, Clone, Copy, Debug)]
+pub struct BeatTriggered {
+    pub entity: Entity,
+    /// Transport beat of the grid line.
+    pub beat: f64,
+    /// Grid line number (see [`BeatTrigger`]).
+    pub index: u64,
+    /// When the grid line was heard, timed from the audio thread's block
+    /// anchor (see the [module docs](self)).
+    pub heard_at: Instant,
+}
+
+/// Where the audio thread last was: the transport beat at the start of
+/// a block and when that block was rendered.
+///
+/// Written once per block by [`BeatAnchorNode`], read by
+/// [`beat_trigger_system`]. A sequence lock keeps the pair consistent
+/// without the audio thread ever waiting.
+struct AnchorCell {
+    /// Odd while a write is in progress.
+    seq: AtomicU64,
+    /// `f64` bits; NaN while there's no anchor.
+    beat: AtomicU64,
+    /// Since `epoch`.
+    nanos: AtomicU64,
+    epoch: Instant,
+}
+
+impl AnchorCell {
+    fn new() -> Self {
+        Self {
+            seq: AtomicU64::new(0),
+            beat: AtomicU64::new(f64::NAN.to_bits()),
+            nanos: AtomicU64::new(0),
+            epoch: Instant::now(),
+        }
+    }
+
+    fn store(&self, beat: f64, at: Instant) {
+        let nanos = at.saturating_duration_since(self.epoch).as_nanos() as u64;
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        self.beat.store(beat.to_bits(), Ordering::Relaxed);
+        self.nanos.store(nanos, Ordering::Relaxed);
+        self.seq.store(seq.wrapping_add(2), Ordering::Release);
+    }
+
+    fn clear(&self) {
+        self.store(f64::NAN, self.epoch);
+    }
+
+    /// `(beat, rendered at)`, or `None` without an anchor (or if the
+    /// audio thread kept writing while we read).
+    fn load(&self) -> Option<(f64, Instant)> {
+        for _ in 0..4 {
+            let seq = self.seq.load(Ordering::Acquire);
+            if seq % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let beat = f64::from_bits(self.beat.load(Ordering::Relaxed));
+            let nanos = self.nanos.load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) != seq {
+                continue;
+            }
+            return (!beat.is_nan()).then(|| (beat, self.epoch + Duration::from_nanos(nanos)));
+        }
+        None
+    }
+}
+
+/// Audio-thread tap recording an [`AnchorCell`] on the first sample of
+/// each block (when the transport publishes a new beat).
+#[derive(Clone)]
+struct BeatAnchorNode {
+    transport: tutti::TransportHandle,
+    cell: Arc<AnchorCell>,
+    published_beat: f64,
+}
+
+impl DspNode for BeatAnchorNode {
+    const ID: u64 = 0x7475_7474_6261_6e63;
+    type Inputs = U0;
+    type Outputs = U0;
+
+    fn reset(&mut self) {
+        self.published_beat = f64::NAN;
+    }
+
+    #[inline]
+    fn tick(&mut self, _input: &Frame<f32, Self::Inputs>) -> Frame<f32, Self::Outputs> {
+        if !self.transport.is_playing() {
+            if !self.published_beat.is_nan() {
+                self.published_beat = f64::NAN;
+                self.cell.clear();
+            }
+            return Frame::default();
+        }
+        let published = self.transport.current_beat();
+        if published != self.published_beat {
+            self.published_beat = published;
+            self.cell.store(published, Instant::now());
+        }
+        Frame::default()
+    }
+}
+
+/// The graph node behind [`BeatTriggered::heard_at`], installed by
+/// [`beat_anchor_system`].
+#[derive(Resource)]
+pub struct BeatAnchor {
+    node: NodeId,
+    cell: Arc<AnchorCell>,
+}
+
+/// Keeps the [`BeatAnchor`] node in the graph, re-adding it after an
+/// engine rebuild or when the transport handle changes. Runs before
+/// the graph commit.
+pub fn beat_anchor_system(
+    mut commands: Commands,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    transport: Option<Res<TransportRes>>,
+    anchor: Option<Res<BeatAnchor>>,
+    mut dirty: ResMut<GraphDirty>,
+) {
+    let (Some(mut graph), Some(transport)) = (graph, transport) else {
+        return;
+    };
+    let installed = anchor.as_ref().filter(|a| graph.0.contains(a.node));
+    if installed.is_some() && !transport.is_changed() {
+        return;
+    }
+    if let Some(anchor) = installed {
+        graph.0.remove(anchor.node);
+    }
+    let cell = Arc::new(AnchorCell::new());
+    let node = graph.0.add(An(BeatAnchorNode {
+        transport: transport.0.clone(),
+        cell: cell.clone(),
+        published_beat: f64::NAN,
+    }));
+    dirty.0 = true;
+    commands.insert_resource(BeatAnchor { node, cell });
+}
+
+/// Heard-playhead tracking for [`beat_trigger_system`].
+#[derive(Default)]
+pub struct BeatTriggerClock {
+    /// Heard beat as of the last frame, while playing.
+    heard: Option<f64>,
+    /// Where playback will start from, while stopped.
+    start: Option<f64>,
+}
+
+/// Fires [`BeatTriggered`] for each [`BeatTrigger`] grid line the heard
+/// playhead crossed since the last frame.
+///
+/// Loop-aware: the heard playhead wraps at the loop end once the
+/// rendered one has. A jump backwards that isn't a wrap (a seek) fires
+/// nothing. Runs after `transport_sync_system`.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Bevy systems take their dependencies as parameters"
+)]
+pub fn beat_trigger_system(
+    mut commands: Commands,
+    transport: Option<Res<TransportRes>>,
+    tempo_map: Option<Res<TempoMap>>,
+    latency: Option<Res<OutputLatency>>,
+    config: Option<Res<AudioConfig>>,
+    anchor: Option<Res<BeatAnchor>>,
+    state: Res<TransportState>,
+    mut clock: Local<BeatTriggerClock>,
+    mut writer: MessageWriter<BeatTriggered>,
+    triggers: Query<(Entity, Ref<BeatTrigger>)>,
+) {
+    let Some(transport) = transport else { return };
+    let now = Instant::now();
+    let beat = transport.current_beat();
+
+    if !transport.is_playing() {
+        clock.heard = None;
+        clock.start = Some(beat);
+        return;
+    }
+
+    let tempo = transport.get_tempo().get();
+    if tempo <= 0.0 {
+        return;
+    }
+    let latency_secs = latency.map_or(0.0, |l| l.seconds.max(0.0));
+    let loop_range = (state.is_looping && state.loop_end > state.loop_start)
+        .then_some((state.loop_start, state.loop_end));
+    let heard = heard_beat(
+        beat,
+        latency_secs,
+        tempo,
+        tempo_map.as_deref(),
+        loop_range,
+        clock.heard,
+    );
+
+    // (from, to, include_from, beats from `to` until the heard playhead)
+    let spans: Vec<(f64, f64, bool, f64)> = match clock.heard {
+        None => {
+            let from = clock.start.unwrap_or(heard);
+            if heard < from {
+                // Still in the latency pre-roll.
+                return;
+            }
+            clock.start = None;
+            vec![(from, heard, true, 0.0)]
+        }
+        Some(prev) if heard >= prev => vec![(prev, heard, false, 0.0)],
+        Some(prev) => match loop_range {
+            Some((start, end)) if prev >= start && prev <= end && heard >= start => vec![
+                (prev, end - 1e-9, false, heard - start),
+                (start, heard, true, 0.0),
+            ],
+            _ => Vec::new(),
+        },
+    };
+    clock.heard = Some(heard);
+
+    let anchor = anchor.and_then(|a| a.cell.load());
+    let meter = Meter::new(tempo_map.as_deref(), &state);
+    let sample_rate = config.map_or(tutti::dsp::DEFAULT_SR, |c| c.sample_rate);
+    let min_step = tempo / 60.0 / sample_rate;
+    for (entity, trigger) in triggers.iter() {
+        if trigger.is_added() {
+            continue;
+        }
+        for &(from, to, include_from, after) in &spans {
+            for (at, index) in trigger.lines(from, to, include_from, &meter, min_step) {
+                let heard_at = match anchor {
+                    Some((anchor_beat, rendered_at)) => {
+                        let secs = seconds_between(
+                            anchor_beat,
+                            at,
+                            tempo,
+                            tempo_map.as_deref(),
+                            loop_range,
+                        );
+                        offset_instant(rendered_at, secs + latency_secs)
+                    }
+                    None => {
+                        let ago = (to - at).max(0.0) + after;
+                        offset_instant(now, -ago * 60.0 / tempo)
+                    }
+                };
+                let event = BeatTriggered {
+                    entity,
+                    beat: at,
+                    index,
+                    heard_at,
+                };
+                commands.trigger(event);
+                writer.write(event);
+            }
+        }
+    }
+}
+
+/// The beat reaching the speakers when the audio thread is at `beat`.
+/// Behind the loop start it wraps back to the loop end, but only once
+/// playback is under way (`previous` is set) — at the start of playback
+/// it's the pre-roll.
+fn heard_beat(
+    beat: f64,
+    latency_secs: f64,
+    tempo: f64,
+    tempo_map: Option<&TempoMap>,
+    loop_range: Option<(f64, f64)>,
+    previous: Option<f64>,
+) -> f64 {
+    let heard = match tempo_map {
+        Some(map) => map.beat_at_seconds(map.seconds_at(beat) - latency_secs),
+        None => beat - latency_secs * tempo / 60.0,
+    };
+    match (loop_range, previous) {
+        (Some((start, end)), Some(_)) if beat >= start && heard < start => {
+            (end - (start - heard)).max(start)
+        }
+        _ => heard,
+    }
+}
+
+/// Playback seconds from beat `from` to beat `to`, negative if `to` came
+/// first. Inside a loop, beats more than half the loop apart are taken
+/// to be on either side of the wrap.
+fn seconds_between(
+    from: f64,
+    to: f64,
+    tempo: f64,
+    tempo_map: Option<&TempoMap>,
+    loop_range: Option<(f64, f64)>,
+) -> f64 {
+    let secs = |a: f64, b: f64| match tempo_map {
+        Some(map) => map.seconds_at(b) - map.seconds_at(a),
+        None => (b - a) * 60.0 / tempo,
+    };
+    match loop_range {
+        Some((start, end)) if (start..=end).contains(&from) && (start..=end).contains(&to) => {
+            let half = (end - start) / 2.0;
+            if to - from > half {
+                // `to` was before the wrap.
+                -(secs(start, from) + secs(to, end))
+            } else if from - to > half {
+                secs(from, end) + secs(start, to)
+            } else {
+                secs(from, to)
+            }
+        }
+        _ => secs(from, to),
+    }
+}
+
+fn offset_instant(instant: Instant, secs: f64) -> Instant {
+    if secs >= 0.0 {
+        instant + Duration::from_secs_f64(secs)
+    } else {
+        instant
+            .checked_sub(Duration::from_secs_f64(-secs))
+            .unwrap_or(instant)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One sample at 120 BPM, 48 kHz.
+    const MIN_STEP: f64 = 120.0 / 60.0 / 48_000.0;
+
+    fn four_four() -> Meter<'static> {
+        Meter::new(None, &TransportState::default())
+    }
+
+    #[test]
+    fn subdivision_lines_respect_offset() {
+        let offbeats = BeatTrigger::new(Subdivision::Quarter).offset(0.5);
+        assert_eq!(
+            offbeats.lines(0.0, 2.0, true, &four_four(), MIN_STEP),
+            vec![(0.5, 0), (1.5, 1)]
+        );
+        let eighths = BeatTrigger::new(Subdivision::Eighth);
+        assert_eq!(
+            eighths.lines(1.0, 2.0, false, &four_four(), MIN_STEP),
+            vec![(1.5, 3), (2.0, 4)]
+        );
+    }
+
+    #[test]
+    fn bar_lines_follow_meter() {
+        let bars = BeatTrigger::new(Subdivision::Bar);
+        assert_eq!(
+            bars.lines(0.0, 9.0, true, &four_four(), MIN_STEP),
+            vec![(0.0, 0), (4.0, 1), (8.0, 2)]
+        );
+        let map = TempoMap::new(120.0).with_meter(1, 3, 4);
+        let meter = Meter::new(Some(&map), &TransportState::default());
+        assert_eq!(
+            bars.lines(0.5, 10.0, false, &meter, MIN_STEP),
+            vec![(4.0, 1), (7.0, 2), (10.0, 3)]
+        );
+    }
+
+    #[test]
+    fn tiny_steps_and_hitches_stay_bounded() {
+        let tiny = BeatTrigger::new(Subdivision::Beats(1e-12));
+        let lines = tiny.lines(0.0, 1.0, true, &four_four(), MIN_STEP);
+        assert_eq!(lines.len(), MAX_LINES_PER_FRAME);
+        // The latest lines survive, spaced one sample apart.
+        let (last, index) = *lines.last().unwrap();
+        assert!((last - index as f64 * MIN_STEP).abs() < 1e-12);
+        assert!(1.0 - last < 2.0 * MIN_STEP);
+
+        let hitch = BeatTrigger::new(Subdivision::Sixteenth);
+        let lines = hitch.lines(0.0, 1000.0, false, &four_four(), MIN_STEP);
+        assert_eq!(lines.len(), MAX_LINES_PER_FRAME);
+        assert_eq!(lines.last(), Some(&(1000.0, 4000)));
+
+        let bars = BeatTrigger::new(Subdivision::Bar);
+        let lines = bars.lines(0.0, 4000.0, true, &four_four(), MIN_STEP);
+        assert_eq!(lines.len(), MAX_LINES_PER_FRAME);
+        assert_eq!(lines.last(), Some(&(4000.0, 1000)));
+    }
+
+    #[test]
+    fn heard_beat_lags_by_latency_and_wraps_in_loop() {
+        // 120 BPM, 100 ms: a fifth of a beat behind.
+        assert!((heard_beat(2.0, 0.1, 120.0, None, None, Some(1.7)) - 1.8).abs() < 1e-9);
+        let looped = heard_beat(0.1, 0.1, 120.0, None, Some((0.0, 4.0)), Some(3.8));
+        assert!((looped - 3.9).abs() < 1e-9);
+        // Pre-roll at the start of playback doesn't wrap.
+        assert!(heard_beat(0.1, 0.1, 120.0, None, Some((0.0, 4.0)), None) < 0.0);
+    }
+
+    #[test]
+    fn seconds_between_unwraps_across_the_loop() {
+        // 120 BPM: half a second per beat.
+        assert!((seconds_between(1.0, 1.5, 120.0, None, None) - 0.25).abs() < 1e-9);
+        assert!((seconds_between(1.5, 1.0, 120.0, None, None) + 0.25).abs() < 1e-9);
+        let looped = Some((0.0, 4.0));
+        // Anchored just after the wrap, line just before it.
+        assert!((seconds_between(0.1, 3.9, 120.0, None, looped) + 0.1).abs() < 1e-9);
+        // Anchored just before the wrap, line just after it.
+        assert!((seconds_between(3.9, 0.1, 120.0, None, looped) - 0.1).abs() < 1e-9);
+        let map = TempoMap::new(60.0);
+        assert!((seconds_between(1.0, 3.0, 120.0, Some(&map), None) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn anchor_cell_round_trips_and_clears() {
+        let cell = AnchorCell::new();
+        assert!(cell.load().is_none());
+        let at = cell.epoch + Duration::from_millis(5);
+        cell.store(8.0, at);
+        assert_eq!(cell.load(), Some((8.0, at)));
+        cell.clear();
+        assert!(cell.load().is_none());
+    }
+}
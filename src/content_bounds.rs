@@ -1,25 +1,30 @@
 use bevy_ecs::prelude::*;
 use bevy_reflect::prelude::*;
 
-use crate::{TransportRes, TuttiGraphRes};
+use crate::{TempoMap, TransportRes, TuttiGraphRes};
 
 /// Content duration bounds synced from Tutti every frame.
 #[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Reflect)]
 #[reflect(Resource, Default, Clone)]
 pub struct ContentBounds {
     pub end_beat: f64,
-    /// Computed from end_beat and current tempo.
+    /// Computed from end_beat through the [`TempoMap`] when one is
+    /// inserted, otherwise at the current tempo.
     pub duration_seconds: f64,
 }
 
 pub fn content_bounds_sync_system(
     graph: Option<Res<TuttiGraphRes>>,
     transport: Option<Res<TransportRes>>,
+    tempo_map: Option<Res<TempoMap>>,
     mut bounds: ResMut<ContentBounds>,
 ) {
     let Some(graph) = graph else { return };
     let Some(transport) = transport else { return };
 
     bounds.end_beat = graph.0.content_end_beat(&transport.0);
-    bounds.duration_seconds = graph.0.content_duration(&transport.0);
+    bounds.duration_seconds = match tempo_map {
+        Some(map) => map.seconds_at(bounds.end_beat),
+        None => graph.0.content_duration(&transport.0),
+    };
 }
@@ -1,4 +1,6 @@
+use bevy_ecs::message::{Message, MessageWriter};
 use bevy_ecs::prelude::*;
+use bevy_log::warn;
 use bevy_reflect::prelude::*;
 
 use crate::{AudioConfig, TuttiDriverRes};
@@ -9,6 +11,8 @@ use crate::{AudioConfig, TuttiDriverRes};
 pub struct AudioDeviceState {
     pub output_devices: Vec<String>,
     pub current_device: String,
+    /// Name of the system default output device, if the host reports one.
+    pub default_device: Option<String>,
     pub is_running: bool,
     pub channels: usize,
 }
@@ -18,12 +22,67 @@ impl Default for AudioDeviceState {
         Self {
             output_devices: Vec::new(),
             current_device: String::new(),
+            default_device: None,
             is_running: false,
             channels: 2,
         }
     }
 }
 
+/// How [`SelectOutputDevice`] identifies the device to switch to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Reflect)]
+pub enum OutputDeviceSelector {
+    /// Index into `TuttiDriver::devices()` (same as `AudioDeviceState::output_devices`).
+    Index(usize),
+    /// Device name (partial match, first hit wins).
+    Name(String),
+}
+
+/// Trigger component: switch the live output device.
+///
+/// Processed by `select_output_device_system`, which resolves the
+/// selector against the current device list, calls
+/// `TuttiDriver::set_device`, and removes this component. Failures are
+/// logged and reported as [`AudioDeviceEvent::StreamError`].
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Clone)]
+pub struct SelectOutputDevice {
+    pub name_or_index: OutputDeviceSelector,
+}
+
+impl SelectOutputDevice {
+    pub fn by_index(index: usize) -> Self {
+        Self {
+            name_or_index: OutputDeviceSelector::Index(index),
+        }
+    }
+
+    pub fn by_name(name: impl Into<String>) -> Self {
+        Self {
+            name_or_index: OutputDeviceSelector::Name(name.into()),
+        }
+    }
+}
+
+/// Output device hot-plug and stream notifications.
+#[derive(Event, Message, Clone, Debug)]
+pub enum AudioDeviceEvent {
+    Added {
+        name: String,
+    },
+    Removed {
+        name: String,
+    },
+    DefaultChanged {
+        name: String,
+    },
+    /// The stream stopped unexpectedly or a device switch failed.
+    StreamError {
+        device: String,
+        error: String,
+    },
+}
+
 pub fn device_state_sync_system(
     driver: Option<NonSend<TuttiDriverRes>>,
     config: Option<Res<AudioConfig>>,
@@ -42,11 +101,223 @@ pub fn device_state_init_system(
     mut state: ResMut<AudioDeviceState>,
 ) {
     let Some(driver) = driver else { return };
+    refresh_device_state(&driver.0, &mut state);
+}
 
-    if let Ok(name) = driver.0.device_name() {
+/// Re-read the current device name and the output device list.
+pub(crate) fn refresh_device_state(driver: &tutti::TuttiDriver, state: &mut AudioDeviceState) {
+    if let Ok(name) = driver.device_name() {
         state.current_device = name;
     }
     if let Ok(devices) = tutti::TuttiDriver::devices() {
-        state.output_devices = devices.map(|d| d.name).collect();
+        let devices: Vec<tutti::DeviceInfo> = devices.collect();
+        state.default_device = devices
+            .iter()
+            .find(|d| d.is_default)
+            .map(|d| d.name.clone());
+        state.output_devices = devices.into_iter().map(|d| d.name).collect();
+    }
+}
+
+/// Re-read the running stream's sample rate and channel count after a
+/// device switch, so systems building units from [`AudioConfig`] use the
+/// new device's format.
+pub(crate) fn refresh_audio_config(driver: &tutti::TuttiDriver, config: Option<&mut AudioConfig>) {
+    let Some(config) = config else { return };
+    let (sample_rate, channels) = (driver.sample_rate(), driver.channels());
+    if config.sample_rate != sample_rate || config.channels != channels {
+        bevy_log::info!("Output stream now {}Hz, {}ch", sample_rate, channels);
+        config.sample_rate = sample_rate;
+        config.channels = channels;
+    }
+}
+
+/// Resolves a selector against the device list: an index in range, or
+/// the first device whose name contains the given text.
+fn resolve_output_device(selector: &OutputDeviceSelector, devices: &[String]) -> Option<usize> {
+    let index = match selector {
+        OutputDeviceSelector::Index(index) => Some(*index),
+        OutputDeviceSelector::Name(name) => devices.iter().position(|d| d.contains(name.as_str())),
+    };
+    index.filter(|i| *i < devices.len())
+}
+
+/// Where to go when the current device is gone: the system default, if
+/// it's in the device list.
+fn fallback_output_device(default: Option<&str>, devices: &[String]) -> Option<usize> {
+    default.and_then(|name| devices.iter().position(|d| d == name))
+}
+
+/// Processes [`SelectOutputDevice`] triggers.
+pub fn select_output_device_system(
+    mut commands: Commands,
+    driver: Option<NonSendMut<TuttiDriverRes>>,
+    mut config: Option<ResMut<AudioConfig>>,
+    mut state: ResMut<AudioDeviceState>,
+    mut device_events: MessageWriter<AudioDeviceEvent>,
+    query: Query<(Entity, &SelectOutputDevice), Added<SelectOutputDevice>>,
+) {
+    let Some(mut driver) = driver else { return };
+
+    for (entity, select) in query.iter() {
+        commands.entity(entity).remove::<SelectOutputDevice>();
+
+        let Some(index) = resolve_output_device(&select.name_or_index, &state.output_devices)
+        else {
+            warn!(
+                "SelectOutputDevice: no output device matches {:?}",
+                select.name_or_index
+            );
+            continue;
+        };
+
+        let name = state.output_devices[index].clone();
+        match driver.0.set_device(index) {
+            Ok(()) => {
+                bevy_log::info!("Output device switched to '{}'", name);
+                refresh_device_state(&driver.0, &mut state);
+                refresh_audio_config(&driver.0, config.as_deref_mut());
+                state.is_running = driver.0.is_running();
+            }
+            Err(e) => {
+                warn!("Failed to switch output device to '{}': {}", name, e);
+                device_events.write(AudioDeviceEvent::StreamError {
+                    device: name,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Polls every 2s; re-enumerates output devices and emits
+/// [`AudioDeviceEvent`]s for devices that appeared or vanished, a changed
+/// system default, and a stream that stopped behind our back.
+///
+/// When the current device disappears (USB interface unplugged) or its
+/// stream dies, the driver falls back to the system default device so
+/// the app keeps producing audio.
+pub fn device_hotplug_poll_system(
+    driver: Option<NonSendMut<TuttiDriverRes>>,
+    mut config: Option<ResMut<AudioConfig>>,
+    mut state: ResMut<AudioDeviceState>,
+    mut device_events: MessageWriter<AudioDeviceEvent>,
+    mut last_check: Local<Option<std::time::Instant>>,
+    mut was_running: Local<bool>,
+) {
+    let Some(mut driver) = driver else { return };
+    let now = std::time::Instant::now();
+
+    if let Some(last) = *last_check {
+        if now.duration_since(last).as_secs() < 2 {
+            return;
+        }
+    }
+    *last_check = Some(now);
+
+    let Ok(devices) = tutti::TuttiDriver::devices() else {
+        return;
+    };
+    let devices: Vec<tutti::DeviceInfo> = devices.collect();
+    let live: Vec<String> = devices.iter().map(|d| d.name.clone()).collect();
+    let default = devices
+        .iter()
+        .find(|d| d.is_default)
+        .map(|d| d.name.clone());
+
+    for name in state.output_devices.iter().filter(|d| !live.contains(d)) {
+        device_events.write(AudioDeviceEvent::Removed { name: name.clone() });
+    }
+    for name in live.iter().filter(|d| !state.output_devices.contains(d)) {
+        device_events.write(AudioDeviceEvent::Added { name: name.clone() });
+    }
+    if default != state.default_device {
+        if let Some(name) = &default {
+            device_events.write(AudioDeviceEvent::DefaultChanged { name: name.clone() });
+        }
+    }
+
+    let current_lost = !state.current_device.is_empty() && !live.contains(&state.current_device);
+    let stream_died = *was_running && !driver.0.is_running();
+    if stream_died {
+        device_events.write(AudioDeviceEvent::StreamError {
+            device: state.current_device.clone(),
+            error: "output stream stopped".to_string(),
+        });
+    }
+
+    state.output_devices = live;
+    state.default_device = default;
+
+    if current_lost || stream_died {
+        let fallback =
+            fallback_output_device(state.default_device.as_deref(), &state.output_devices);
+        if let Some(index) = fallback {
+            match driver.0.set_device(index) {
+                Ok(()) => {
+                    bevy_log::info!(
+                        "Output device '{}' lost; fell back to '{}'",
+                        state.current_device,
+                        state.output_devices[index]
+                    );
+                    refresh_audio_config(&driver.0, config.as_deref_mut());
+                }
+                Err(e) => {
+                    warn!("Failed to fall back to default output device: {}", e);
+                    device_events.write(AudioDeviceEvent::StreamError {
+                        device: state.output_devices[index].clone(),
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+        if let Ok(name) = driver.0.device_name() {
+            state.current_device = name;
+        }
+    }
+    state.is_running = driver.0.is_running();
+    *was_running = state.is_running;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn devices() -> Vec<String> {
+        ["Built-in Output", "USB Audio Interface", "USB Headset"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn selector_resolves_index_and_partial_name() {
+        let devices = devices();
+        let resolve = |selector| resolve_output_device(&selector, &devices);
+        assert_eq!(resolve(OutputDeviceSelector::Index(1)), Some(1));
+        assert_eq!(resolve(OutputDeviceSelector::Index(3)), None);
+        assert_eq!(
+            resolve(OutputDeviceSelector::Name("Headset".into())),
+            Some(2)
+        );
+        // First hit wins.
+        assert_eq!(resolve(OutputDeviceSelector::Name("USB".into())), Some(1));
+        assert_eq!(resolve(OutputDeviceSelector::Name("HDMI".into())), None);
+        assert_eq!(
+            resolve_output_device(&OutputDeviceSelector::Index(0), &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn fallback_needs_a_listed_default() {
+        let devices = devices();
+        assert_eq!(
+            fallback_output_device(Some("Built-in Output"), &devices),
+            Some(0)
+        );
+        // Names match exactly here; a default that vanished with the
+        // lost device is no fallback.
+        assert_eq!(fallback_output_device(Some("Built-in"), &devices), None);
+        assert_eq!(fallback_output_device(None, &devices), None);
     }
 }
@@ -0,0 +1,188 @@
+//! Audio-engine health as Bevy diagnostics.
+//!
+//! [`TuttiDiagnosticsPlugin`] publishes the engine's CPU meter (enabled by
+//! `TuttiPlugin`) and the reconcile pipeline's workload under `tutti/…`
+//! [`DiagnosticPath`]s, so they show up alongside frame time in any
+//! diagnostics HUD or `LogDiagnosticsPlugin`. Per-node DSP time is mirrored
+//! onto the owning entity as [`NodeCpuLoad`].
+
+use std::time::Instant;
+
+use bevy_app::prelude::*;
+use bevy_diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+use tutti::core::ecs::AudioNode;
+
+use crate::graph::{commit_graph, GraphDirty, GraphReconcileSystems};
+use crate::resources::{MeteringRes, TuttiGraphRes};
+
+#[cfg(feature = "sampler")]
+use crate::graph::{PendingSamplerLoad, WaveImportQueue};
+#[cfg(all(feature = "plugin", feature = "vst2"))]
+use crate::vst2_load::PendingVst2Build;
+
+/// Bevy plugin: registers the `tutti/…` diagnostics and the systems that
+/// measure them. Add after `TuttiPlugin`.
+///
+/// Per-node timing is switched on by this plugin; it costs a clock read
+/// per node per callback, so it stays off unless diagnostics are wanted.
+#[derive(Default)]
+pub struct TuttiDiagnosticsPlugin;
+
+impl TuttiDiagnosticsPlugin {
+    /// Audio callback time as a percentage of the buffer's real-time budget.
+    pub const DSP_LOAD: DiagnosticPath = DiagnosticPath::const_new("tutti/dsp_load");
+    /// Wall-clock duration of the audio callback, in milliseconds.
+    pub const CALLBACK_DURATION: DiagnosticPath =
+        DiagnosticPath::const_new("tutti/callback_duration");
+    /// Buffer underruns/overruns since the engine started.
+    pub const XRUNS: DiagnosticPath = DiagnosticPath::const_new("tutti/xruns");
+    /// Nodes in the audio graph.
+    pub const NODE_COUNT: DiagnosticPath = DiagnosticPath::const_new("tutti/node_count");
+    /// `graph.commit()` calls from the reconcile pipeline, per second.
+    pub const COMMITS_PER_SECOND: DiagnosticPath =
+        DiagnosticPath::const_new("tutti/commits_per_second");
+    /// Graph work waiting on something else: asset loads, file imports,
+    /// plugin builds.
+    pub const PENDING_RECONCILE: DiagnosticPath =
+        DiagnosticPath::const_new("tutti/pending_reconcile");
+}
+
+impl Plugin for TuttiDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::DSP_LOAD).with_suffix("%"))
+            .register_diagnostic(Diagnostic::new(Self::CALLBACK_DURATION).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(Self::XRUNS))
+            .register_diagnostic(Diagnostic::new(Self::NODE_COUNT))
+            .register_diagnostic(Diagnostic::new(Self::COMMITS_PER_SECOND))
+            .register_diagnostic(Diagnostic::new(Self::PENDING_RECONCILE));
+
+        app.register_type::<NodeCpuLoad>();
+        app.add_systems(
+            Update,
+            (
+                enable_node_cpu_timing
+                    .run_if(resource_added::<MeteringRes>)
+                    .before(node_cpu_load_system),
+                engine_diagnostics_system,
+                node_cpu_load_system,
+                reconcile_diagnostics_system
+                    .in_set(GraphReconcileSystems::Commit)
+                    .before(commit_graph),
+            ),
+        );
+    }
+}
+
+/// DSP time spent in this entity's graph node, as a fraction of the
+/// audio callback's real-time budget (`1.0` = the whole buffer).
+///
+/// Inserted and updated by [`TuttiDiagnosticsPlugin`] on [`AudioNode`]
+/// entities the engine has timing for. Gain stages and meter taps added
+/// by `ChannelStrip`/`MeterTap` aren't included.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Default, Clone)]
+pub struct NodeCpuLoad {
+    /// Average over the engine's last measurement window.
+    pub load: f32,
+    /// Highest `load` seen since the component was inserted.
+    pub peak: f32,
+}
+
+/// Switches on per-node timing whenever a [`MeteringRes`] is inserted,
+/// so it survives an engine restart (or an engine started late).
+pub fn enable_node_cpu_timing(metering: Option<Res<MeteringRes>>) {
+    let Some(metering) = metering else { return };
+    metering.inner().cpu().enable_node_timing();
+}
+
+/// Publishes DSP load, callback duration, xruns and node count.
+pub fn engine_diagnostics_system(
+    metering: Option<Res<MeteringRes>>,
+    graph: Option<Res<TuttiGraphRes>>,
+    mut diagnostics: Diagnostics,
+) {
+    if let Some(metering) = metering {
+        let cpu = metering.inner().cpu();
+        diagnostics.add_measurement(&TuttiDiagnosticsPlugin::DSP_LOAD, || {
+            cpu.load() as f64 * 100.0
+        });
+        diagnostics.add_measurement(&TuttiDiagnosticsPlugin::CALLBACK_DURATION, || {
+            cpu.callback_duration().as_secs_f64() * 1000.0
+        });
+        diagnostics.add_measurement(&TuttiDiagnosticsPlugin::XRUNS, || cpu.xruns() as f64);
+    }
+    if let Some(graph) = graph {
+        diagnostics.add_measurement(&TuttiDiagnosticsPlugin::NODE_COUNT, || {
+            graph.0.node_count() as f64
+        });
+    }
+}
+
+/// Mirrors per-node DSP time onto [`NodeCpuLoad`].
+pub fn node_cpu_load_system(
+    mut commands: Commands,
+    metering: Option<Res<MeteringRes>>,
+    mut nodes: Query<(Entity, &AudioNode, Option<&mut NodeCpuLoad>)>,
+) {
+    let Some(metering) = metering else { return };
+    let cpu = metering.inner().cpu();
+    for (entity, node, existing) in nodes.iter_mut() {
+        let Some(load) = cpu.node_load(node.0) else {
+            continue;
+        };
+        match existing {
+            Some(mut existing) => {
+                existing.load = load;
+                existing.peak = existing.peak.max(load);
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(NodeCpuLoad { load, peak: load });
+            }
+        }
+    }
+}
+
+/// Publishes commit rate and pending reconcile work. Runs in
+/// [`GraphReconcileSystems::Commit`] just before [`commit_graph`], so
+/// [`GraphDirty`] says whether this frame commits.
+pub fn reconcile_diagnostics_system(
+    dirty: Res<GraphDirty>,
+    mut last_frame: Local<Option<Instant>>,
+    mut diagnostics: Diagnostics,
+    #[cfg(feature = "sampler")] imports: Option<Res<WaveImportQueue>>,
+    #[cfg(feature = "sampler")] pending_samplers: Query<(), With<PendingSamplerLoad>>,
+    #[cfg(all(feature = "plugin", feature = "vst2"))] pending_vst2: Query<
+        (),
+        With<PendingVst2Build>,
+    >,
+) {
+    let now = Instant::now();
+    if let Some(previous) = last_frame.replace(now) {
+        let dt = now.duration_since(previous).as_secs_f64();
+        if dt > 0.0 {
+            let commits = if dirty.0 { 1.0 } else { 0.0 };
+            diagnostics
+                .add_measurement(&TuttiDiagnosticsPlugin::COMMITS_PER_SECOND, || commits / dt);
+        }
+    }
+
+    #[allow(unused_mut, reason = "only feature-gated sources add to it")]
+    let mut pending = 0usize;
+    #[cfg(feature = "sampler")]
+    {
+        pending += imports.map_or(0, |queue| queue.imports.len());
+        pending += pending_samplers.iter().count();
+    }
+    #[cfg(all(feature = "plugin", feature = "vst2"))]
+    {
+        pending += pending_vst2.iter().count();
+    }
+    diagnostics.add_measurement(&TuttiDiagnosticsPlugin::PENDING_RECONCILE, || {
+        pending as f64
+    });
+}
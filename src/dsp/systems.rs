@@ -6,14 +6,16 @@
 
 use bevy_ecs::prelude::*;
 
-use tutti::core::ecs::{AudioNode, Frequency, ModDepth, NodeKind};
 #[cfg(feature = "dsp")]
 use tutti::core::ecs::{
-    Attack, CompressorRatio, DelayTime, Feedback, FilterQ, GainDb, ModRate, Release,
-    ReverbDamping, ReverbRoomSize, ThresholdDb, WetMix,
+    Attack, CompressorRatio, DelayTime, Feedback, FilterQ, GainDb, ModRate, Release, ReverbDamping,
+    ReverbRoomSize, ThresholdDb, WetMix,
 };
+use tutti::core::ecs::{AudioNode, Frequency, ModDepth, NodeKind};
 
 use crate::graph::reconcile::GraphDirty;
+#[cfg(feature = "dsp")]
+use crate::graph::rehydrate::AudioNodeRecipe;
 use crate::resources::{TransportRes, TuttiGraphRes};
 
 use super::components::AddLfo;
@@ -44,6 +46,7 @@ pub fn dsp_compressor_system(
         commands.entity(entity).remove::<AddCompressor>().insert((
             AudioNode(node_id),
             NodeKind::Compressor,
+            AudioNodeRecipe::from(add),
             ThresholdDb(add.threshold_db),
             CompressorRatio(add.ratio),
             Attack(add.attack),
@@ -79,6 +82,7 @@ pub fn dsp_gate_system(
         commands.entity(entity).remove::<AddGate>().insert((
             AudioNode(node_id),
             NodeKind::Gate,
+            AudioNodeRecipe::from(add),
             ThresholdDb(add.threshold_db),
             Attack(add.attack),
             Release(add.release),
@@ -155,6 +159,7 @@ pub fn dsp_filter_system(
         commands.entity(entity).remove::<AddFilter>().insert((
             AudioNode(node_id),
             NodeKind::Filter,
+            AudioNodeRecipe::from(add),
             Frequency(add.frequency),
             FilterQ(add.q),
             GainDb(add.gain_db),
@@ -188,6 +193,7 @@ pub fn dsp_reverb_system(
         commands.entity(entity).remove::<AddReverb>().insert((
             AudioNode(node_id),
             NodeKind::Reverb,
+            AudioNodeRecipe::from(add),
             ReverbRoomSize(add.room_size),
             ReverbDamping(add.damping),
             WetMix(add.wet),
@@ -220,6 +226,7 @@ pub fn dsp_delay_system(
         commands.entity(entity).remove::<AddDelay>().insert((
             AudioNode(node_id),
             NodeKind::Delay,
+            AudioNodeRecipe::from(add),
             DelayTime(add.delay_time_secs),
             Feedback(add.feedback),
             WetMix(add.wet),
@@ -250,6 +257,7 @@ pub fn dsp_chorus_system(
         commands.entity(entity).remove::<AddChorus>().insert((
             AudioNode(node_id),
             NodeKind::Chorus,
+            AudioNodeRecipe::from(add),
             ModRate(add.rate_hz),
             ModDepth(add.depth_secs),
             Feedback(add.feedback),
@@ -0,0 +1,203 @@
+//! Engine start status + retry trigger.
+//!
+//! `TuttiPlugin::build` tries to start the engine once. When that fails
+//! (no sound card, device busy, headset unplugged) none of the
+//! per-subsystem resources exist and every system quietly no-ops. The
+//! [`AudioEngineStatus`] resource records why, and a [`RetryAudioEngine`]
+//! trigger rebuilds the engine later with the same settings.
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+use crate::device_state::{refresh_device_state, AudioDeviceState};
+use crate::plugin::{insert_engine_resources, EngineBuildSettings};
+use crate::resources::TuttiDriverRes;
+
+/// Whether the audio engine is up.
+///
+/// Inserted by `TuttiPlugin`. `Failed` carries the builder's error
+/// message so launchers can show something better than silence.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Resource, Clone)]
+pub enum AudioEngineStatus {
+    /// The engine was built and its resources are present.
+    Running,
+    /// The last build attempt failed; engine resources are absent.
+    Failed { error: String },
+    /// A rebuild was requested and runs at the start of the next frame.
+    Restarting,
+}
+
+impl AudioEngineStatus {
+    pub fn is_running(&self) -> bool {
+        matches!(self, Self::Running)
+    }
+
+    /// The error message from the last failed build, if any.
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            Self::Failed { error } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Trigger component: spawn an entity with this to rebuild a failed engine.
+///
+/// `retry_audio_engine_system` consumes it and flips
+/// [`AudioEngineStatus`] to `Restarting`; the rebuild itself happens in
+/// `PreUpdate` of the next frame on the main thread. Ignored while the
+/// engine is already running.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Default)]
+pub struct RetryAudioEngine;
+
+/// Processes [`RetryAudioEngine`] triggers.
+pub fn retry_audio_engine_system(
+    mut commands: Commands,
+    mut status: ResMut<AudioEngineStatus>,
+    query: Query<Entity, Added<RetryAudioEngine>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).remove::<RetryAudioEngine>();
+
+        match *status {
+            AudioEngineStatus::Running => {
+                bevy_log::info!("RetryAudioEngine ignored: engine is already running");
+            }
+            AudioEngineStatus::Restarting => {}
+            AudioEngineStatus::Failed { .. } => {
+                bevy_log::info!("Retrying Tutti Audio Engine start");
+                *status = AudioEngineStatus::Restarting;
+            }
+        }
+    }
+}
+
+/// Rebuilds the engine while [`AudioEngineStatus`] is `Restarting`.
+///
+/// Exclusive system: it inserts the non-send driver resource, which
+/// pins it to the main thread, and it needs `&mut World` to insert the
+/// per-subsystem resources the same way `TuttiPlugin::build` does.
+pub fn audio_engine_restart_system(world: &mut World) {
+    if !matches!(
+        world.get_resource::<AudioEngineStatus>(),
+        Some(AudioEngineStatus::Restarting)
+    ) {
+        return;
+    }
+    let Some(settings) = world.get_resource::<EngineBuildSettings>().cloned() else {
+        return;
+    };
+
+    match settings.build() {
+        Ok(engine) => {
+            insert_engine_resources(world, engine, &settings);
+            world.insert_resource(AudioEngineStatus::Running);
+
+            let state = world
+                .get_non_send_resource::<TuttiDriverRes>()
+                .map(|driver| {
+                    let mut state = world
+                        .get_resource::<AudioDeviceState>()
+                        .cloned()
+                        .unwrap_or_default();
+                    refresh_device_state(&driver.0, &mut state);
+                    state
+                });
+            if let Some(state) = state {
+                world.insert_resource(state);
+            }
+        }
+        Err(error) => {
+            bevy_log::error!("Failed to restart Tutti Audio Engine: {}", error);
+            world.insert_resource(AudioEngineStatus::Failed { error });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline::TuttiOfflineClock;
+    use crate::plugin::HeadlessConfig;
+    use crate::resources::TuttiGraphRes;
+    use bevy_app::{App, PreUpdate, Update};
+
+    fn failed_app() -> App {
+        let mut app = App::new();
+        app.insert_resource(AudioEngineStatus::Failed {
+            error: "no output device".into(),
+        });
+        app.insert_resource(EngineBuildSettings {
+            output_device: None,
+            inputs: 0,
+            outputs: 2,
+            enable_midi: false,
+            headless: Some(HeadlessConfig {
+                sample_rate: 48_000.0,
+                block_size: 64,
+            }),
+            #[cfg(feature = "mpe")]
+            mpe_mode: None,
+        });
+        app.add_systems(PreUpdate, audio_engine_restart_system);
+        app.add_systems(Update, retry_audio_engine_system);
+        app
+    }
+
+    #[test]
+    fn retry_after_failure_brings_engine_up() {
+        let mut app = failed_app();
+        app.update();
+        assert!(app.world().get_resource::<TuttiGraphRes>().is_none());
+
+        app.world_mut().spawn(RetryAudioEngine);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<AudioEngineStatus>(),
+            AudioEngineStatus::Restarting
+        );
+
+        app.update();
+        assert!(app.world().resource::<AudioEngineStatus>().is_running());
+        assert!(app.world().get_resource::<TuttiGraphRes>().is_some());
+        assert!(app.world().get_resource::<TuttiOfflineClock>().is_some());
+        assert!(app
+            .world()
+            .get_non_send_resource::<TuttiDriverRes>()
+            .is_some());
+
+        // A second trigger while running is a no-op.
+        app.world_mut().spawn(RetryAudioEngine);
+        app.update();
+        assert!(app.world().resource::<AudioEngineStatus>().is_running());
+    }
+
+    #[cfg(feature = "sampler")]
+    #[test]
+    fn audio_input_devices_are_listed_after_retry() {
+        use crate::audio_input::{AudioInputState, TuttiAudioInputPlugin};
+        use crate::resources::SamplerRes;
+
+        let mut app = failed_app();
+        app.add_plugins(TuttiAudioInputPlugin);
+        app.update();
+        assert!(app.world().get_resource::<SamplerRes>().is_none());
+
+        app.world_mut().spawn(RetryAudioEngine);
+        app.update();
+        app.update();
+        let expected = app
+            .world()
+            .resource::<SamplerRes>()
+            .0
+            .audio_input()
+            .list_input_devices()
+            .len();
+        assert_eq!(
+            app.world().resource::<AudioInputState>().devices.len(),
+            expected
+        );
+    }
+}
@@ -5,6 +5,7 @@ use bevy_ecs::prelude::*;
 use bevy_reflect::prelude::*;
 
 use crate::resources::{AudioConfig, TuttiGraphRes};
+use crate::tempo_map::TempoMap;
 
 /// Trigger component: spawn an entity with this to start an offline export.
 ///
@@ -39,6 +40,9 @@ impl StartExport {
         self
     }
 
+    /// Export `beats` from the start of the timeline. With a [`TempoMap`]
+    /// inserted the length follows its tempo changes and `tempo` is
+    /// ignored.
     pub fn duration_beats(mut self, beats: f64, tempo: f64) -> Self {
         self.duration_beats = Some((beats, tempo));
         self
@@ -78,6 +82,7 @@ pub fn export_start_system(
     mut commands: Commands,
     graph: Option<Res<TuttiGraphRes>>,
     config: Option<Res<AudioConfig>>,
+    tempo_map: Option<Res<TempoMap>>,
     query: Query<(Entity, &StartExport), Added<StartExport>>,
 ) {
     let Some(graph) = graph else { return };
@@ -91,7 +96,10 @@ pub fn export_start_system(
             builder = builder.duration_seconds(seconds);
         }
         if let Some((beats, tempo)) = start.duration_beats {
-            builder = builder.duration_beats(beats, tempo);
+            builder = match &tempo_map {
+                Some(map) => builder.duration_seconds(map.seconds_at(beats)),
+                None => builder.duration_beats(beats, tempo),
+            };
         }
         if let Some(format) = start.format {
             builder = builder.format(format);
@@ -0,0 +1,587 @@
+//! Summing buses and aux sends.
+//!
+//! [`AudioFeedsTo`](super::AudioFeedsTo) wires one output port into one
+//! input port, and an input port takes a single connection. A bus sums
+//! any number of sources instead: mark an entity with [`AudioBus`] and
+//! point sends at it with [`SendTo`].
+//!
+//! Each send is its own entity, a child of the source track, so a track
+//! can feed several returns at independent levels:
+//!
+//! ```rust,ignore
+//! let reverb = commands.spawn_audio_node(reverb_unit, NodeKind::Generic)
+//!     .insert(AudioBus)
+//!     .id();
+//! commands.entity(track).with_children(|sends| {
+//!     sends.spawn(SendTo::new(reverb, 0.3));
+//!     sends.spawn(SendTo::new(delay, 0.15).pre_fader());
+//! });
+//! ```
+//!
+//! On the audio thread every send is a small 4-in/2-out node: the tapped
+//! source scaled by the send level, plus whatever the previous send on
+//! the same bus produced. The sends on a bus form a chain whose last node
+//! feeds the bus's inputs 0/1, so adding a source never rebuilds the bus.
+//!
+//! - [`ensure_audio_buses`] — `Spawn`: gives bare [`AudioBus`] entities a
+//!   stereo pass-through node.
+//! - [`reconcile_sends`] — `Spawn`: builds send nodes, taps sources,
+//!   re-chains buses, removes the nodes of dropped sends.
+//! - [`reconcile_send_levels`] — `Params`: `Changed<SendTo>` → send gain.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy_ecs::entity::{EntityMapper, MapEntities};
+use bevy_ecs::message::MessageWriter;
+use bevy_ecs::prelude::*;
+use bevy_ecs::reflect::{ReflectComponent, ReflectMapEntities};
+use bevy_reflect::prelude::*;
+
+use tutti::core::ecs::{AudioNode, NodeKind};
+use tutti::dsp::{An, AudioNode as DspNode, Frame, Shared, U2, U4};
+
+use super::feedback::{report_blocked, AudioRoutingError, RoutedEdges};
+use super::reconcile::GraphDirty;
+use super::rehydrate::AudioNodeRecipe;
+use super::strip::{output_node, ChannelStrip};
+use crate::resources::TuttiGraphRes;
+
+/// Time constant for the send level smoothing, in seconds.
+const SEND_SMOOTHING_SECS: f32 = 0.005;
+
+/// Marks an entity as a summing bus: every [`SendTo`] pointing at it is
+/// mixed into its inputs 0/1.
+///
+/// Put it on an existing `AudioNode` entity (a reverb, a delay) to make
+/// that unit the return, or on a bare entity to get a stereo pass-through
+/// node that can carry `Volume`/`Pan`, a [`MeterTap`](super::MeterTap)
+/// and its own [`AudioFeedsTo`](super::AudioFeedsTo) onwards. Like any
+/// other node, pipe it to master with
+/// [`pipe_audio_node_output`](super::pipe_audio_node_output).
+///
+/// The sends own inputs 0/1; don't also wire `AudioFeedsTo` into those
+/// ports. A bus whose unit has a single input receives the left channel.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Default)]
+pub struct AudioBus;
+
+/// "Send this entity's parent to `bus` at `level`."
+///
+/// Spawn as a child of the source (`ChildOf(track)`); one entity per
+/// send. Post-fader sends tap the source's [`ChannelStrip`] when it has
+/// one, so they follow `Volume`, `Pan` and `Mute`. Pre-fader sends tap
+/// the unit itself — samplers apply `Volume` inside the unit, so their
+/// pre-fader sends still follow it.
+///
+/// `level` is a linear gain, ramped on the audio thread. Drive it from an
+/// automation lane with `AutomationParam::SendLevel`.
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq)]
+#[relationship(relationship_target = BusSends)]
+#[reflect(Component, MapEntities)]
+pub struct SendTo {
+    /// The [`AudioBus`] entity this send feeds.
+    #[relationship]
+    pub bus: Entity,
+    /// Linear send gain.
+    pub level: f32,
+    /// Tap before the source's fader instead of after it.
+    pub pre_fader: bool,
+}
+
+impl SendTo {
+    /// A post-fader send at `level`.
+    pub fn new(bus: Entity, level: f32) -> Self {
+        Self {
+            bus,
+            level,
+            pre_fader: false,
+        }
+    }
+
+    /// Tap before the source's fader.
+    pub fn pre_fader(mut self) -> Self {
+        self.pre_fader = true;
+        self
+    }
+}
+
+impl MapEntities for SendTo {
+    fn map_entities<M: EntityMapper>(&mut self, mapper: &mut M) {
+        self.bus = mapper.get_mapped(self.bus);
+    }
+}
+
+/// Auto-maintained list of every send feeding this bus.
+///
+/// Bevy's relationship infrastructure keeps this in sync with
+/// [`SendTo`]; don't insert it manually.
+#[derive(Component, Debug, Default)]
+#[relationship_target(relationship = SendTo)]
+pub struct BusSends(Vec<Entity>);
+
+impl BusSends {
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// The graph node behind a [`SendTo`].
+///
+/// Inserted by [`reconcile_sends`]; don't insert it manually.
+///
+/// Not `Reflect`: `node` wraps a foreign fundsp `NodeId` and the level is
+/// a fundsp `Shared` atomic. Scenes rebuild it from [`SendTo`].
+#[derive(Component, Clone)]
+pub struct SendToNode {
+    /// The send's own graph node (4 inputs, 2 outputs).
+    pub node: tutti::NodeId,
+    level: Shared,
+}
+
+impl std::fmt::Debug for SendToNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendToNode")
+            .field("node", &self.node)
+            .field("level", &self.level.value())
+            .finish()
+    }
+}
+
+/// Audio-thread side of a send: `source * level + previous send`.
+#[derive(Clone)]
+struct SendNode {
+    level: Shared,
+    gain: f32,
+    coeff: f32,
+    sample_rate: f64,
+}
+
+impl SendNode {
+    fn new(level: Shared) -> Self {
+        let gain = level.value();
+        let mut node = Self {
+            level,
+            gain,
+            coeff: 0.0,
+            sample_rate: tutti::dsp::DEFAULT_SR,
+        };
+        node.update_coeff();
+        node
+    }
+
+    fn update_coeff(&mut self) {
+        let samples = SEND_SMOOTHING_SECS as f64 * self.sample_rate;
+        self.coeff = (1.0 - (-1.0 / samples.max(1.0)).exp()) as f32;
+    }
+}
+
+impl DspNode for SendNode {
+    const ID: u64 = 0x7475_7474_6973_6e64;
+    type Inputs = U4;
+    type Outputs = U2;
+
+    fn reset(&mut self) {
+        self.gain = self.level.value();
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.update_coeff();
+    }
+
+    #[inline]
+    fn tick(&mut self, input: &Frame<f32, Self::Inputs>) -> Frame<f32, Self::Outputs> {
+        self.gain += (self.level.value() - self.gain) * self.coeff;
+        [
+            input[0] * self.gain + input[2],
+            input[1] * self.gain + input[3],
+        ]
+        .into()
+    }
+}
+
+/// Gives [`AudioBus`] entities without an `AudioNode` a stereo
+/// pass-through node. Entities waiting on an [`AudioNodeRecipe`] are
+/// left for the rehydrate pass.
+pub fn ensure_audio_buses(
+    mut commands: Commands,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    mut dirty: ResMut<GraphDirty>,
+    query: Query<
+        Entity,
+        (
+            Added<AudioBus>,
+            Without<AudioNode>,
+            Without<AudioNodeRecipe>,
+        ),
+    >,
+) {
+    let Some(mut graph) = graph else { return };
+    for entity in query.iter() {
+        let id = graph.0.add(tutti::dsp::pass() | tutti::dsp::pass());
+        dirty.0 = true;
+        commands
+            .entity(entity)
+            .insert((AudioNode(id), NodeKind::Generic));
+    }
+}
+
+/// What [`reconcile_sends`] last wired for a send entity.
+#[derive(Debug, Clone, Copy)]
+pub struct TrackedSend {
+    node: tutti::NodeId,
+    source: Entity,
+    bus: Entity,
+    pre_fader: bool,
+}
+
+/// Reconciles [`SendTo`] into send nodes and bus chains.
+///
+/// New sends, and sends whose bus or `pre_fader` changed, get a
+/// [`SendToNode`] fed from their parent's tap point; sends whose source
+/// or bus has no `AudioNode` yet (or that aren't anyone's child) are
+/// deferred, with a one-time warning, and retried every frame. A
+/// post-fader send re-taps when its source gains a [`ChannelStrip`].
+/// Removed sends have their node removed. A send that would close a loop
+/// (the bus already feeds its source) is left out, reported once as an
+/// [`AudioRoutingError`] and retried every frame.
+///
+/// Every bus touched this frame is re-chained: its sends in entity order,
+/// each one's inputs 2/3 fed by the one before, the last feeding the
+/// bus's inputs 0/1.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Bevy systems take their dependencies as parameters"
+)]
+pub fn reconcile_sends(
+    mut commands: Commands,
+    mut tracked: Local<HashMap<Entity, TrackedSend>>,
+    mut deferred: Local<HashSet<Entity>>,
+    mut blocked: Local<HashSet<Entity>>,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    mut dirty: ResMut<GraphDirty>,
+    mut routed: ResMut<RoutedEdges>,
+    mut errors: MessageWriter<AudioRoutingError>,
+    changed: Query<Entity, Changed<SendTo>>,
+    sends: Query<(Entity, &SendTo, Option<&ChildOf>)>,
+    nodes: Query<(&AudioNode, Option<Ref<ChannelStrip>>)>,
+    mut removed: RemovedComponents<SendTo>,
+) {
+    let Some(mut graph) = graph else {
+        for entity in removed.read() {
+            tracked.remove(&entity);
+            deferred.remove(&entity);
+            blocked.remove(&entity);
+        }
+        return;
+    };
+
+    let mut rechain: HashSet<Entity> = HashSet::new();
+
+    for entity in removed.read() {
+        deferred.remove(&entity);
+        blocked.remove(&entity);
+        let Some(send) = tracked.remove(&entity) else {
+            continue;
+        };
+        routed.remove(send.source, send.bus);
+        if graph.0.contains(send.node) {
+            graph.0.remove(send.node);
+            dirty.0 = true;
+        }
+        rechain.insert(send.bus);
+        if let Ok(mut entity) = commands.get_entity(entity) {
+            entity.try_remove::<SendToNode>();
+        }
+    }
+
+    let mut candidates: Vec<Entity> = changed.iter().collect();
+    for entity in deferred.iter().chain(blocked.iter()).copied() {
+        if !candidates.contains(&entity) {
+            candidates.push(entity);
+        }
+    }
+    let mut restripped: HashSet<Entity> = HashSet::new();
+    for (entity, send, parent) in sends.iter() {
+        if send.pre_fader {
+            continue;
+        }
+        let strip_added = parent
+            .and_then(|p| nodes.get(p.parent()).ok())
+            .and_then(|(_, strip)| strip)
+            .is_some_and(|strip| strip.is_added());
+        if strip_added {
+            restripped.insert(entity);
+            if !candidates.contains(&entity) {
+                candidates.push(entity);
+            }
+        }
+    }
+
+    for entity in candidates {
+        let Ok((_, send, parent)) = sends.get(entity) else {
+            deferred.remove(&entity);
+            blocked.remove(&entity);
+            continue;
+        };
+        let previous = tracked.get(&entity).copied();
+        if previous.is_some_and(|p| {
+            p.bus == send.bus && p.pre_fader == send.pre_fader && graph.0.contains(p.node)
+        }) && !restripped.contains(&entity)
+        {
+            // Level-only change; `reconcile_send_levels` handles it.
+            continue;
+        }
+        let source = parent.map(|p| p.parent());
+        let (Some(source), Some(Ok((src_node, src_strip))), Ok((bus_node, _))) =
+            (source, source.map(|s| nodes.get(s)), nodes.get(send.bus))
+        else {
+            if deferred.insert(entity) {
+                bevy_log::warn!(
+                    "SendTo: {:?} -> {:?} has no parent AudioNode or a bus without AudioNode; deferring",
+                    entity,
+                    send.bus
+                );
+            }
+            continue;
+        };
+        deferred.remove(&entity);
+        if graph.0.inputs(bus_node.0) == 0 {
+            bevy_log::warn!(
+                "SendTo: bus {:?} has no audio inputs; skipping send {:?}",
+                send.bus,
+                entity
+            );
+            continue;
+        }
+        if let Some(previous) = previous {
+            routed.remove(previous.source, previous.bus);
+        }
+        if let Err(error) = routed.check(source, send.bus) {
+            report_blocked(&mut blocked, entity, error, &mut errors);
+            if let Some(previous) = tracked.remove(&entity) {
+                if graph.0.contains(previous.node) {
+                    graph.0.remove(previous.node);
+                    dirty.0 = true;
+                }
+                rechain.insert(previous.bus);
+                commands.entity(entity).remove::<SendToNode>();
+            }
+            continue;
+        }
+        blocked.remove(&entity);
+        routed.insert(source, send.bus);
+
+        let node = match previous {
+            Some(previous) if graph.0.contains(previous.node) => previous.node,
+            _ => {
+                let level = Shared::new(send.level);
+                let node = graph.0.add(An(SendNode::new(level.clone())));
+                commands.entity(entity).insert(SendToNode { node, level });
+                node
+            }
+        };
+
+        let tap = if send.pre_fader {
+            src_node.0
+        } else {
+            output_node(src_node, src_strip.as_deref())
+        };
+        let outputs = graph.0.outputs(tap);
+        if outputs == 0 {
+            bevy_log::warn!(
+                "SendTo: source of {:?} has no audio outputs; send is silent",
+                entity
+            );
+        } else {
+            graph.0.connect(tap, 0, node, 0);
+            graph
+                .0
+                .connect(tap, if outputs == 1 { 0 } else { 1 }, node, 1);
+        }
+        dirty.0 = true;
+
+        if let Some(previous) = previous {
+            rechain.insert(previous.bus);
+        }
+        rechain.insert(send.bus);
+        tracked.insert(
+            entity,
+            TrackedSend {
+                node,
+                source,
+                bus: send.bus,
+                pre_fader: send.pre_fader,
+            },
+        );
+    }
+
+    for bus in rechain {
+        let Ok((bus_node, _)) = nodes.get(bus) else {
+            continue;
+        };
+        if !graph.0.contains(bus_node.0) {
+            continue;
+        }
+        let mut chain: Vec<(Entity, tutti::NodeId)> = tracked
+            .iter()
+            .filter(|(_, send)| send.bus == bus && graph.0.contains(send.node))
+            .map(|(entity, send)| (*entity, send.node))
+            .collect();
+        chain.sort_by_key(|(entity, _)| *entity);
+
+        let mut previous: Option<tutti::NodeId> = None;
+        for &(_, node) in &chain {
+            match previous {
+                Some(prev) => {
+                    graph.0.connect(prev, 0, node, 2);
+                    graph.0.connect(prev, 1, node, 3);
+                }
+                None => {
+                    graph.0.disconnect(node, 2);
+                    graph.0.disconnect(node, 3);
+                }
+            }
+            previous = Some(node);
+        }
+
+        let bus_inputs = graph.0.inputs(bus_node.0).min(2);
+        for port in 0..bus_inputs {
+            match previous {
+                Some(last) => graph.0.connect(last, port, bus_node.0, port),
+                None => graph.0.disconnect(bus_node.0, port),
+            }
+        }
+        dirty.0 = true;
+    }
+}
+
+/// Reconciles `Changed<SendTo>` into the send node's level. No graph
+/// mutation — the level is an atomic — so `GraphDirty` stays untouched.
+pub fn reconcile_send_levels(changed: Query<(&SendTo, &SendToNode), Changed<SendTo>>) {
+    for (send, node) in changed.iter() {
+        node.level.set_value(send.level);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::reconcile::{
+        commit_graph, reconcile_node_despawn, GraphReconcileSystems, SpawnAudioNode,
+    };
+    use bevy_app::App;
+    use tutti::dsp::sine_hz;
+    use tutti::TuttiEngine;
+
+    fn test_app() -> App {
+        let engine = TuttiEngine::builder()
+            .inputs(0)
+            .outputs(2)
+            .build()
+            .expect("build engine");
+        let TuttiEngine { graph, .. } = engine;
+
+        let mut app = App::new();
+        app.insert_resource(crate::resources::TuttiGraphRes(graph));
+        app.init_resource::<GraphDirty>()
+            .init_resource::<RoutedEdges>()
+            .add_message::<AudioRoutingError>();
+        app.configure_sets(
+            bevy_app::Update,
+            (
+                GraphReconcileSystems::Spawn,
+                GraphReconcileSystems::Params,
+                GraphReconcileSystems::Despawn,
+                GraphReconcileSystems::Commit,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            bevy_app::Update,
+            (
+                ensure_audio_buses.in_set(GraphReconcileSystems::Spawn),
+                reconcile_sends
+                    .after(ensure_audio_buses)
+                    .in_set(GraphReconcileSystems::Spawn),
+                reconcile_send_levels.in_set(GraphReconcileSystems::Params),
+                reconcile_node_despawn.in_set(GraphReconcileSystems::Despawn),
+                commit_graph.in_set(GraphReconcileSystems::Commit),
+            ),
+        );
+        app
+    }
+
+    fn graph_contains(app: &App, node: tutti::NodeId) -> bool {
+        app.world()
+            .resource::<crate::resources::TuttiGraphRes>()
+            .0
+            .contains(node)
+    }
+
+    #[test]
+    fn bare_bus_gets_pass_through_node() {
+        let mut app = test_app();
+        let bus = app.world_mut().spawn(AudioBus).id();
+        app.update();
+        let node = app.world().get::<AudioNode>(bus).expect("AudioNode").0;
+        assert!(graph_contains(&app, node));
+    }
+
+    #[test]
+    fn sends_build_nodes_follow_level_and_clean_up() {
+        let mut app = test_app();
+        let bus = app.world_mut().spawn(AudioBus).id();
+        let track = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
+            .id();
+        let first = app
+            .world_mut()
+            .spawn((SendTo::new(bus, 0.5), ChildOf(track)))
+            .id();
+        let second = app
+            .world_mut()
+            .spawn((SendTo::new(bus, 0.25).pre_fader(), ChildOf(track)))
+            .id();
+        app.update();
+        app.update();
+
+        assert_eq!(app.world().get::<BusSends>(bus).map(|s| s.len()), Some(2));
+        let first_node = app
+            .world()
+            .get::<SendToNode>(first)
+            .expect("SendToNode")
+            .clone();
+        assert!(graph_contains(&app, first_node.node));
+        assert!((first_node.level.value() - 0.5).abs() < 1e-6);
+        assert!(app.world().get::<SendToNode>(second).is_some());
+
+        app.world_mut().get_mut::<SendTo>(first).unwrap().level = 0.75;
+        app.update();
+        assert!((first_node.level.value() - 0.75).abs() < 1e-6);
+
+        app.world_mut().despawn(first);
+        app.update();
+        assert!(!graph_contains(&app, first_node.node));
+        assert!(app.world().get::<SendToNode>(second).is_some());
+    }
+
+    #[test]
+    fn orphan_send_is_deferred_without_panic() {
+        let mut app = test_app();
+        let bus = app.world_mut().spawn(AudioBus).id();
+        let send = app.world_mut().spawn(SendTo::new(bus, 1.0)).id();
+        app.update();
+        assert!(app.world().get::<SendToNode>(send).is_none());
+    }
+}
@@ -0,0 +1,519 @@
+//! Cycle detection for audio routing, and explicit feedback edges.
+//!
+//! The graph must stay acyclic: fundsp's `Net` can't order a loop. Every
+//! routing reconciler ([`AudioFeedsTo`](super::AudioFeedsTo),
+//! [`AudioConnections`](super::AudioConnections),
+//! [`SidechainOf`](super::SidechainOf), [`SendTo`](super::SendTo)) records
+//! the entity-to-entity edges it wires in [`RoutedEdges`] and, before
+//! wiring a new one, checks that it wouldn't close a loop. A link that
+//! would is left unwired, logged, and reported once as an
+//! [`AudioRoutingError`]; it's retried every frame and connects as soon
+//! as the loop is broken elsewhere.
+//!
+//! Intentional feedback (dub delays, modular patches) goes through a
+//! [`FeedbackEdge`] instead. It isn't a graph edge at all: the source
+//! writes into a ring buffer from a sink node, and a separate source node
+//! plays it back into the target `delay_samples` later. At least one
+//! block of delay is needed for that to be well-defined, so shorter
+//! delays are raised to [`MIN_FEEDBACK_DELAY`].
+//!
+//! - [`reconcile_feedback_edges`] — `Spawn`: builds, rebuilds and removes
+//!   the writer/reader node pairs.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use bevy_ecs::entity::{EntityMapper, MapEntities};
+use bevy_ecs::message::{Message, MessageWriter};
+use bevy_ecs::prelude::*;
+use bevy_ecs::reflect::{ReflectComponent, ReflectMapEntities};
+use bevy_reflect::Reflect;
+
+use tutti::core::ecs::AudioNode;
+use tutti::dsp::{An, AudioNode as DspNode, Frame, U0, U2};
+
+use super::reconcile::GraphDirty;
+use super::routing::{unwire_feed, wire_feed, AudioConnection, WiredFeed};
+use super::strip::{output_node, ChannelStrip};
+use crate::resources::TuttiGraphRes;
+
+/// Shortest [`FeedbackEdge`] delay: one fundsp block (`MAX_BUFFER_SIZE`).
+pub const MIN_FEEDBACK_DELAY: u32 = 64;
+
+/// Longest [`FeedbackEdge`] delay, about 22 s at 48 kHz. The ring buffer
+/// is allocated up front, so this bounds its size.
+pub const MAX_FEEDBACK_DELAY: u32 = 1 << 20;
+
+/// A routing link was rejected.
+#[derive(Event, Message, Clone, Debug, PartialEq, Eq)]
+pub enum AudioRoutingError {
+    /// Wiring `source` → `target` would close a loop. `path` is the
+    /// existing route from `target` back to `source`, both included.
+    /// Break the loop or use a [`FeedbackEdge`].
+    Cycle {
+        source: Entity,
+        target: Entity,
+        path: Vec<Entity>,
+    },
+}
+
+/// Entity-level audio edges currently wired by the routing reconcilers.
+///
+/// Read it to inspect the routing graph; the reconcilers keep it up to
+/// date. [`FeedbackEdge`]s aren't included — they don't constrain the
+/// graph's order.
+#[derive(Resource, Debug, Default)]
+pub struct RoutedEdges {
+    /// `from → (to → number of links)`.
+    edges: HashMap<Entity, HashMap<Entity, u32>>,
+}
+
+impl RoutedEdges {
+    pub(crate) fn insert(&mut self, from: Entity, to: Entity) {
+        *self.edges.entry(from).or_default().entry(to).or_default() += 1;
+    }
+
+    pub(crate) fn remove(&mut self, from: Entity, to: Entity) {
+        let Some(targets) = self.edges.get_mut(&from) else {
+            return;
+        };
+        if let Some(count) = targets.get_mut(&to) {
+            *count -= 1;
+            if *count == 0 {
+                targets.remove(&to);
+            }
+        }
+        if targets.is_empty() {
+            self.edges.remove(&from);
+        }
+    }
+
+    /// Entities `from` feeds directly.
+    pub fn targets(&self, from: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.edges
+            .get(&from)
+            .into_iter()
+            .flat_map(|targets| targets.keys().copied())
+    }
+
+    /// Shortest route from `from` to `to`, both included.
+    pub fn path(&self, from: Entity, to: Entity) -> Option<Vec<Entity>> {
+        if from == to {
+            return Some(vec![from]);
+        }
+        let mut came_from: HashMap<Entity, Entity> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(entity) = queue.pop_front() {
+            for next in self.targets(entity) {
+                if next == from || came_from.contains_key(&next) {
+                    continue;
+                }
+                came_from.insert(next, entity);
+                if next == to {
+                    let mut path = vec![to];
+                    let mut at = to;
+                    while let Some(&previous) = came_from.get(&at) {
+                        path.push(previous);
+                        at = previous;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                queue.push_back(next);
+            }
+        }
+        None
+    }
+
+    /// `Err` when wiring `source` → `target` would close a loop.
+    pub fn check(&self, source: Entity, target: Entity) -> Result<(), AudioRoutingError> {
+        match self.path(target, source) {
+            Some(path) => Err(AudioRoutingError::Cycle {
+                source,
+                target,
+                path,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Logs and reports a rejected link the first time `key` is blocked.
+pub(crate) fn report_blocked<K: std::hash::Hash + Eq>(
+    blocked: &mut HashSet<K>,
+    key: K,
+    error: AudioRoutingError,
+    errors: &mut MessageWriter<AudioRoutingError>,
+) {
+    if blocked.insert(key) {
+        let AudioRoutingError::Cycle {
+            source,
+            target,
+            path,
+        } = &error;
+        bevy_log::warn!(
+            "Audio routing: {:?} -> {:?} would close a loop ({:?}); not connecting",
+            source,
+            target,
+            path
+        );
+        errors.write(error);
+    }
+}
+
+/// "Feed this entity's audio back into `target`, `delay_samples` later."
+///
+/// Insert on the *source* entity. Unlike [`AudioFeedsTo`](super::AudioFeedsTo)
+/// it may point upstream: the delayed signal re-enters the graph from a
+/// node of its own, so no loop forms. The source's outputs 0/1 (its
+/// [`ChannelStrip`] when it has one, mono duplicated) reach `target`'s
+/// inputs from `dst_port`, with the same channel adaptation as
+/// [`AudioFeedsTo::stereo`](super::AudioFeedsTo::stereo).
+///
+/// There's no gain on the edge; set the loop gain with the source's
+/// `Volume` (keep it below 1). `delay_samples` is clamped to
+/// [`MIN_FEEDBACK_DELAY`]`..=`[`MAX_FEEDBACK_DELAY`].
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[relationship(relationship_target = FeedbackSources)]
+#[reflect(Component, MapEntities)]
+pub struct FeedbackEdge {
+    /// The entity the delayed audio feeds.
+    #[relationship]
+    pub target: Entity,
+    pub delay_samples: u32,
+    /// First input port on the target.
+    pub dst_port: u32,
+}
+
+impl FeedbackEdge {
+    pub fn new(target: Entity, delay_samples: u32) -> Self {
+        Self {
+            target,
+            delay_samples,
+            dst_port: 0,
+        }
+    }
+
+    pub fn into_port(mut self, dst_port: u32) -> Self {
+        self.dst_port = dst_port;
+        self
+    }
+
+    fn delay(&self) -> u32 {
+        self.delay_samples
+            .clamp(MIN_FEEDBACK_DELAY, MAX_FEEDBACK_DELAY)
+    }
+}
+
+impl MapEntities for FeedbackEdge {
+    fn map_entities<M: EntityMapper>(&mut self, mapper: &mut M) {
+        self.target = mapper.get_mapped(self.target);
+    }
+}
+
+/// Auto-maintained list of every entity feeding back into this one.
+///
+/// Bevy's relationship infrastructure keeps this in sync with
+/// [`FeedbackEdge`]; don't insert it manually.
+#[derive(Component, Debug, Default)]
+#[relationship_target(relationship = FeedbackEdge)]
+pub struct FeedbackSources(Vec<Entity>);
+
+impl FeedbackSources {
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Stereo ring buffer shared by a feedback writer and reader.
+///
+/// Both nodes advance one frame per sample and run in the same block, in
+/// either order. With at least a block of delay, the reader only ever
+/// reads frames the writer finished in an earlier block, and the buffer
+/// holds `delay + 2 blocks` so those frames aren't overwritten yet.
+struct FeedbackLine {
+    frames: Vec<[AtomicU32; 2]>,
+    delay: u64,
+    written: AtomicU64,
+    read: AtomicU64,
+}
+
+impl FeedbackLine {
+    fn new(delay: u32) -> Self {
+        let len = delay as usize + 2 * MIN_FEEDBACK_DELAY as usize;
+        Self {
+            frames: (0..len)
+                .map(|_| [AtomicU32::new(0), AtomicU32::new(0)])
+                .collect(),
+            delay: delay as u64,
+            written: AtomicU64::new(0),
+            read: AtomicU64::new(0),
+        }
+    }
+
+    fn frame(&self, index: u64) -> &[AtomicU32; 2] {
+        &self.frames[(index % self.frames.len() as u64) as usize]
+    }
+}
+
+/// Audio-thread sink: writes the source into the line.
+#[derive(Clone)]
+struct FeedbackWriter {
+    line: Arc<FeedbackLine>,
+}
+
+impl DspNode for FeedbackWriter {
+    const ID: u64 = 0x7475_7474_6662_7772;
+    type Inputs = U2;
+    type Outputs = U0;
+
+    #[inline]
+    fn tick(&mut self, input: &Frame<f32, Self::Inputs>) -> Frame<f32, Self::Outputs> {
+        let index = self.line.written.load(Ordering::Relaxed);
+        let frame = self.line.frame(index);
+        frame[0].store(input[0].to_bits(), Ordering::Relaxed);
+        frame[1].store(input[1].to_bits(), Ordering::Relaxed);
+        self.line.written.store(index + 1, Ordering::Relaxed);
+        Frame::default()
+    }
+}
+
+/// Audio-thread source: plays the line back `delay` frames late.
+#[derive(Clone)]
+struct FeedbackReader {
+    line: Arc<FeedbackLine>,
+}
+
+impl DspNode for FeedbackReader {
+    const ID: u64 = 0x7475_7474_6662_7264;
+    type Inputs = U0;
+    type Outputs = U2;
+
+    #[inline]
+    fn tick(&mut self, _input: &Frame<f32, Self::Inputs>) -> Frame<f32, Self::Outputs> {
+        let index = self.line.read.load(Ordering::Relaxed);
+        self.line.read.store(index + 1, Ordering::Relaxed);
+        if index < self.line.delay {
+            return Frame::default();
+        }
+        let frame = self.line.frame(index - self.line.delay);
+        [
+            f32::from_bits(frame[0].load(Ordering::Relaxed)),
+            f32::from_bits(frame[1].load(Ordering::Relaxed)),
+        ]
+        .into()
+    }
+}
+
+/// What [`reconcile_feedback_edges`] built for a source entity.
+#[derive(Debug, Clone)]
+pub struct TrackedFeedback {
+    edge: FeedbackEdge,
+    writer: tutti::NodeId,
+    reader: tutti::NodeId,
+    wired: Option<WiredFeed>,
+}
+
+fn remove_feedback(
+    graph: &mut TuttiGraphRes,
+    tracked: &TrackedFeedback,
+    target_id: Option<tutti::NodeId>,
+) {
+    if let Some(wired) = &tracked.wired {
+        unwire_feed(graph, wired, target_id);
+    }
+    for node in [tracked.writer, tracked.reader] {
+        if graph.0.contains(node) {
+            graph.0.remove(node);
+        }
+    }
+}
+
+/// Reconciles [`FeedbackEdge`] into a writer/reader node pair.
+///
+/// `Changed<FeedbackEdge>` (including `Added`) tears down whatever the
+/// edge built before and builds it again: a writer fed from the source's
+/// output node and a reader wired into the target. Removing the edge
+/// removes both nodes. Edges whose endpoints have no `AudioNode` yet are
+/// deferred and retried every frame; a source that gains a
+/// [`ChannelStrip`] or either endpoint getting a new `AudioNode` rebuilds
+/// the edge.
+#[allow(
+    clippy::type_complexity,
+    reason = "Bevy queries are tuple-shaped by design"
+)]
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Bevy systems take their dependencies as parameters"
+)]
+pub fn reconcile_feedback_edges(
+    mut tracked: Local<HashMap<Entity, TrackedFeedback>>,
+    mut deferred: Local<HashSet<Entity>>,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    mut dirty: ResMut<GraphDirty>,
+    changed: Query<Entity, Changed<FeedbackEdge>>,
+    restripped: Query<Entity, (Added<ChannelStrip>, With<FeedbackEdge>)>,
+    renoded: Query<Entity, Changed<AudioNode>>,
+    edges: Query<(Entity, &FeedbackEdge)>,
+    nodes: Query<(&AudioNode, Option<&ChannelStrip>)>,
+    mut removed: RemovedComponents<FeedbackEdge>,
+) {
+    let Some(mut graph) = graph else {
+        for entity in removed.read() {
+            tracked.remove(&entity);
+            deferred.remove(&entity);
+        }
+        return;
+    };
+    let target_id = |target: Entity| nodes.get(target).ok().map(|(n, _)| n.0);
+
+    for entity in removed.read() {
+        deferred.remove(&entity);
+        if let Some(previous) = tracked.remove(&entity) {
+            remove_feedback(&mut graph, &previous, target_id(previous.edge.target));
+            dirty.0 = true;
+        }
+    }
+
+    let renoded: HashSet<Entity> = renoded.iter().collect();
+    let mut candidates: Vec<Entity> = changed.iter().collect();
+    for entity in restripped.iter().chain(deferred.iter().copied()) {
+        if !candidates.contains(&entity) {
+            candidates.push(entity);
+        }
+    }
+    if !renoded.is_empty() {
+        for (entity, edge) in edges.iter() {
+            let touched = renoded.contains(&entity) || renoded.contains(&edge.target);
+            if touched && !candidates.contains(&entity) {
+                candidates.push(entity);
+            }
+        }
+    }
+
+    for src_entity in candidates {
+        let Ok((_, edge)) = edges.get(src_entity) else {
+            deferred.remove(&src_entity);
+            continue;
+        };
+        if let Some(previous) = tracked.remove(&src_entity) {
+            remove_feedback(&mut graph, &previous, target_id(previous.edge.target));
+            dirty.0 = true;
+        }
+        let (Ok((src_node, src_strip)), Some(dst_id)) =
+            (nodes.get(src_entity), target_id(edge.target))
+        else {
+            if deferred.insert(src_entity) {
+                bevy_log::warn!(
+                    "FeedbackEdge: {:?} -> {:?} has an endpoint without AudioNode; deferring",
+                    src_entity,
+                    edge.target
+                );
+            }
+            continue;
+        };
+        deferred.remove(&src_entity);
+
+        let src_id = output_node(src_node, src_strip);
+        let outputs = graph.0.outputs(src_id);
+        if outputs == 0 {
+            bevy_log::warn!(
+                "FeedbackEdge: {:?} has no audio outputs; skipping",
+                src_entity
+            );
+            continue;
+        }
+        let line = Arc::new(FeedbackLine::new(edge.delay()));
+        let writer = graph.0.add(An(FeedbackWriter { line: line.clone() }));
+        let reader = graph.0.add(An(FeedbackReader { line }));
+        graph.0.connect(src_id, 0, writer, 0);
+        graph
+            .0
+            .connect(src_id, if outputs == 1 { 0 } else { 1 }, writer, 1);
+        let link = AudioConnection {
+            dst_port: edge.dst_port,
+            ..AudioConnection::stereo(edge.target)
+        };
+        let wired = wire_feed(&mut graph, src_entity, reader, dst_id, &link);
+        tracked.insert(
+            src_entity,
+            TrackedFeedback {
+                edge: *edge,
+                writer,
+                reader,
+                wired,
+            },
+        );
+        dirty.0 = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities(n: usize) -> Vec<Entity> {
+        let mut world = World::new();
+        (0..n).map(|_| world.spawn_empty().id()).collect()
+    }
+
+    #[test]
+    fn check_rejects_loops_and_reports_path() {
+        let e = entities(4);
+        let mut edges = RoutedEdges::default();
+        edges.insert(e[0], e[1]);
+        edges.insert(e[1], e[2]);
+        assert!(edges.check(e[2], e[3]).is_ok());
+        assert_eq!(
+            edges.check(e[2], e[0]),
+            Err(AudioRoutingError::Cycle {
+                source: e[2],
+                target: e[0],
+                path: vec![e[0], e[1], e[2]],
+            })
+        );
+        // Self-loop.
+        assert!(edges.check(e[3], e[3]).is_err());
+
+        // Edges are counted: one of two parallel links going away keeps
+        // the route.
+        edges.insert(e[1], e[2]);
+        edges.remove(e[1], e[2]);
+        assert!(edges.check(e[2], e[0]).is_err());
+        edges.remove(e[1], e[2]);
+        assert!(edges.check(e[2], e[0]).is_ok());
+    }
+
+    #[test]
+    fn feedback_line_delays_by_whole_frames() {
+        let line = Arc::new(FeedbackLine::new(MIN_FEEDBACK_DELAY));
+        let mut writer = FeedbackWriter { line: line.clone() };
+        let mut reader = FeedbackReader { line };
+        let mut heard = Vec::new();
+        // Reader first in every block: the worst case.
+        for block in 0..3 {
+            for _ in 0..MIN_FEEDBACK_DELAY {
+                heard.push(reader.tick(&Frame::default())[0]);
+            }
+            for i in 0..MIN_FEEDBACK_DELAY {
+                let x = (block * MIN_FEEDBACK_DELAY + i) as f32;
+                writer.tick(&[x, -x].into());
+            }
+        }
+        assert!(heard[..MIN_FEEDBACK_DELAY as usize]
+            .iter()
+            .all(|&x| x == 0.0));
+        for (i, &x) in heard[MIN_FEEDBACK_DELAY as usize..].iter().enumerate() {
+            assert_eq!(x, i as f32);
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! Per-entity level metering: [`MeterTap`] → [`NodeMeterLevels`].
+//!
+//! [`MasterMeterLevels`](crate::MasterMeterLevels) only covers the master
+//! bus. Put a [`MeterTap`] on any `AudioNode` entity and
+//! [`ensure_meter_taps`] hangs a sink node off the entity's audible output
+//! (its [`ChannelStrip`] when it has one, so levels are post-fader). The
+//! sink measures every channel on the audio thread into lock-free atomics;
+//! [`sync_node_meter_levels`] reads them each frame and applies meter
+//! ballistics (peak falloff, peak hold) on the ECS side.
+//!
+//! - [`ensure_meter_taps`] — `Spawn`: builds taps, re-taps when a strip
+//!   appears.
+//! - [`sync_node_meter_levels`] — after `Commit`: atomics →
+//!   [`NodeMeterLevels`].
+//! - [`reconcile_meter_tap_despawn`] — `Despawn`: removes orphaned taps.
+
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+use tutti::core::ecs::AudioNode;
+use tutti::dsp::{An, AudioNode as DspNode, Frame, Size, U0, U1, U2, U3, U4, U5, U6, U7, U8};
+
+use super::reconcile::GraphDirty;
+use super::strip::{output_node, ChannelStrip};
+use crate::resources::TuttiGraphRes;
+
+/// Most channels a tap measures; wider outputs meter their first eight.
+pub const MAX_METER_CHANNELS: usize = 8;
+
+/// Time constant of the RMS average, in seconds.
+const METER_RMS_SECS: f64 = 0.3;
+
+/// Samples between publications from the audio thread (~5 ms at 48 kHz).
+const METER_PUBLISH_SAMPLES: u32 = 256;
+
+/// Opt-in: meter this entity's output into [`NodeMeterLevels`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Default, Clone)]
+pub struct MeterTap {
+    /// How long `peak_hold` stays up before dropping to the current peak.
+    pub hold_secs: f32,
+    /// How fast `peak` falls once the signal drops, in dB per second.
+    pub falloff_db_per_sec: f32,
+}
+
+impl Default for MeterTap {
+    fn default() -> Self {
+        Self {
+            hold_secs: 1.5,
+            falloff_db_per_sec: 24.0,
+        }
+    }
+}
+
+/// Per-channel levels of a [`MeterTap`]ped entity, linear amplitude.
+///
+/// `clip` latches when a channel reaches full scale and stays set until
+/// [`reset_clip`](Self::reset_clip).
+#[derive(Component, Debug, Default, Clone, PartialEq, Reflect)]
+#[reflect(Component, Default, Clone)]
+pub struct NodeMeterLevels {
+    pub peak: Vec<f32>,
+    pub rms: Vec<f32>,
+    pub peak_hold: Vec<f32>,
+    pub clip: Vec<bool>,
+}
+
+impl NodeMeterLevels {
+    fn new(channels: usize) -> Self {
+        Self {
+            peak: vec![0.0; channels],
+            rms: vec![0.0; channels],
+            peak_hold: vec![0.0; channels],
+            clip: vec![false; channels],
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        self.peak.len()
+    }
+
+    /// Loudest channel's peak.
+    pub fn max_peak(&self) -> f32 {
+        self.peak.iter().copied().fold(0.0, f32::max)
+    }
+
+    pub fn reset_clip(&mut self) {
+        self.clip.iter_mut().for_each(|c| *c = false);
+    }
+}
+
+#[derive(Default)]
+struct TapChannel {
+    /// Highest peak published since the ECS last read it (f32 bits; the
+    /// bit order of non-negative floats matches their numeric order, so
+    /// `fetch_max` works).
+    peak: AtomicU32,
+    rms: AtomicU32,
+    clip: AtomicBool,
+}
+
+/// Levels shared between a tap node and its [`MeterTapNode`].
+struct TapShared {
+    channels: Vec<TapChannel>,
+}
+
+impl TapShared {
+    fn new(channels: usize) -> Self {
+        Self {
+            channels: (0..channels).map(|_| TapChannel::default()).collect(),
+        }
+    }
+}
+
+/// Audio-thread side of a [`MeterTap`]: an `N`-input sink.
+#[derive(Clone)]
+struct TapNode<N: Size<f32>> {
+    shared: Arc<TapShared>,
+    window_peak: [f32; MAX_METER_CHANNELS],
+    mean_square: [f64; MAX_METER_CHANNELS],
+    coeff: f64,
+    counter: u32,
+    _channels: PhantomData<N>,
+}
+
+impl<N: Size<f32>> TapNode<N> {
+    fn new(shared: Arc<TapShared>) -> Self {
+        let mut node = Self {
+            shared,
+            window_peak: [0.0; MAX_METER_CHANNELS],
+            mean_square: [0.0; MAX_METER_CHANNELS],
+            coeff: 0.0,
+            counter: 0,
+            _channels: PhantomData,
+        };
+        node.set_sample_rate(tutti::dsp::DEFAULT_SR);
+        node
+    }
+
+    fn publish(&mut self) {
+        for (i, channel) in self.shared.channels.iter().enumerate() {
+            channel
+                .peak
+                .fetch_max(self.window_peak[i].to_bits(), Ordering::Relaxed);
+            channel.rms.store(
+                (self.mean_square[i].sqrt() as f32).to_bits(),
+                Ordering::Relaxed,
+            );
+            self.window_peak[i] = 0.0;
+        }
+    }
+}
+
+impl<N: Size<f32>> DspNode for TapNode<N> {
+    const ID: u64 = 0x7475_7474_6d65_7472;
+    type Inputs = N;
+    type Outputs = U0;
+
+    fn reset(&mut self) {
+        self.window_peak = [0.0; MAX_METER_CHANNELS];
+        self.mean_square = [0.0; MAX_METER_CHANNELS];
+        self.counter = 0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.coeff = 1.0 - (-1.0 / (METER_RMS_SECS * sample_rate).max(1.0)).exp();
+    }
+
+    #[inline]
+    fn tick(&mut self, input: &Frame<f32, Self::Inputs>) -> Frame<f32, Self::Outputs> {
+        for (i, &x) in input.iter().enumerate().take(MAX_METER_CHANNELS) {
+            let level = x.abs();
+            if level > self.window_peak[i] {
+                self.window_peak[i] = level;
+            }
+            if level >= 1.0 {
+                self.shared.channels[i].clip.store(true, Ordering::Relaxed);
+            }
+            let square = (x as f64) * (x as f64);
+            self.mean_square[i] += (square - self.mean_square[i]) * self.coeff;
+        }
+        self.counter += 1;
+        if self.counter >= METER_PUBLISH_SAMPLES {
+            self.counter = 0;
+            self.publish();
+        }
+        Frame::default()
+    }
+}
+
+/// The tap node behind a [`MeterTap`].
+///
+/// Inserted by [`ensure_meter_taps`]; don't insert it manually.
+///
+/// Not `Reflect`: holds the atomics shared with the audio thread.
+#[derive(Component)]
+pub struct MeterTapNode {
+    node: tutti::NodeId,
+    source: tutti::NodeId,
+    shared: Arc<TapShared>,
+    hold_left: Vec<f32>,
+}
+
+impl MeterTapNode {
+    pub fn node(&self) -> tutti::NodeId {
+        self.node
+    }
+}
+
+impl std::fmt::Debug for MeterTapNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeterTapNode")
+            .field("node", &self.node)
+            .field("source", &self.source)
+            .field("channels", &self.shared.channels.len())
+            .finish()
+    }
+}
+
+fn add_tap_node(
+    graph: &mut TuttiGraphRes,
+    channels: usize,
+    shared: Arc<TapShared>,
+) -> tutti::NodeId {
+    match channels {
+        1 => graph.0.add(An(TapNode::<U1>::new(shared))),
+        2 => graph.0.add(An(TapNode::<U2>::new(shared))),
+        3 => graph.0.add(An(TapNode::<U3>::new(shared))),
+        4 => graph.0.add(An(TapNode::<U4>::new(shared))),
+        5 => graph.0.add(An(TapNode::<U5>::new(shared))),
+        6 => graph.0.add(An(TapNode::<U6>::new(shared))),
+        7 => graph.0.add(An(TapNode::<U7>::new(shared))),
+        _ => graph.0.add(An(TapNode::<U8>::new(shared))),
+    }
+}
+
+/// Builds (or rebuilds) the tap for every [`MeterTap`] entity whose
+/// audible output changed: tap added, node spawned, or strip spliced in.
+///
+/// Runs in [`super::GraphReconcileSystems::Spawn`], after
+/// [`super::ensure_channel_strips`] so a strip built this frame is tapped
+/// straight away. Entities whose output has no channels are skipped with
+/// a warning.
+#[allow(
+    clippy::type_complexity,
+    reason = "Bevy queries are tuple-shaped by design"
+)]
+pub fn ensure_meter_taps(
+    mut commands: Commands,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    mut dirty: ResMut<GraphDirty>,
+    query: Query<
+        (
+            Entity,
+            &AudioNode,
+            Option<&ChannelStrip>,
+            Option<&MeterTapNode>,
+        ),
+        (
+            With<MeterTap>,
+            Or<(Added<MeterTap>, Added<AudioNode>, Added<ChannelStrip>)>,
+        ),
+    >,
+) {
+    let Some(mut graph) = graph else { return };
+
+    for (entity, node, strip, existing) in query.iter() {
+        let source = output_node(node, strip);
+        if existing.is_some_and(|tap| tap.source == source && graph.0.contains(tap.node)) {
+            continue;
+        }
+        if let Some(tap) = existing {
+            if graph.0.contains(tap.node) {
+                graph.0.remove(tap.node);
+                dirty.0 = true;
+            }
+        }
+
+        let outputs = graph.0.outputs(source);
+        if outputs == 0 {
+            bevy_log::warn!(
+                "MeterTap: {:?} has no audio outputs; nothing to meter",
+                entity
+            );
+            continue;
+        }
+        let channels = outputs.min(MAX_METER_CHANNELS);
+        let shared = Arc::new(TapShared::new(channels));
+        let tap_id = add_tap_node(&mut graph, channels, shared.clone());
+        for channel in 0..channels {
+            graph.0.connect(source, channel, tap_id, channel);
+        }
+        dirty.0 = true;
+
+        commands.entity(entity).insert((
+            MeterTapNode {
+                node: tap_id,
+                source,
+                shared,
+                hold_left: vec![0.0; channels],
+            },
+            NodeMeterLevels::new(channels),
+        ));
+    }
+}
+
+/// Reads every tap's atomics into its [`NodeMeterLevels`].
+///
+/// `peak` follows the loudest sample since the last frame and falls at
+/// [`MeterTap::falloff_db_per_sec`]; `peak_hold` keeps the highest peak
+/// for [`MeterTap::hold_secs`]. Wall-clock dt comes from a [`Local`]
+/// instant so hosts don't need `bevy_time`.
+pub fn sync_node_meter_levels(
+    mut last_tick: Local<Option<Instant>>,
+    mut query: Query<(&MeterTap, &mut MeterTapNode, &mut NodeMeterLevels)>,
+) {
+    let now = Instant::now();
+    let dt = last_tick.map_or(0.0, |last| now.duration_since(last).as_secs_f32());
+    *last_tick = Some(now);
+
+    for (tap, mut tap_node, mut levels) in query.iter_mut() {
+        let falloff = 10f32.powf(-tap.falloff_db_per_sec.max(0.0) * dt / 20.0);
+        let tap_node = &mut *tap_node;
+        for (i, channel) in tap_node.shared.channels.iter().enumerate() {
+            let peak = f32::from_bits(channel.peak.swap(0, Ordering::Relaxed));
+            levels.peak[i] = peak.max(levels.peak[i] * falloff);
+            levels.rms[i] = f32::from_bits(channel.rms.load(Ordering::Relaxed));
+            if channel.clip.swap(false, Ordering::Relaxed) {
+                levels.clip[i] = true;
+            }
+
+            tap_node.hold_left[i] -= dt;
+            if levels.peak[i] >= levels.peak_hold[i] {
+                levels.peak_hold[i] = levels.peak[i];
+                tap_node.hold_left[i] = tap.hold_secs;
+            } else if tap_node.hold_left[i] <= 0.0 {
+                levels.peak_hold[i] = levels.peak[i];
+            }
+        }
+    }
+}
+
+/// Removes an entity's tap node when its `AudioNode` or [`MeterTap`] goes
+/// away, and the tap components with the latter.
+///
+/// Mirrors [`super::reconcile_strip_despawn`]: `(Entity, NodeId)` pairs
+/// are tracked locally because removed components can't be read back.
+pub fn reconcile_meter_tap_despawn(
+    mut commands: Commands,
+    mut tracked: Local<std::collections::HashMap<Entity, tutti::NodeId>>,
+    changed: Query<(Entity, &MeterTapNode), Changed<MeterTapNode>>,
+    mut removed_nodes: RemovedComponents<AudioNode>,
+    mut removed_taps: RemovedComponents<MeterTap>,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    mut dirty: ResMut<GraphDirty>,
+) {
+    for (entity, tap) in changed.iter() {
+        tracked.insert(entity, tap.node);
+    }
+
+    let untapped: Vec<Entity> = removed_taps.read().collect();
+    for &entity in &untapped {
+        if let Ok(mut e) = commands.get_entity(entity) {
+            e.remove::<(MeterTapNode, NodeMeterLevels)>();
+        }
+    }
+
+    let gone: Vec<Entity> = removed_nodes.read().chain(untapped).collect();
+    let Some(mut graph) = graph else {
+        for entity in gone {
+            tracked.remove(&entity);
+        }
+        return;
+    };
+
+    for entity in gone {
+        if let Some(id) = tracked.remove(&entity) {
+            if graph.0.contains(id) {
+                graph.0.remove(id);
+                dirty.0 = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::reconcile::{
+        commit_graph, reconcile_node_despawn, GraphReconcileSystems, SpawnAudioNode,
+    };
+    use bevy_app::App;
+    use tutti::core::ecs::NodeKind;
+    use tutti::dsp::sine_hz;
+    use tutti::TuttiEngine;
+
+    #[test]
+    fn tap_node_publishes_peak_rms_and_clip() {
+        let shared = Arc::new(TapShared::new(2));
+        let mut node = TapNode::<U2>::new(shared.clone());
+        for i in 0..METER_PUBLISH_SAMPLES {
+            let left = if i == 10 { 0.5 } else { 0.25 };
+            node.tick(&[left, 1.5].into());
+        }
+        let peak = |ch: usize| f32::from_bits(shared.channels[ch].peak.load(Ordering::Relaxed));
+        assert_eq!(peak(0), 0.5);
+        assert_eq!(peak(1), 1.5);
+        assert!(!shared.channels[0].clip.load(Ordering::Relaxed));
+        assert!(shared.channels[1].clip.load(Ordering::Relaxed));
+        let rms = f32::from_bits(shared.channels[0].rms.load(Ordering::Relaxed));
+        assert!(rms > 0.0 && rms < 0.5);
+    }
+
+    #[test]
+    fn meter_tap_follows_output_and_despawn_removes_it() {
+        let engine = TuttiEngine::builder()
+            .inputs(0)
+            .outputs(2)
+            .build()
+            .expect("build engine");
+        let TuttiEngine { graph, .. } = engine;
+
+        let mut app = App::new();
+        app.insert_resource(TuttiGraphRes(graph));
+        app.init_resource::<GraphDirty>();
+        app.configure_sets(
+            bevy_app::Update,
+            (
+                GraphReconcileSystems::Spawn,
+                GraphReconcileSystems::Params,
+                GraphReconcileSystems::Despawn,
+                GraphReconcileSystems::Commit,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            bevy_app::Update,
+            (
+                ensure_meter_taps.in_set(GraphReconcileSystems::Spawn),
+                reconcile_node_despawn.in_set(GraphReconcileSystems::Despawn),
+                reconcile_meter_tap_despawn.in_set(GraphReconcileSystems::Despawn),
+                commit_graph.in_set(GraphReconcileSystems::Commit),
+                sync_node_meter_levels.after(GraphReconcileSystems::Commit),
+            ),
+        );
+
+        let entity = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
+            .insert(MeterTap::default())
+            .id();
+        app.update();
+        app.update();
+
+        let levels = app
+            .world()
+            .get::<NodeMeterLevels>(entity)
+            .expect("NodeMeterLevels");
+        assert_eq!(levels.channels(), 1);
+        let tap = app.world().get::<MeterTapNode>(entity).unwrap().node();
+        assert!(app.world().resource::<TuttiGraphRes>().0.contains(tap));
+
+        app.world_mut().despawn(entity);
+        app.update();
+        assert!(!app.world().resource::<TuttiGraphRes>().0.contains(tap));
+    }
+}
@@ -5,19 +5,37 @@
 //! to interleave their per-frame work between spawn → params → despawn → commit.
 //!
 //! Sub-concepts:
-//! - [`reconcile`] — `SpawnAudioNode` extension, `Volume`/`Pan`/`Mute` reconcile,
+//! - [`reconcile`] — `SpawnAudioNode` extension, `Volume`/`Mute` reconcile,
 //!   per-effect param reconcilers, `GraphReconcileSystems` ordering.
+//! - [`smoothing`] — `ParamSmoothing` policy and audio-clock ramps for
+//!   click-free parameter changes.
+//! - [`strip`] — per-entity `ChannelStrip` gain/pan node; `Volume`/`Mute`/`Pan`
+//!   for kinds without a typed gain setter.
+//! - [`meter_tap`] — `MeterTap` → per-entity `NodeMeterLevels`.
+//! - [`bus`] — `AudioBus` summing buses and `SendTo` aux sends.
 //! - [`sidechain`] — `SidechainOf` relationship → port-1 wiring.
-//! - [`routing`] — `AudioFeedsTo` relationship → general port-to-port wiring.
+//! - [`routing`] — `AudioFeedsTo` relationship and `AudioConnections` edge
+//!   lists → general port-to-port wiring.
+//! - [`feedback`] — cycle detection for the routing reconcilers
+//!   (`RoutedEdges`, `AudioRoutingError`) and `FeedbackEdge` delayed loops.
+//! - [`rehydrate`] — `AudioNodeRecipe` → node rebuild after a scene load.
+//! - [`scheduled_param`] — `ScheduledParamChange` → beat-timed parameter writes.
 //! - [`pending_load`] — sampler pending-load promotion (sampler-gated).
-//! - [`scheduled`] — time-delayed MIDI dispatch (midi-gated).
+//! - [`scheduled`] — time- and beat-scheduled MIDI dispatch (midi-gated).
 
 use bevy_app::{App, Plugin, Update};
 use bevy_ecs::prelude::*;
 
+pub mod bus;
+pub mod feedback;
+pub mod meter_tap;
 pub mod reconcile;
+pub mod rehydrate;
 pub mod routing;
+pub mod scheduled_param;
 pub mod sidechain;
+pub mod smoothing;
+pub mod strip;
 
 #[cfg(feature = "sampler")]
 pub mod pending_load;
@@ -38,25 +56,96 @@ pub use reconcile::{
     reconcile_filter_params, reconcile_gate_params,
 };
 
-pub use routing::{reconcile_audio_routing, AudioFedBy, AudioFeedsTo};
+pub use bus::{
+    ensure_audio_buses, reconcile_send_levels, reconcile_sends, AudioBus, BusSends, SendTo,
+    SendToNode,
+};
+pub use feedback::{
+    reconcile_feedback_edges, AudioRoutingError, FeedbackEdge, FeedbackSources, RoutedEdges,
+    MAX_FEEDBACK_DELAY, MIN_FEEDBACK_DELAY,
+};
+pub use meter_tap::{
+    ensure_meter_taps, reconcile_meter_tap_despawn, sync_node_meter_levels, MeterTap, MeterTapNode,
+    NodeMeterLevels,
+};
+#[cfg(feature = "plugin")]
+pub use rehydrate::refresh_plugin_recipe_state;
+pub use rehydrate::{rehydrate_audio_nodes, AudioNodeRecipe, FilterMode};
+pub use routing::{
+    reconcile_audio_connections, reconcile_audio_routing, AudioConnection, AudioConnections,
+    AudioFedBy, AudioFeedsTo, FeedPorts, WiredFeed,
+};
+pub use scheduled_param::{
+    collect_scheduled_param_changes, dispatch_scheduled_param_changes, ScheduleParamChange,
+    ScheduledParamChange, ScheduledParamQueue,
+};
 pub use sidechain::{reconcile_sidechain_links, SidechainOf, SidechainSources};
+pub use smoothing::{
+    sync_param_ramps, DefaultParamSmoothing, ParamRamps, ParamSmoother, ParamSmoothing,
+    ParamTarget, SmoothedParam, SmoothingCurve,
+};
+pub use strip::{
+    ensure_channel_strips, output_node, pan_gains, pipe_audio_node_output, reconcile_strip_despawn,
+    reconcile_strip_levels, ChannelStrip, WithGainStage,
+};
 
 #[cfg(feature = "sampler")]
 pub use pending_load::{
     poll_wave_imports, promote_pending_samplers, PendingSamplerLoad, WaveImportQueue,
 };
 #[cfg(feature = "midi")]
-pub use scheduled::{tick_scheduled_midi, MidiSynthMarker, ScheduledMidi};
+pub use scheduled::{tick_scheduled_midi, MidiSynthMarker, MidiTiming, ScheduledMidi};
 
 /// Bevy plugin: graph reconciliation pipeline.
 ///
 /// Runs the four-phase reconcile cycle every `Update`: `Spawn` → `Params`
 /// → `Despawn` → `Commit`. Other plugins hook into these sets to interleave
 /// their work.
-pub struct TuttiGraphPlugin;
+///
+/// `default_smoothing` seeds [`DefaultParamSmoothing`], the smoothing for
+/// entities without their own [`ParamSmoothing`]. A resource the app
+/// inserted before adding the plugin wins.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TuttiGraphPlugin {
+    pub default_smoothing: ParamSmoothing,
+}
+
+impl TuttiGraphPlugin {
+    pub fn with_default_smoothing(mut self, smoothing: ParamSmoothing) -> Self {
+        self.default_smoothing = smoothing;
+        self
+    }
+}
 
 impl Plugin for TuttiGraphPlugin {
     fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<DefaultParamSmoothing>() {
+            app.insert_resource(DefaultParamSmoothing(self.default_smoothing));
+        }
+        app.init_resource::<ParamRamps>()
+            .init_resource::<RoutedEdges>()
+            .add_message::<AudioRoutingError>();
+
+        app.register_type::<AudioNodeRecipe>()
+            .register_type::<FilterMode>()
+            .register_type::<AudioFeedsTo>()
+            .register_type::<FeedPorts>()
+            .register_type::<AudioConnection>()
+            .register_type::<AudioConnections>()
+            .register_type::<SidechainOf>()
+            .register_type::<FeedbackEdge>()
+            .register_type::<WithGainStage>()
+            .register_type::<ParamSmoothing>()
+            .register_type::<SmoothingCurve>()
+            .register_type::<SmoothedParam>()
+            .register_type::<DefaultParamSmoothing>()
+            .register_type::<ScheduledParamChange>()
+            .register_type::<ScheduledParamQueue>()
+            .register_type::<MeterTap>()
+            .register_type::<NodeMeterLevels>()
+            .register_type::<AudioBus>()
+            .register_type::<SendTo>();
+
         app.init_resource::<GraphDirty>().configure_sets(
             Update,
             (
@@ -72,10 +161,52 @@ impl Plugin for TuttiGraphPlugin {
             Update,
             (
                 reconcile_params.in_set(GraphReconcileSystems::Params),
+                reconcile_strip_levels.in_set(GraphReconcileSystems::Params),
                 reconcile_node_despawn.in_set(GraphReconcileSystems::Despawn),
+                reconcile_strip_despawn.in_set(GraphReconcileSystems::Despawn),
                 commit_graph.in_set(GraphReconcileSystems::Commit),
-                reconcile_sidechain_links.in_set(GraphReconcileSystems::Spawn),
-                reconcile_audio_routing.in_set(GraphReconcileSystems::Spawn),
+                rehydrate_audio_nodes
+                    .before(reconcile_sidechain_links)
+                    .before(reconcile_audio_routing)
+                    .before(reconcile_audio_connections)
+                    .before(reconcile_feedback_edges)
+                    .in_set(GraphReconcileSystems::Spawn),
+                reconcile_sidechain_links
+                    .after(ensure_channel_strips)
+                    .in_set(GraphReconcileSystems::Spawn),
+                reconcile_audio_routing
+                    .after(ensure_channel_strips)
+                    .in_set(GraphReconcileSystems::Spawn),
+                reconcile_audio_connections
+                    .after(ensure_channel_strips)
+                    .in_set(GraphReconcileSystems::Spawn),
+                reconcile_feedback_edges
+                    .after(ensure_channel_strips)
+                    .in_set(GraphReconcileSystems::Spawn),
+                ensure_channel_strips.in_set(GraphReconcileSystems::Spawn),
+                ensure_meter_taps
+                    .after(ensure_channel_strips)
+                    .in_set(GraphReconcileSystems::Spawn),
+                reconcile_meter_tap_despawn.in_set(GraphReconcileSystems::Despawn),
+                ensure_audio_buses
+                    .before(ensure_channel_strips)
+                    .in_set(GraphReconcileSystems::Spawn),
+                reconcile_sends
+                    .after(ensure_audio_buses)
+                    .after(ensure_channel_strips)
+                    .in_set(GraphReconcileSystems::Spawn),
+                reconcile_send_levels.in_set(GraphReconcileSystems::Params),
+                sync_node_meter_levels.after(GraphReconcileSystems::Commit),
+                sync_param_ramps
+                    .after(GraphReconcileSystems::Spawn)
+                    .before(GraphReconcileSystems::Params),
+                (
+                    collect_scheduled_param_changes,
+                    dispatch_scheduled_param_changes,
+                )
+                    .chain()
+                    .after(sync_param_ramps)
+                    .before(GraphReconcileSystems::Params),
             ),
         );
 
@@ -88,6 +219,10 @@ impl Plugin for TuttiGraphPlugin {
                     poll_wave_imports,
                     promote_pending_samplers
                         .after(poll_wave_imports)
+                        .before(reconcile_sidechain_links)
+                        .before(reconcile_audio_routing)
+                        .before(reconcile_audio_connections)
+                        .before(reconcile_feedback_edges)
                         .in_set(GraphReconcileSystems::Spawn),
                 ),
             );
@@ -104,7 +239,12 @@ impl Plugin for TuttiGraphPlugin {
             use crate::vst2_load::process_pending_vst2_builds;
             app.add_systems(
                 Update,
-                process_pending_vst2_builds.in_set(GraphReconcileSystems::Spawn),
+                process_pending_vst2_builds
+                    .before(reconcile_sidechain_links)
+                    .before(reconcile_audio_routing)
+                    .before(reconcile_audio_connections)
+                    .before(reconcile_feedback_edges)
+                    .in_set(GraphReconcileSystems::Spawn),
             );
         }
 
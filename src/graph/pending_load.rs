@@ -32,6 +32,7 @@ use tutti::sampler::file::ImportHandle;
 use tutti::sampler::SamplerUnit;
 
 use super::reconcile::GraphDirty;
+use super::rehydrate::AudioNodeRecipe;
 use crate::resources::TuttiGraphRes;
 
 /// "When this asset is loaded, build a `SamplerUnit` and add it to the graph."
@@ -186,15 +187,22 @@ pub fn promote_pending_samplers(
         let id = graph.0.add(unit);
         dirty.0 = true;
 
-        commands
-            .entity(entity)
-            .remove::<PendingSamplerLoad>()
-            .insert((
-                AudioNode(id),
-                NodeKind::Sampler,
-                Volume(pending_load.gain),
-                SamplerSpeed(pending_load.speed),
-                SamplerLooping(pending_load.looping),
-            ));
+        let mut e = commands.entity(entity);
+        e.remove::<PendingSamplerLoad>().insert((
+            AudioNode(id),
+            NodeKind::Sampler,
+            Volume(pending_load.gain),
+            SamplerSpeed(pending_load.speed),
+            SamplerLooping(pending_load.looping),
+        ));
+        // Only path-loaded waves can be rebuilt from a scene.
+        if let Some(path) = pending_load.wave.path() {
+            e.insert(AudioNodeRecipe::Sampler {
+                path: path.to_string(),
+                gain: pending_load.gain,
+                speed: pending_load.speed,
+                looping: pending_load.looping,
+            });
+        }
     }
 }
@@ -7,7 +7,8 @@
 //! - [`reconcile_node_despawn`] — picks up `RemovedComponents<AudioNode>`
 //!   and removes the underlying graph node.
 //! - [`reconcile_params`] — sweeps `Changed<Volume>` (and friends) and writes
-//!   the new value through a typed `node_mut::<T>` call.
+//!   the new value through a typed `node_mut::<T>` call. Kinds without a
+//!   typed gain setter go through [`super::strip`] instead.
 //! - [`commit_graph`] — `graph.commit()` once per frame iff any reconcile
 //!   system mutated the graph.
 //! - [`GraphReconcileSystems`] — system-set ordering anchor for hosts that
@@ -20,6 +21,9 @@ use bevy_ecs::system::EntityCommands;
 use tutti::core::ecs::{AudioNode, Mute, NodeKind, Volume};
 use tutti::dsp::AudioUnit;
 
+#[cfg(any(feature = "sampler", feature = "dsp"))]
+use super::smoothing::SmoothedParam;
+use super::smoothing::{ParamRamps, ParamSmoother, ParamSmoothing};
 use crate::resources::TuttiGraphRes;
 
 #[cfg(feature = "sampler")]
@@ -154,6 +158,10 @@ pub fn crossfade_audio_node(
             return;
         };
         graph.0.crossfade_boxed(node.0, tutti::Fade::Smooth, 0.005, new_unit);
+        // The driver's clones point at the outgoing unit.
+        if let Some(mut ramps) = world.get_resource_mut::<ParamRamps>() {
+            ramps.detach(entity);
+        }
         if let Some(mut dirty) = world.get_resource_mut::<GraphDirty>() {
             dirty.0 = true;
         }
@@ -195,37 +203,49 @@ pub fn reconcile_node_despawn(
     }
 }
 
-type ChangedParams<'w> = (&'w AudioNode, &'w NodeKind, &'w Volume, Option<&'w Mute>);
+type ChangedParams<'w> = (
+    Entity,
+    &'w AudioNode,
+    &'w NodeKind,
+    Option<&'w Volume>,
+    Option<Ref<'w, Mute>>,
+    Option<&'w ParamSmoothing>,
+);
 type ChangedParamFilter = Or<(Changed<Volume>, Changed<Mute>)>;
 
-/// Reconciles `Changed<Volume>` and `Changed<Mute>` into the underlying
-/// graph node. Dispatch is keyed off [`NodeKind`]; unknown kinds are
-/// skipped (apps can layer their own systems for custom kinds).
+/// Reconciles `Changed<Volume>` and `Changed<Mute>` into kinds that own a
+/// typed gain setter (currently [`NodeKind::Sampler`]). Every other kind
+/// gets its gain from a [`ChannelStrip`](super::strip::ChannelStrip)
+/// instead, reconciled by [`reconcile_strip_levels`](super::strip::reconcile_strip_levels).
 #[allow(unused_mut, unused_variables)]
 pub fn reconcile_params(
     graph: Option<ResMut<TuttiGraphRes>>,
+    mut smoother: ParamSmoother,
     changed_vol: Query<ChangedParams, ChangedParamFilter>,
     mut dirty: ResMut<GraphDirty>,
 ) {
     let Some(mut graph) = graph else { return };
 
-    for (node, kind, volume, mute) in changed_vol.iter() {
-        let muted = mute.map(|m| m.0).unwrap_or(false);
-        let target = if muted { 0.0 } else { volume.0 };
+    for (entity, node, kind, volume, mute, smoothing) in changed_vol.iter() {
+        let muted = mute.as_ref().is_some_and(|m| m.0);
 
         match *kind {
             #[cfg(feature = "sampler")]
             NodeKind::Sampler => {
-                if let Some(unit) = graph.0.node_mut::<SamplerUnit>(node.0) {
-                    unit.set_gain(target);
-                    dirty.0 = true;
-                }
+                let gain = volume.map_or(1.0, |v| v.0);
+                let gain = if muted { 0.0 } else { gain };
+                // Mute switches; only level changes ramp.
+                let smoothing = match &mute {
+                    Some(m) if m.is_changed() => Some(&ParamSmoothing::OFF),
+                    _ => smoothing,
+                };
+                let target = (entity, node, kind);
+                smoother.write(&mut graph, target, SmoothedParam::Volume, gain, smoothing);
+                dirty.0 = true;
             }
-            // Other kinds: no first-class typed setter at this layer;
-            // hosts are expected to layer their own systems. See
-            // module docs for the extension pattern.
+            // Other kinds: handled by the channel strip.
             _ => {
-                let _ = (target, node);
+                let _ = (entity, node, volume, muted, smoothing);
             }
         }
     }
@@ -233,10 +253,12 @@ pub fn reconcile_params(
 
 #[cfg(feature = "sampler")]
 type ChangedSamplerParams<'w> = (
+    Entity,
     &'w AudioNode,
     &'w NodeKind,
     Option<&'w SamplerSpeed>,
     Option<&'w SamplerLooping>,
+    Option<&'w ParamSmoothing>,
 );
 #[cfg(feature = "sampler")]
 type ChangedSamplerFilter = Or<(Changed<SamplerSpeed>, Changed<SamplerLooping>)>;
@@ -244,31 +266,33 @@ type ChangedSamplerFilter = Or<(Changed<SamplerSpeed>, Changed<SamplerLooping>)>
 /// Reconciles `Changed<SamplerSpeed>` and `Changed<SamplerLooping>` into
 /// the underlying [`SamplerUnit`].
 ///
-/// `SamplerSpeed` writes through `SamplerUnit::set_speed` (`&mut self`,
-/// reached via `node_mut::<SamplerUnit>`). `SamplerLooping` writes through
-/// `SamplerUnit::set_looping` (atomic, `&self`) — it doesn't strictly
-/// require `node_mut`, but using it here keeps the dispatch shape uniform
-/// and lets the dirty flag coalesce a single commit per frame regardless
-/// of which sampler param changed.
+/// `SamplerSpeed` goes through [`ParamSmoother`], which ramps it on the
+/// audio thread or writes `SamplerUnit::set_speed`. `SamplerLooping`
+/// writes through `SamplerUnit::set_looping` (atomic, `&self`) — it
+/// doesn't strictly require `node_mut`, but using it here keeps the
+/// dispatch shape uniform and lets the dirty flag coalesce a single commit
+/// per frame regardless of which sampler param changed.
 #[cfg(feature = "sampler")]
 pub fn reconcile_sampler_params(
     graph: Option<ResMut<TuttiGraphRes>>,
+    mut smoother: ParamSmoother,
     changed: Query<ChangedSamplerParams, ChangedSamplerFilter>,
     mut dirty: ResMut<GraphDirty>,
 ) {
     let Some(mut graph) = graph else { return };
 
-    for (node, kind, speed, looping) in changed.iter() {
+    for (entity, node, kind, speed, looping, smoothing) in changed.iter() {
         if !matches!(*kind, NodeKind::Sampler) {
             continue;
         }
-        let Some(unit) = graph.0.node_mut::<SamplerUnit>(node.0) else {
-            continue;
-        };
         if let Some(s) = speed {
-            unit.set_speed(s.0);
+            let (target, param) = ((entity, node, kind), SmoothedParam::SamplerSpeed);
+            smoother.write(&mut graph, target, param, s.0, smoothing);
         }
         if let Some(l) = looping {
+            let Some(unit) = graph.0.node_mut::<SamplerUnit>(node.0) else {
+                continue;
+            };
             unit.set_looping(l.0);
         }
         dirty.0 = true;
@@ -295,7 +319,9 @@ pub fn reconcile_plugin_params(
 // Each one runs in `GraphReconcileSystems::Params`, queries entities with
 // the right `NodeKind` and a Changed<X> on any param it owns, then
 // writes through the unit's typed setter (lock-free atomic store —
-// no graph mutation, so `GraphDirty` stays untouched).
+// no graph mutation, so `GraphDirty` stays untouched). Continuous params
+// go through `ParamSmoother`: with smoothing on, the `ParamRamps` driver
+// node walks the setter to the target sample by sample.
 // =============================================================================
 
 #[cfg(feature = "dsp")]
@@ -314,29 +340,34 @@ type FilterChangedFilter =
 #[allow(clippy::type_complexity, reason = "Bevy queries are tuple-shaped by design")]
 pub fn reconcile_filter_params(
     graph: Option<ResMut<TuttiGraphRes>>,
+    mut smoother: ParamSmoother,
     changed: Query<
-        (&AudioNode, &NodeKind, Option<&Frequency>, Option<&FilterQ>, Option<&GainDb>),
+        (
+            Entity,
+            &AudioNode,
+            &NodeKind,
+            Option<&Frequency>,
+            Option<&FilterQ>,
+            Option<&GainDb>,
+            Option<&ParamSmoothing>,
+        ),
         FilterChangedFilter,
     >,
 ) {
     let Some(mut graph) = graph else { return };
-    for (node, kind, freq, q, gain) in changed.iter() {
+    for (entity, node, kind, freq, q, gain, smoothing) in changed.iter() {
         if !matches!(*kind, NodeKind::Filter) {
             continue;
         }
-        let Some(unit) =
-            graph.0.node_mut::<tutti::units::StereoSvfFilterNode<f64>>(node.0)
-        else {
-            continue;
-        };
+        let target = (entity, node, kind);
         if let Some(f) = freq {
-            unit.set_frequency(f.0);
+            smoother.write(&mut graph, target, SmoothedParam::Frequency, f.0, smoothing);
         }
         if let Some(q) = q {
-            unit.set_q(q.0);
+            smoother.write(&mut graph, target, SmoothedParam::FilterQ, q.0, smoothing);
         }
         if let Some(g) = gain {
-            unit.set_gain_db(g.0);
+            smoother.write(&mut graph, target, SmoothedParam::GainDb, g.0, smoothing);
         }
     }
 }
@@ -350,27 +381,34 @@ type DelayChangedFilter = Or<(Changed<DelayTime>, Changed<Feedback>, Changed<Wet
 #[allow(clippy::type_complexity, reason = "Bevy queries are tuple-shaped by design")]
 pub fn reconcile_delay_params(
     graph: Option<ResMut<TuttiGraphRes>>,
+    mut smoother: ParamSmoother,
     changed: Query<
-        (&AudioNode, &NodeKind, Option<&DelayTime>, Option<&Feedback>, Option<&WetMix>),
+        (
+            Entity,
+            &AudioNode,
+            &NodeKind,
+            Option<&DelayTime>,
+            Option<&Feedback>,
+            Option<&WetMix>,
+            Option<&ParamSmoothing>,
+        ),
         DelayChangedFilter,
     >,
 ) {
     let Some(mut graph) = graph else { return };
-    for (node, kind, time, fb, wet) in changed.iter() {
+    for (entity, node, kind, time, fb, wet, smoothing) in changed.iter() {
         if !matches!(*kind, NodeKind::Delay) {
             continue;
         }
-        let Some(unit) = graph.0.node_mut::<tutti::units::StereoDelayLineNode>(node.0) else {
-            continue;
-        };
+        let target = (entity, node, kind);
         if let Some(t) = time {
-            unit.set_delay_time(t.0);
+            smoother.write(&mut graph, target, SmoothedParam::DelayTime, t.0, smoothing);
         }
         if let Some(f) = fb {
-            unit.set_feedback(f.0);
+            smoother.write(&mut graph, target, SmoothedParam::Feedback, f.0, smoothing);
         }
         if let Some(w) = wet {
-            unit.set_mix(w.0);
+            smoother.write(&mut graph, target, SmoothedParam::WetMix, w.0, smoothing);
         }
     }
 }
@@ -389,37 +427,38 @@ type ChorusChangedFilter = Or<(
 #[allow(clippy::type_complexity, reason = "Bevy queries are tuple-shaped by design")]
 pub fn reconcile_chorus_params(
     graph: Option<ResMut<TuttiGraphRes>>,
+    mut smoother: ParamSmoother,
     changed: Query<
         (
+            Entity,
             &AudioNode,
             &NodeKind,
             Option<&ModRate>,
             Option<&ModDepth>,
             Option<&Feedback>,
             Option<&WetMix>,
+            Option<&ParamSmoothing>,
         ),
         ChorusChangedFilter,
     >,
 ) {
     let Some(mut graph) = graph else { return };
-    for (node, kind, rate, depth, fb, wet) in changed.iter() {
+    for (entity, node, kind, rate, depth, fb, wet, smoothing) in changed.iter() {
         if !matches!(*kind, NodeKind::Chorus) {
             continue;
         }
-        let Some(unit) = graph.0.node_mut::<tutti::units::ChorusNode>(node.0) else {
-            continue;
-        };
+        let target = (entity, node, kind);
         if let Some(r) = rate {
-            unit.set_rate(r.0);
+            smoother.write(&mut graph, target, SmoothedParam::ModRate, r.0, smoothing);
         }
         if let Some(d) = depth {
-            unit.set_depth(d.0);
+            smoother.write(&mut graph, target, SmoothedParam::ModDepth, d.0, smoothing);
         }
         if let Some(f) = fb {
-            unit.set_feedback(f.0);
+            smoother.write(&mut graph, target, SmoothedParam::Feedback, f.0, smoothing);
         }
         if let Some(w) = wet {
-            unit.set_mix(w.0);
+            smoother.write(&mut graph, target, SmoothedParam::WetMix, w.0, smoothing);
         }
     }
 }
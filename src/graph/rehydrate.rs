@@ -0,0 +1,506 @@
+//! Scene save/load for entity-as-node graphs.
+//!
+//! [`AudioNode`] wraps a foreign `NodeId` and the `Add*` / `PlaySoundFont`
+//! / `PendingSamplerLoad` triggers are consumed once the node exists, so
+//! none of them survive a `DynamicScene` round-trip. [`AudioNodeRecipe`]
+//! fills the gap: a reflected description of *how* to rebuild a node —
+//! a DSP spec, an asset path, or a plugin path plus state blob.
+//!
+//! The spawn systems attach a recipe whenever they build a node, so a
+//! scene extracted from a live world already carries everything needed.
+//! On scene load, [`rehydrate_audio_nodes`] sees `Added<AudioNodeRecipe>`
+//! on entities without an `AudioNode` and re-inserts the matching spawn
+//! trigger. Routing ([`super::AudioFeedsTo`]) and sidechain
+//! ([`super::SidechainOf`]) links are reflected with `MapEntities` and
+//! their reconcilers defer connects until both endpoints have been
+//! rebuilt, so wiring comes back without extra work.
+//!
+//! Plugin state is captured at spawn time only. Call
+//! [`refresh_plugin_recipe_state`] (or schedule it) right before
+//! extracting a scene to snapshot the current plugin presets.
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+use tutti::core::ecs::AudioNode;
+
+#[cfg(feature = "dsp")]
+use tutti::core::ecs::{
+    Attack, CompressorRatio, DelayTime, Feedback, FilterQ, Frequency, GainDb, ModDepth, ModRate,
+    Release, ReverbDamping, ReverbRoomSize, ThresholdDb, WetMix,
+};
+
+#[cfg(feature = "dsp")]
+use crate::dsp::{AddChorus, AddCompressor, AddDelay, AddFilter, AddGate, AddReverb};
+
+/// Reflected description of how to rebuild an entity's graph node.
+///
+/// Inserted automatically by the spawn systems alongside `AudioNode`.
+/// Every variant holds plain data (numbers, asset paths, byte blobs) so
+/// it serializes through the type registry without feature-specific
+/// types; rebuilding a variant whose feature is disabled logs a warning.
+#[derive(Component, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Component, Clone)]
+pub enum AudioNodeRecipe {
+    Filter {
+        mode: FilterMode,
+        frequency: f32,
+        q: f32,
+        gain_db: f32,
+    },
+    Reverb {
+        room_size: f32,
+        time_secs: f32,
+        damping: f32,
+        wet: f32,
+    },
+    Delay {
+        max_delay_secs: f32,
+        delay_time_secs: f32,
+        feedback: f32,
+        wet: f32,
+    },
+    Chorus {
+        rate_hz: f32,
+        depth_secs: f32,
+        feedback: f32,
+        wet: f32,
+    },
+    Compressor {
+        threshold_db: f32,
+        ratio: f32,
+        attack: f32,
+        release: f32,
+        makeup_db: f32,
+        stereo: bool,
+    },
+    Gate {
+        threshold_db: f32,
+        attack: f32,
+        hold: f32,
+        release: f32,
+        stereo: bool,
+    },
+    /// A `SamplerUnit` over the wave at `path` (loaded through `AssetServer`).
+    Sampler {
+        path: String,
+        gain: f32,
+        speed: f32,
+        looping: bool,
+    },
+    /// A `SoundFontUnit` over the `.sf2` at `path`.
+    SoundFont {
+        path: String,
+        preset: i32,
+        channel: i32,
+    },
+    /// A hosted plugin loaded from `path`, restored with `state`.
+    Plugin { path: String, state: Vec<u8> },
+}
+
+/// Reflected mirror of `tutti::units::SvfType` for [`AudioNodeRecipe::Filter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+pub enum FilterMode {
+    #[default]
+    LowPass,
+    HighPass,
+    BandPass,
+    Notch,
+    Bell,
+    LowShelf,
+    HighShelf,
+}
+
+#[cfg(feature = "dsp")]
+impl From<tutti::units::SvfType> for FilterMode {
+    fn from(svf: tutti::units::SvfType) -> Self {
+        use tutti::units::SvfType;
+        match svf {
+            SvfType::LowPass => Self::LowPass,
+            SvfType::HighPass => Self::HighPass,
+            SvfType::BandPass => Self::BandPass,
+            SvfType::Notch => Self::Notch,
+            SvfType::Bell => Self::Bell,
+            SvfType::LowShelf => Self::LowShelf,
+            SvfType::HighShelf => Self::HighShelf,
+        }
+    }
+}
+
+#[cfg(feature = "dsp")]
+impl From<FilterMode> for tutti::units::SvfType {
+    fn from(mode: FilterMode) -> Self {
+        match mode {
+            FilterMode::LowPass => Self::LowPass,
+            FilterMode::HighPass => Self::HighPass,
+            FilterMode::BandPass => Self::BandPass,
+            FilterMode::Notch => Self::Notch,
+            FilterMode::Bell => Self::Bell,
+            FilterMode::LowShelf => Self::LowShelf,
+            FilterMode::HighShelf => Self::HighShelf,
+        }
+    }
+}
+
+#[cfg(feature = "dsp")]
+impl From<&AddFilter> for AudioNodeRecipe {
+    fn from(add: &AddFilter) -> Self {
+        Self::Filter {
+            mode: add.svf_type.into(),
+            frequency: add.frequency,
+            q: add.q,
+            gain_db: add.gain_db,
+        }
+    }
+}
+
+#[cfg(feature = "dsp")]
+impl From<&AddReverb> for AudioNodeRecipe {
+    fn from(add: &AddReverb) -> Self {
+        Self::Reverb {
+            room_size: add.room_size,
+            time_secs: add.time_secs,
+            damping: add.damping,
+            wet: add.wet,
+        }
+    }
+}
+
+#[cfg(feature = "dsp")]
+impl From<&AddDelay> for AudioNodeRecipe {
+    fn from(add: &AddDelay) -> Self {
+        Self::Delay {
+            max_delay_secs: add.max_delay_secs,
+            delay_time_secs: add.delay_time_secs,
+            feedback: add.feedback,
+            wet: add.wet,
+        }
+    }
+}
+
+#[cfg(feature = "dsp")]
+impl From<&AddChorus> for AudioNodeRecipe {
+    fn from(add: &AddChorus) -> Self {
+        Self::Chorus {
+            rate_hz: add.rate_hz,
+            depth_secs: add.depth_secs,
+            feedback: add.feedback,
+            wet: add.wet,
+        }
+    }
+}
+
+#[cfg(feature = "dsp")]
+impl From<&AddCompressor> for AudioNodeRecipe {
+    fn from(add: &AddCompressor) -> Self {
+        Self::Compressor {
+            threshold_db: add.threshold_db,
+            ratio: add.ratio,
+            attack: add.attack,
+            release: add.release,
+            makeup_db: add.makeup_db,
+            stereo: add.stereo,
+        }
+    }
+}
+
+#[cfg(feature = "dsp")]
+impl From<&AddGate> for AudioNodeRecipe {
+    fn from(add: &AddGate) -> Self {
+        Self::Gate {
+            threshold_db: add.threshold_db,
+            attack: add.attack,
+            hold: add.hold,
+            release: add.release,
+            stereo: add.stereo,
+        }
+    }
+}
+
+/// Live parameter components saved next to a DSP recipe.
+///
+/// When a scene carries both a recipe and (reflected) parameter
+/// components, the parameter values win: they reflect edits made after
+/// the node was first built.
+#[cfg(feature = "dsp")]
+#[derive(bevy_ecs::query::QueryData)]
+pub struct DspParamOverrides {
+    frequency: Option<&'static Frequency>,
+    q: Option<&'static FilterQ>,
+    gain_db: Option<&'static GainDb>,
+    delay_time: Option<&'static DelayTime>,
+    feedback: Option<&'static Feedback>,
+    wet: Option<&'static WetMix>,
+    mod_rate: Option<&'static ModRate>,
+    mod_depth: Option<&'static ModDepth>,
+    threshold: Option<&'static ThresholdDb>,
+    ratio: Option<&'static CompressorRatio>,
+    attack: Option<&'static Attack>,
+    release: Option<&'static Release>,
+    room_size: Option<&'static ReverbRoomSize>,
+    damping: Option<&'static ReverbDamping>,
+}
+
+/// Rebuilds graph nodes for scene-loaded entities.
+///
+/// Runs in [`super::GraphReconcileSystems::Spawn`]. For each entity that
+/// gained an [`AudioNodeRecipe`] without an [`AudioNode`], inserts the
+/// spawn trigger that builds the node — `AddFilter` & co. for DSP,
+/// [`PendingSamplerLoad`](super::PendingSamplerLoad) for samplers,
+/// `PlaySoundFont` for SoundFonts, and `PendingVst2Build` for plugins.
+/// The trigger's own system attaches `AudioNode` on a later pass.
+#[allow(
+    clippy::type_complexity,
+    reason = "Bevy queries are tuple-shaped by design"
+)]
+pub fn rehydrate_audio_nodes(
+    mut commands: Commands,
+    #[cfg(any(feature = "sampler", feature = "soundfont"))] asset_server: Option<
+        Res<bevy_asset::AssetServer>,
+    >,
+    #[cfg(all(feature = "plugin", feature = "vst2"))] config: Option<
+        Res<crate::resources::AudioConfig>,
+    >,
+    #[cfg(feature = "dsp")] recipes: Query<
+        (Entity, &AudioNodeRecipe, DspParamOverrides),
+        (Added<AudioNodeRecipe>, Without<AudioNode>),
+    >,
+    #[cfg(not(feature = "dsp"))] recipes: Query<
+        (Entity, &AudioNodeRecipe),
+        (Added<AudioNodeRecipe>, Without<AudioNode>),
+    >,
+) {
+    #[cfg(feature = "dsp")]
+    let iter = recipes.iter();
+    #[cfg(not(feature = "dsp"))]
+    let iter = recipes.iter().map(|(entity, recipe)| (entity, recipe, ()));
+
+    for (entity, recipe, _params) in iter {
+        let mut e = commands.entity(entity);
+        match recipe.clone() {
+            #[cfg(feature = "dsp")]
+            AudioNodeRecipe::Filter {
+                mode,
+                frequency,
+                q,
+                gain_db,
+            } => {
+                e.insert(AddFilter {
+                    svf_type: mode.into(),
+                    frequency: _params.frequency.map_or(frequency, |p| p.0),
+                    q: _params.q.map_or(q, |p| p.0),
+                    gain_db: _params.gain_db.map_or(gain_db, |p| p.0),
+                });
+            }
+            #[cfg(feature = "dsp")]
+            AudioNodeRecipe::Reverb {
+                room_size,
+                time_secs,
+                damping,
+                wet,
+            } => {
+                e.insert(AddReverb {
+                    room_size: _params.room_size.map_or(room_size, |p| p.0),
+                    time_secs,
+                    damping: _params.damping.map_or(damping, |p| p.0),
+                    wet: _params.wet.map_or(wet, |p| p.0),
+                });
+            }
+            #[cfg(feature = "dsp")]
+            AudioNodeRecipe::Delay {
+                max_delay_secs,
+                delay_time_secs,
+                feedback,
+                wet,
+            } => {
+                e.insert(AddDelay {
+                    max_delay_secs,
+                    delay_time_secs: _params.delay_time.map_or(delay_time_secs, |p| p.0),
+                    feedback: _params.feedback.map_or(feedback, |p| p.0),
+                    wet: _params.wet.map_or(wet, |p| p.0),
+                });
+            }
+            #[cfg(feature = "dsp")]
+            AudioNodeRecipe::Chorus {
+                rate_hz,
+                depth_secs,
+                feedback,
+                wet,
+            } => {
+                e.insert(AddChorus {
+                    rate_hz: _params.mod_rate.map_or(rate_hz, |p| p.0),
+                    depth_secs: _params.mod_depth.map_or(depth_secs, |p| p.0),
+                    feedback: _params.feedback.map_or(feedback, |p| p.0),
+                    wet: _params.wet.map_or(wet, |p| p.0),
+                });
+            }
+            #[cfg(feature = "dsp")]
+            AudioNodeRecipe::Compressor {
+                threshold_db,
+                ratio,
+                attack,
+                release,
+                makeup_db,
+                stereo,
+            } => {
+                e.insert(AddCompressor {
+                    threshold_db: _params.threshold.map_or(threshold_db, |p| p.0),
+                    ratio: _params.ratio.map_or(ratio, |p| p.0),
+                    attack: _params.attack.map_or(attack, |p| p.0),
+                    release: _params.release.map_or(release, |p| p.0),
+                    makeup_db: _params.gain_db.map_or(makeup_db, |p| p.0),
+                    stereo,
+                });
+            }
+            #[cfg(feature = "dsp")]
+            AudioNodeRecipe::Gate {
+                threshold_db,
+                attack,
+                hold,
+                release,
+                stereo,
+            } => {
+                e.insert(AddGate {
+                    threshold_db: _params.threshold.map_or(threshold_db, |p| p.0),
+                    attack: _params.attack.map_or(attack, |p| p.0),
+                    hold,
+                    release: _params.release.map_or(release, |p| p.0),
+                    stereo,
+                });
+            }
+            #[cfg(feature = "sampler")]
+            AudioNodeRecipe::Sampler {
+                path,
+                gain,
+                speed,
+                looping,
+            } => {
+                let Some(server) = asset_server.as_ref() else {
+                    bevy_log::warn!(
+                        "rehydrate: no AssetServer to load '{}' for {:?}",
+                        path,
+                        entity
+                    );
+                    continue;
+                };
+                e.insert(
+                    super::PendingSamplerLoad::new(server.load(path))
+                        .gain(gain)
+                        .speed(speed)
+                        .looping(looping),
+                );
+            }
+            #[cfg(feature = "soundfont")]
+            AudioNodeRecipe::SoundFont {
+                path,
+                preset,
+                channel,
+            } => {
+                let Some(server) = asset_server.as_ref() else {
+                    bevy_log::warn!(
+                        "rehydrate: no AssetServer to load '{}' for {:?}",
+                        path,
+                        entity
+                    );
+                    continue;
+                };
+                e.insert(
+                    crate::soundfont::PlaySoundFont::new(server.load(path))
+                        .preset(preset)
+                        .channel(channel),
+                );
+            }
+            #[cfg(all(feature = "plugin", feature = "vst2"))]
+            AudioNodeRecipe::Plugin { path, state } => {
+                let Some(config) = config.as_ref() else {
+                    bevy_log::warn!("rehydrate: no AudioConfig to build plugin '{}'", path);
+                    continue;
+                };
+                let mut build = crate::vst2_load::PendingVst2Build::new(path, config.sample_rate);
+                if !state.is_empty() {
+                    build = build.with_state(state);
+                }
+                e.insert(build);
+            }
+            #[allow(unreachable_patterns)]
+            other => {
+                bevy_log::warn!(
+                    "rehydrate: {:?} needs a feature that isn't enabled; {:?} left without AudioNode",
+                    other,
+                    entity
+                );
+            }
+        }
+    }
+}
+
+/// Snapshots each hosted plugin's current state into its
+/// [`AudioNodeRecipe::Plugin`] blob. Run before extracting a scene.
+#[cfg(feature = "plugin")]
+pub fn refresh_plugin_recipe_state(
+    mut plugins: Query<(&crate::plugin_host::PluginEmitter, &mut AudioNodeRecipe)>,
+) {
+    for (emitter, mut recipe) in plugins.iter_mut() {
+        if let AudioNodeRecipe::Plugin { state, .. } = recipe.as_mut() {
+            *state = emitter.handle.save_state();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "soundfont"))]
+mod tests {
+    use super::*;
+    use bevy_app::{App, TaskPoolPlugin, Update};
+    use bevy_asset::{AssetApp, AssetPlugin};
+    use tutti::core::ecs::NodeKind;
+    use tutti::TuttiEngine;
+
+    #[test]
+    fn built_soundfont_is_not_rebuilt() {
+        let TuttiEngine { mut graph, .. } = TuttiEngine::builder()
+            .inputs(0)
+            .outputs(2)
+            .build()
+            .expect("build engine");
+        let id = graph.add(tutti::dsp::pass() | tutti::dsp::pass());
+
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<tutti::synth::SoundFontAsset>()
+            .add_systems(Update, rehydrate_audio_nodes);
+
+        let recipe = AudioNodeRecipe::SoundFont {
+            path: "sounds/GeneralMidi.sf2".into(),
+            preset: 0,
+            channel: 0,
+        };
+        // What `soundfont_playback_system` leaves behind once the unit is
+        // in the graph.
+        let built = app
+            .world_mut()
+            .spawn((
+                AudioNode(id),
+                NodeKind::Generator,
+                crate::playback::AudioEmitter { node_id: id },
+                recipe.clone(),
+            ))
+            .id();
+        // A scene-loaded entity: recipe only.
+        let loaded = app.world_mut().spawn(recipe).id();
+
+        app.update();
+        app.update();
+
+        let world = app.world();
+        assert!(!world
+            .entity(built)
+            .contains::<crate::soundfont::PlaySoundFont>());
+        let play = world
+            .get::<crate::soundfont::PlaySoundFont>(loaded)
+            .expect("rehydrated trigger");
+        assert_eq!(
+            play.source.path().map(|p| p.to_string()),
+            Some("sounds/GeneralMidi.sf2".to_string())
+        );
+    }
+}
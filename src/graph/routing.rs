@@ -3,41 +3,51 @@
 //! [`AudioFeedsTo`] is a many-to-one relationship from a *source* entity
 //! (anything carrying an [`AudioNode`]) to a *target* entity (also
 //! carrying an [`AudioNode`]). Source and destination ports are part of
-//! the relationship value, so a single component instance encodes one
-//! mono "wire" in the graph. Stereo edges are expressed as two
-//! [`AudioFeedsTo`] entities — one per channel — usually living on a
-//! shared "edge" entity.
+//! the relationship value. [`FeedPorts`] picks how many wires one
+//! component makes: a single mono wire, a stereo pair, or every port,
+//! adapting mono sources to stereo inputs and vice versa.
 //!
 //! [`AudioFedBy`] is the auto-maintained relationship-target counterpart
 //! Bevy populates on the target side. Reading it gives you every source
 //! currently feeding the target.
 //!
 //! The reconcile system [`reconcile_audio_routing`] runs in
-//! [`GraphReconcileSystems::Spawn`]: on `Added<AudioFeedsTo>` it looks
+//! [`GraphReconcileSystems::Spawn`]: on `Changed<AudioFeedsTo>` it looks
 //! up both entities' [`AudioNode`] and calls
-//! `graph.connect(src_node, src_port, dst_node, dst_port)`. On
-//! `RemovedComponents<AudioFeedsTo>` it disconnects the recorded
-//! `(dst_node, dst_port)` it had wired earlier.
+//! `graph.connect(src_node, src_port, dst_node, dst_port)` for each port
+//! pair, first disconnecting whatever the link wired before. On
+//! `RemovedComponents<AudioFeedsTo>` it disconnects the destination ports
+//! it had wired earlier.
+//!
+//! A relationship holds one target per source. [`AudioConnections`] is
+//! the many-to-many form: a list of edges on the source, reconciled by
+//! [`reconcile_audio_connections`], which diffs the list against what it
+//! wired last time and only touches the edges that changed.
 //!
 //! Pure graph-op binding — no DAW vocabulary. Sidechain ([`super::sidechain`])
 //! is a sibling, hardcoded to port 1; this is the general case. Hosts
 //! can use both freely; they don't interact.
 
 use bevy_ecs::entity::{EntityMapper, MapEntities};
+use bevy_ecs::message::MessageWriter;
 use bevy_ecs::prelude::*;
 use bevy_ecs::reflect::{ReflectComponent, ReflectMapEntities};
 use bevy_reflect::Reflect;
 
 use tutti::core::ecs::AudioNode;
 
+use super::feedback::{report_blocked, AudioRoutingError, RoutedEdges};
 use super::reconcile::GraphDirty;
+use super::strip::{output_node, ChannelStrip};
 use crate::resources::TuttiGraphRes;
 
 /// "This entity's audio output `src_port` feeds `target`'s input `dst_port`."
 ///
-/// Insert on the *source* entity. Every [`AudioFeedsTo`] is exactly one
-/// `graph.connect(src, src_port, dst, dst_port)` — stereo wires use two
-/// instances, multi-input mixers use one per source, etc.
+/// Insert on the *source* entity. With [`FeedPorts::Single`] (the
+/// default, from [`mono`](Self::mono) / [`between`](Self::between)) it is
+/// exactly one `graph.connect(src, src_port, dst, dst_port)`;
+/// [`stereo`](Self::stereo) and [`all`](Self::all) wire consecutive ports
+/// from there.
 ///
 /// `target` is annotated with `#[relationship]` so Bevy's relationship
 /// machinery auto-maintains the [`AudioFedBy`] component on the target.
@@ -54,6 +64,36 @@ pub struct AudioFeedsTo {
     pub src_port: u32,
     /// Input port on the destination node (`graph.connect(…, dst, dst_port)`).
     pub dst_port: u32,
+    /// How many consecutive port pairs to wire, starting at
+    /// `src_port`/`dst_port`.
+    pub ports: FeedPorts,
+}
+
+/// Port pairs an [`AudioFeedsTo`] wires.
+///
+/// For `Stereo` and `All`, channel counts are adapted when they differ: a
+/// single source channel is duplicated to every destination channel, and
+/// several source channels into a single input are summed (the first two
+/// of them).
+#[derive(Reflect, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeedPorts {
+    /// `src_port` → `dst_port` only.
+    #[default]
+    Single,
+    /// Two channels.
+    Stereo,
+    /// As many channels as both sides have.
+    All,
+}
+
+impl FeedPorts {
+    fn width(self) -> usize {
+        match self {
+            FeedPorts::Single => 1,
+            FeedPorts::Stereo => 2,
+            FeedPorts::All => usize::MAX,
+        }
+    }
 }
 
 impl AudioFeedsTo {
@@ -61,11 +101,7 @@ impl AudioFeedsTo {
     /// `target`'s input port 0 — the common mono-tap-of-mono-input case.
     #[inline]
     pub fn mono(target: Entity) -> Self {
-        Self {
-            target,
-            src_port: 0,
-            dst_port: 0,
-        }
+        Self::between(target, 0, 0)
     }
 
     /// Construct a connection between specific ports.
@@ -75,8 +111,35 @@ impl AudioFeedsTo {
             target,
             src_port,
             dst_port,
+            ports: FeedPorts::Single,
         }
     }
+
+    /// Connect outputs 0/1 to `target`'s inputs 0/1, duplicating a mono
+    /// source and summing into a mono input.
+    #[inline]
+    pub fn stereo(target: Entity) -> Self {
+        Self {
+            ports: FeedPorts::Stereo,
+            ..Self::mono(target)
+        }
+    }
+
+    /// Connect every output to the matching input of `target`.
+    #[inline]
+    pub fn all(target: Entity) -> Self {
+        Self {
+            ports: FeedPorts::All,
+            ..Self::mono(target)
+        }
+    }
+
+    /// Wire [`ports`](Self::ports) consecutive pairs from this link's ports.
+    #[inline]
+    pub fn with_ports(mut self, ports: FeedPorts) -> Self {
+        self.ports = ports;
+        self
+    }
 }
 
 impl MapEntities for AudioFeedsTo {
@@ -108,94 +171,512 @@ impl AudioFedBy {
     }
 }
 
+/// What the routing reconcilers wired for one link: the target, the
+/// destination ports it connected, and the downmix node it added (if
+/// any), so removal can undo exactly that.
+#[derive(Debug, Clone)]
+pub struct WiredFeed {
+    target: Entity,
+    dst_ports: Vec<u32>,
+    adapter: Option<tutti::NodeId>,
+}
+
+/// Connects `link` from `src_id` into `target_id`, adapting channel
+/// counts per [`FeedPorts`]. `None` (after a warning) when either side
+/// has no port in range.
+pub(super) fn wire_feed(
+    graph: &mut TuttiGraphRes,
+    src_entity: Entity,
+    src_id: tutti::NodeId,
+    target_id: tutti::NodeId,
+    link: &AudioConnection,
+) -> Option<WiredFeed> {
+    let (src_port, dst_port) = (link.src_port as usize, link.dst_port as usize);
+    let width = link.ports.width();
+    let outputs = graph.0.outputs(src_id).saturating_sub(src_port).min(width);
+    let inputs = graph
+        .0
+        .inputs(target_id)
+        .saturating_sub(dst_port)
+        .min(width);
+    if outputs == 0 || inputs == 0 {
+        bevy_log::warn!(
+            "Audio routing: {:?} -> {:?} port out of range (src_port={}, dst_port={}); skipping connect",
+            src_entity,
+            link.target,
+            link.src_port,
+            link.dst_port
+        );
+        return None;
+    }
+
+    let mut wired = WiredFeed {
+        target: link.target,
+        dst_ports: Vec::new(),
+        adapter: None,
+    };
+    if outputs == 1 {
+        // Mono source: duplicate into every destination channel.
+        for i in 0..inputs {
+            graph.0.connect(src_id, src_port, target_id, dst_port + i);
+            wired.dst_ports.push((dst_port + i) as u32);
+        }
+    } else if inputs == 1 {
+        // Mono destination: sum the first two channels.
+        let sum = graph.0.add(tutti::dsp::pass() + tutti::dsp::pass());
+        graph.0.connect(src_id, src_port, sum, 0);
+        graph.0.connect(src_id, src_port + 1, sum, 1);
+        graph.0.connect(sum, 0, target_id, dst_port);
+        wired.dst_ports.push(dst_port as u32);
+        wired.adapter = Some(sum);
+    } else {
+        for i in 0..outputs.min(inputs) {
+            graph
+                .0
+                .connect(src_id, src_port + i, target_id, dst_port + i);
+            wired.dst_ports.push((dst_port + i) as u32);
+        }
+    }
+    Some(wired)
+}
+
+/// Disconnects what [`wire_feed`] connected. `target_id` is `None` when
+/// the target has lost its `AudioNode` (fundsp already dropped the edges).
+pub(super) fn unwire_feed(
+    graph: &mut TuttiGraphRes,
+    wired: &WiredFeed,
+    target_id: Option<tutti::NodeId>,
+) {
+    if let Some(target_id) = target_id.filter(|id| graph.0.contains(*id)) {
+        let inputs = graph.0.inputs(target_id);
+        for &port in &wired.dst_ports {
+            // A port past the input count would mean the unit was swapped
+            // under us; skip rather than panic.
+            if (port as usize) < inputs {
+                graph.0.disconnect(target_id, port as usize);
+            }
+        }
+    }
+    if let Some(adapter) = wired.adapter {
+        if graph.0.contains(adapter) {
+            graph.0.remove(adapter);
+        }
+    }
+}
+
+/// One edge of [`AudioConnections`]: the same wiring as an
+/// [`AudioFeedsTo`], without the one-target-per-source limit.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AudioConnection {
+    pub target: Entity,
+    pub src_port: u32,
+    pub dst_port: u32,
+    pub ports: FeedPorts,
+}
+
+impl AudioConnection {
+    /// Output port 0 to `target`'s input port 0.
+    #[inline]
+    pub fn mono(target: Entity) -> Self {
+        Self::between(target, 0, 0)
+    }
+
+    /// Connection between specific ports.
+    #[inline]
+    pub fn between(target: Entity, src_port: u32, dst_port: u32) -> Self {
+        Self {
+            target,
+            src_port,
+            dst_port,
+            ports: FeedPorts::Single,
+        }
+    }
+
+    /// Outputs 0/1 to `target`'s inputs 0/1, see [`AudioFeedsTo::stereo`].
+    #[inline]
+    pub fn stereo(target: Entity) -> Self {
+        Self {
+            ports: FeedPorts::Stereo,
+            ..Self::mono(target)
+        }
+    }
+
+    /// Every output to the matching input, see [`AudioFeedsTo::all`].
+    #[inline]
+    pub fn all(target: Entity) -> Self {
+        Self {
+            ports: FeedPorts::All,
+            ..Self::mono(target)
+        }
+    }
+
+    #[inline]
+    pub fn with_ports(mut self, ports: FeedPorts) -> Self {
+        self.ports = ports;
+        self
+    }
+}
+
+impl From<AudioFeedsTo> for AudioConnection {
+    fn from(link: AudioFeedsTo) -> Self {
+        Self {
+            target: link.target,
+            src_port: link.src_port,
+            dst_port: link.dst_port,
+            ports: link.ports,
+        }
+    }
+}
+
+/// "This entity's audio feeds every target in the list."
+///
+/// Insert on the *source* entity, alongside or instead of an
+/// [`AudioFeedsTo`]; edit the list in place to add or drop edges. Use it
+/// to split one source into, say, a dry bus and an FX chain without
+/// helper entities.
+///
+/// An input port takes one connection: two edges (from any sources)
+/// into the same port leave whichever was wired last. Targets don't get
+/// an [`AudioFedBy`] entry for these edges.
+#[derive(Component, Reflect, Debug, Default, Clone, PartialEq)]
+#[reflect(Component, Default, MapEntities)]
+pub struct AudioConnections(pub Vec<AudioConnection>);
+
+impl AudioConnections {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an edge.
+    pub fn to(mut self, connection: AudioConnection) -> Self {
+        self.0.push(connection);
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AudioConnection> + '_ {
+        self.0.iter()
+    }
+}
+
+impl MapEntities for AudioConnections {
+    fn map_entities<M: EntityMapper>(&mut self, mapper: &mut M) {
+        for connection in &mut self.0 {
+            connection.target = mapper.get_mapped(connection.target);
+        }
+    }
+}
+
 /// Reconciles routing wiring into graph operations.
 ///
-/// `Added<AudioFeedsTo>`: looks up `(src_node, target_node)` from each
-/// side's `AudioNode` component and calls
-/// `graph.connect(src_node, src_port, target_node, dst_port)`.
+/// `Changed<AudioFeedsTo>` (including `Added`): looks up
+/// `(src_node, target_node)` from each side's `AudioNode` component and
+/// connects every port pair the link's [`FeedPorts`] asks for (adding a
+/// downmix node for stereo → mono). A link edited in place or re-inserted
+/// with a new target first disconnects what it wired before; a change
+/// that leaves the wiring as it was is a no-op.
 /// `RemovedComponents<AudioFeedsTo>`: disconnects the recorded
-/// `(target_node, dst_port)` pair.
+/// destination ports and removes the downmix node.
+///
+/// We track what each source wired ([`WiredFeed`]) in a [`Local`] map so
+/// the despawn path can find the destination ports to disconnect (the
+/// removed component value is unreadable). On removal we look up the
+/// *target's* current `AudioNode` (target may have despawned, in which
+/// case there's nothing to disconnect — fundsp drops the edge when either
+/// endpoint is removed).
+///
+/// A link that would close a loop is left unwired, reported once as an
+/// [`AudioRoutingError`] and retried every frame (see [`super::feedback`]).
 ///
-/// We track `(src_entity → (target_entity, dst_port))` in a [`Local`]
-/// map so the despawn path can find the destination port to disconnect
-/// (the removed component value is unreadable). On removal we look up
-/// the *target's* current `AudioNode` (target may have despawned, in
-/// which case there's nothing to disconnect — fundsp drops the edge
-/// when either endpoint is removed).
+/// Deferred (with a one-time warning) when source or target is missing
+/// `AudioNode` — a scene-loaded or pending-load endpoint that hasn't been
+/// built yet. Deferred links are retried every frame until both
+/// endpoints carry `AudioNode` or the link is removed. The plugin runs
+/// this after `promote_pending_samplers`, `process_pending_vst2_builds`
+/// and `rehydrate_audio_nodes`, so a node built this frame is wired this
+/// frame.
 ///
-/// Skipped (with a warning) when:
-/// - source or target is missing `AudioNode` (likely a misconfigured
-///   spawn order — caller should ensure both endpoints carry
-///   `AudioNode` before inserting `AudioFeedsTo`).
-/// - the target has fewer inputs than `dst_port` (a
-///   misconfigured port — calling `connect` past the input count
-///   would panic in fundsp's `Net`).
+/// The source side connects from the entity's [`ChannelStrip`] when it
+/// has one. A strip spliced in after the link was wired re-issues the
+/// link from the strip (whose channel count may differ from the unit's),
+/// and so does a new `AudioNode` on either endpoint.
+///
+/// Skipped (with a warning) when the source has no output at `src_port`
+/// or the target no input at `dst_port` (calling `connect` past the
+/// port count would panic in fundsp's `Net`); retried when the link or
+/// either endpoint's `AudioNode` changes.
+#[allow(
+    clippy::type_complexity,
+    reason = "Bevy queries are tuple-shaped by design"
+)]
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Bevy systems take their dependencies as parameters"
+)]
 pub fn reconcile_audio_routing(
-    mut tracked: Local<std::collections::HashMap<Entity, (Entity, u32)>>,
+    mut tracked: Local<std::collections::HashMap<Entity, (AudioConnection, WiredFeed)>>,
+    mut deferred: Local<std::collections::HashSet<Entity>>,
+    mut blocked: Local<std::collections::HashSet<Entity>>,
     graph: Option<ResMut<TuttiGraphRes>>,
     mut dirty: ResMut<GraphDirty>,
-    added: Query<(Entity, &AudioFeedsTo), Added<AudioFeedsTo>>,
-    nodes: Query<&AudioNode>,
+    mut routed: ResMut<RoutedEdges>,
+    mut errors: MessageWriter<AudioRoutingError>,
+    changed: Query<Entity, Changed<AudioFeedsTo>>,
+    restripped: Query<Entity, (Added<ChannelStrip>, With<AudioFeedsTo>)>,
+    renoded: Query<Entity, Changed<AudioNode>>,
+    links: Query<(Entity, &AudioFeedsTo)>,
+    nodes: Query<(&AudioNode, Option<&ChannelStrip>)>,
     mut removed: RemovedComponents<AudioFeedsTo>,
 ) {
     let Some(mut graph) = graph else {
         for entity in removed.read() {
             tracked.remove(&entity);
+            deferred.remove(&entity);
+            blocked.remove(&entity);
         }
         return;
     };
 
-    for (src_entity, link) in added.iter() {
-        let target_entity = link.target;
-        let Ok(src_node) = nodes.get(src_entity) else {
-            bevy_log::warn!(
-                "AudioFeedsTo: source {:?} has no AudioNode; skipping connect",
-                src_entity
-            );
-            continue;
-        };
-        let Ok(target_node) = nodes.get(target_entity) else {
-            bevy_log::warn!(
-                "AudioFeedsTo: target {:?} has no AudioNode; skipping connect",
-                target_entity
-            );
+    for src_entity in removed.read() {
+        deferred.remove(&src_entity);
+        blocked.remove(&src_entity);
+        let Some((_, wired)) = tracked.remove(&src_entity) else {
             continue;
         };
-        let target_inputs = graph.0.inputs(target_node.0);
-        if (link.dst_port as usize) >= target_inputs {
-            bevy_log::warn!(
-                "AudioFeedsTo: target {:?} has only {} inputs (dst_port={} out of range); skipping connect",
-                target_entity,
-                target_inputs,
-                link.dst_port
-            );
-            continue;
-        }
-        graph
-            .0
-            .connect(src_node.0, link.src_port as usize, target_node.0, link.dst_port as usize);
-        tracked.insert(src_entity, (target_entity, link.dst_port));
+        let target_id = nodes.get(wired.target).ok().map(|(n, _)| n.0);
+        unwire_feed(&mut graph, &wired, target_id);
+        routed.remove(src_entity, wired.target);
         dirty.0 = true;
     }
 
-    for src_entity in removed.read() {
-        let Some((target_entity, dst_port)) = tracked.remove(&src_entity) else {
+    let renoded: std::collections::HashSet<Entity> = renoded.iter().collect();
+    let mut candidates: Vec<Entity> = changed.iter().collect();
+    let retries = deferred.iter().chain(blocked.iter()).copied();
+    for entity in restripped.iter().chain(retries) {
+        if !candidates.contains(&entity) {
+            candidates.push(entity);
+        }
+    }
+    if !renoded.is_empty() {
+        for (entity, link) in links.iter() {
+            let touched = renoded.contains(&entity) || renoded.contains(&link.target);
+            if touched && !candidates.contains(&entity) {
+                candidates.push(entity);
+            }
+        }
+    }
+
+    for src_entity in candidates {
+        let Ok((_, link)) = links.get(src_entity) else {
+            deferred.remove(&src_entity);
+            blocked.remove(&src_entity);
             continue;
         };
-        let Ok(target_node) = nodes.get(target_entity) else {
-            // Target despawned along with the link; nothing to disconnect.
+        let target_entity = link.target;
+        let (Ok((src_node, src_strip)), Ok((target_node, _))) =
+            (nodes.get(src_entity), nodes.get(target_entity))
+        else {
+            if deferred.insert(src_entity) {
+                bevy_log::warn!(
+                    "AudioFeedsTo: {:?} -> {:?} has an endpoint without AudioNode; deferring connect",
+                    src_entity,
+                    target_entity
+                );
+            }
             continue;
         };
-        if !graph.0.contains(target_node.0) {
+        deferred.remove(&src_entity);
+        let link = AudioConnection::from(*link);
+        let rebuilt = restripped.contains(src_entity)
+            || renoded.contains(&src_entity)
+            || renoded.contains(&target_entity);
+        if let Some((previous, wired)) = tracked.remove(&src_entity) {
+            if previous == link && !rebuilt {
+                tracked.insert(src_entity, (previous, wired));
+                continue;
+            }
+            let previous_target = nodes.get(wired.target).ok().map(|(n, _)| n.0);
+            unwire_feed(&mut graph, &wired, previous_target);
+            routed.remove(src_entity, wired.target);
+            dirty.0 = true;
+        }
+        if let Err(error) = routed.check(src_entity, target_entity) {
+            report_blocked(&mut blocked, src_entity, error, &mut errors);
             continue;
         }
-        if (dst_port as usize) >= graph.0.inputs(target_node.0) {
-            // Mismatched inputs (unlikely — would mean the unit was swapped
-            // under us). Skip rather than panic.
+        blocked.remove(&src_entity);
+        let src_id = output_node(src_node, src_strip);
+        if let Some(wired) = wire_feed(&mut graph, src_entity, src_id, target_node.0, &link) {
+            routed.insert(src_entity, target_entity);
+            tracked.insert(src_entity, (link, wired));
+            dirty.0 = true;
+        }
+    }
+}
+
+/// Splits `wired` against `desired`: edges to drop, then edges to add.
+/// Duplicates in `desired` count once.
+fn diff_connections(
+    wired: &[AudioConnection],
+    desired: &[AudioConnection],
+) -> (Vec<AudioConnection>, Vec<AudioConnection>) {
+    let removed = wired
+        .iter()
+        .filter(|edge| !desired.contains(edge))
+        .copied()
+        .collect();
+    let mut added: Vec<AudioConnection> = Vec::new();
+    for edge in desired {
+        if !wired.contains(edge) && !added.contains(edge) {
+            added.push(*edge);
+        }
+    }
+    (removed, added)
+}
+
+/// Reconciles [`AudioConnections`] into graph operations by diffing.
+///
+/// For each source whose list changed, the edges wired last time are
+/// compared with the list: edges no longer present are disconnected
+/// (their destination ports only, plus any downmix node) and new edges
+/// are wired, each exactly like an [`AudioFeedsTo`] with the same ports.
+/// Unchanged edges are left alone. Removing the component disconnects
+/// every edge it wired.
+///
+/// Edges with an endpoint that has no `AudioNode` yet are deferred, with
+/// a one-time warning per source, and retried every frame. A source that
+/// gains a [`ChannelStrip`] or a new `AudioNode` has all its edges
+/// re-wired; a target with a new `AudioNode` has its edges re-wired, as
+/// do edges skipped for an out-of-range port. Edges that would close a
+/// loop are reported once as an [`AudioRoutingError`] and retried every
+/// frame.
+#[allow(
+    clippy::type_complexity,
+    reason = "Bevy queries are tuple-shaped by design"
+)]
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Bevy systems take their dependencies as parameters"
+)]
+pub fn reconcile_audio_connections(
+    mut tracked: Local<std::collections::HashMap<Entity, Vec<(AudioConnection, WiredFeed)>>>,
+    mut deferred: Local<std::collections::HashSet<Entity>>,
+    mut blocked: Local<std::collections::HashSet<(Entity, AudioConnection)>>,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    mut dirty: ResMut<GraphDirty>,
+    mut routed: ResMut<RoutedEdges>,
+    mut errors: MessageWriter<AudioRoutingError>,
+    changed: Query<Entity, Changed<AudioConnections>>,
+    restripped: Query<Entity, (Added<ChannelStrip>, With<AudioConnections>)>,
+    renoded: Query<Entity, Changed<AudioNode>>,
+    lists: Query<(Entity, &AudioConnections)>,
+    nodes: Query<(&AudioNode, Option<&ChannelStrip>)>,
+    mut removed: RemovedComponents<AudioConnections>,
+) {
+    let Some(mut graph) = graph else {
+        for entity in removed.read() {
+            tracked.remove(&entity);
+            deferred.remove(&entity);
+            blocked.retain(|(src, _)| *src != entity);
+        }
+        return;
+    };
+    let target_id = |target: Entity| nodes.get(target).ok().map(|(n, _)| n.0);
+
+    for src_entity in removed.read() {
+        deferred.remove(&src_entity);
+        blocked.retain(|(src, _)| *src != src_entity);
+        for (_, wired) in tracked.remove(&src_entity).unwrap_or_default() {
+            unwire_feed(&mut graph, &wired, target_id(wired.target));
+            routed.remove(src_entity, wired.target);
+            dirty.0 = true;
+        }
+    }
+
+    let renoded: std::collections::HashSet<Entity> = renoded.iter().collect();
+    let mut candidates: Vec<Entity> = changed.iter().collect();
+    let retries = deferred
+        .iter()
+        .copied()
+        .chain(blocked.iter().map(|(src, _)| *src));
+    for entity in restripped.iter().chain(retries) {
+        if !candidates.contains(&entity) {
+            candidates.push(entity);
+        }
+    }
+    if !renoded.is_empty() {
+        for (entity, list) in lists.iter() {
+            let touched =
+                renoded.contains(&entity) || list.iter().any(|edge| renoded.contains(&edge.target));
+            if touched && !candidates.contains(&entity) {
+                candidates.push(entity);
+            }
+        }
+    }
+
+    for src_entity in candidates {
+        let Ok((_, list)) = lists.get(src_entity) else {
+            deferred.remove(&src_entity);
+            blocked.retain(|(src, _)| *src != src_entity);
             continue;
+        };
+        let mut edges = tracked.remove(&src_entity).unwrap_or_default();
+        let src_rebuilt = restripped.contains(src_entity) || renoded.contains(&src_entity);
+        edges.retain(|(edge, wired)| {
+            if src_rebuilt || renoded.contains(&edge.target) {
+                unwire_feed(&mut graph, wired, target_id(wired.target));
+                routed.remove(src_entity, wired.target);
+                dirty.0 = true;
+                false
+            } else {
+                true
+            }
+        });
+
+        let wired_edges: Vec<AudioConnection> = edges.iter().map(|(edge, _)| *edge).collect();
+        let (to_remove, to_add) = diff_connections(&wired_edges, &list.0);
+        edges.retain(|(edge, wired)| {
+            if to_remove.contains(edge) {
+                unwire_feed(&mut graph, wired, target_id(wired.target));
+                routed.remove(src_entity, wired.target);
+                dirty.0 = true;
+                false
+            } else {
+                true
+            }
+        });
+
+        blocked.retain(|(src, edge)| *src != src_entity || list.0.contains(edge));
+        let mut waiting = false;
+        for edge in to_add {
+            let (Ok((src_node, src_strip)), Some(dst_id)) =
+                (nodes.get(src_entity), target_id(edge.target))
+            else {
+                waiting = true;
+                continue;
+            };
+            if let Err(error) = routed.check(src_entity, edge.target) {
+                report_blocked(&mut blocked, (src_entity, edge), error, &mut errors);
+                continue;
+            }
+            blocked.remove(&(src_entity, edge));
+            let src_id = output_node(src_node, src_strip);
+            if let Some(wired) = wire_feed(&mut graph, src_entity, src_id, dst_id, &edge) {
+                routed.insert(src_entity, edge.target);
+                edges.push((edge, wired));
+                dirty.0 = true;
+            }
         }
-        graph.0.disconnect(target_node.0, dst_port as usize);
-        dirty.0 = true;
+        if waiting {
+            if deferred.insert(src_entity) {
+                bevy_log::warn!(
+                    "AudioConnections: {:?} has an edge with an endpoint without AudioNode; deferring",
+                    src_entity
+                );
+            }
+        } else {
+            deferred.remove(&src_entity);
+        }
+        tracked.insert(src_entity, edges);
     }
 }
 
@@ -216,7 +697,9 @@ mod tests {
 
         let mut app = App::new();
         app.insert_resource(crate::resources::TuttiGraphRes(graph));
-        app.init_resource::<GraphDirty>();
+        app.init_resource::<GraphDirty>()
+            .init_resource::<RoutedEdges>()
+            .add_message::<AudioRoutingError>();
         app.configure_sets(
             bevy_app::Update,
             (
@@ -237,6 +720,23 @@ mod tests {
         app
     }
 
+    /// Just [`reconcile_audio_routing`], no sets and no `commit_graph`,
+    /// so a raised [`GraphDirty`] stays observable after the update.
+    fn routing_only_app() -> App {
+        let TuttiEngine { graph, .. } = TuttiEngine::builder()
+            .inputs(0)
+            .outputs(2)
+            .build()
+            .expect("build engine");
+        let mut app = App::new();
+        app.insert_resource(crate::resources::TuttiGraphRes(graph));
+        app.init_resource::<GraphDirty>()
+            .init_resource::<RoutedEdges>()
+            .add_message::<AudioRoutingError>();
+        app.add_systems(bevy_app::Update, reconcile_audio_routing);
+        app
+    }
+
     #[test]
     fn audio_feeds_to_grows_relationship_target() {
         // Pure relationship machinery — Bevy populates AudioFedBy
@@ -323,6 +823,236 @@ mod tests {
         assert!(!dirty.0, "commit_graph cleared the dirty flag");
     }
 
+    #[test]
+    fn audio_feeds_to_connects_once_endpoints_are_built() {
+        // Scene-load shape: the link arrives before either endpoint has
+        // an AudioNode. The reconciler defers, then connects on the
+        // frame both nodes exist. No commit_graph here, so the dirty
+        // flag raised by the connect stays observable.
+        use tutti::core::ecs::NodeKind;
+        use tutti::dsp::sine_hz;
+
+        let mut app = routing_only_app();
+
+        let src = app.world_mut().spawn_empty().id();
+        let target = app.world_mut().spawn_empty().id();
+        app.world_mut()
+            .entity_mut(src)
+            .insert(AudioFeedsTo::between(target, 0, 0));
+        app.update();
+        assert!(!app.world().resource::<GraphDirty>().0);
+
+        let (src_id, target_id) = {
+            let mut graph = app
+                .world_mut()
+                .resource_mut::<crate::resources::TuttiGraphRes>();
+            (
+                graph.0.add(sine_hz::<f32>(440.0)),
+                graph.0.add(tutti::dsp::pass() | tutti::dsp::pass()),
+            )
+        };
+        app.world_mut()
+            .entity_mut(src)
+            .insert((AudioNode(src_id), NodeKind::Generator));
+        app.world_mut()
+            .entity_mut(target)
+            .insert((AudioNode(target_id), NodeKind::Generic));
+        app.update();
+
+        assert!(
+            app.world().resource::<GraphDirty>().0,
+            "deferred link connected"
+        );
+    }
+
+    #[test]
+    fn stereo_feed_adapts_channel_counts() {
+        use tutti::dsp::sine_hz;
+
+        let TuttiEngine { graph, .. } = TuttiEngine::builder()
+            .inputs(0)
+            .outputs(2)
+            .build()
+            .expect("build engine");
+        let mut graph = crate::resources::TuttiGraphRes(graph);
+        let mono = graph.0.add(sine_hz::<f32>(440.0));
+        let stereo = graph.0.add(tutti::dsp::pass() | tutti::dsp::pass());
+        let mono_in = graph.0.add(tutti::dsp::pass());
+        let mut world = World::new();
+        let (src, target) = (world.spawn_empty().id(), world.spawn_empty().id());
+
+        // Mono → stereo duplicates the one channel.
+        let link = AudioFeedsTo::stereo(target).into();
+        let wired = wire_feed(&mut graph, src, mono, stereo, &link).expect("wired");
+        assert_eq!(wired.dst_ports, vec![0, 1]);
+        assert!(wired.adapter.is_none());
+
+        // Stereo → mono sums through an adapter node, removed on unwire.
+        let link = AudioFeedsTo::all(target).into();
+        let wired = wire_feed(&mut graph, src, stereo, mono_in, &link).expect("wired");
+        assert_eq!(wired.dst_ports, vec![0]);
+        let adapter = wired.adapter.expect("downmix node");
+        assert!(graph.0.contains(adapter));
+        unwire_feed(&mut graph, &wired, Some(mono_in));
+        assert!(!graph.0.contains(adapter));
+
+        // Single stays a single wire even between stereo nodes.
+        let link = AudioFeedsTo::between(target, 1, 0).into();
+        let wired = wire_feed(&mut graph, src, stereo, stereo, &link).expect("wired");
+        assert_eq!(wired.dst_ports, vec![0]);
+
+        // Out-of-range ports are skipped.
+        let link = AudioFeedsTo::between(target, 0, 2).into();
+        assert!(wire_feed(&mut graph, src, mono, stereo, &link).is_none());
+    }
+
+    #[test]
+    fn connection_diff_touches_only_changed_edges() {
+        let mut world = World::new();
+        let (dry, fx, aux) = (
+            world.spawn_empty().id(),
+            world.spawn_empty().id(),
+            world.spawn_empty().id(),
+        );
+        let wired = [AudioConnection::stereo(dry), AudioConnection::stereo(fx)];
+        let desired = [
+            AudioConnection::stereo(dry),
+            AudioConnection::mono(aux),
+            AudioConnection::mono(aux),
+        ];
+        let (removed, added) = diff_connections(&wired, &desired);
+        assert_eq!(removed, vec![AudioConnection::stereo(fx)]);
+        assert_eq!(added, vec![AudioConnection::mono(aux)]);
+
+        // Same target, different ports: a different edge.
+        let (removed, added) = diff_connections(
+            &[AudioConnection::mono(dry)],
+            &[AudioConnection::stereo(dry)],
+        );
+        assert_eq!(removed, vec![AudioConnection::mono(dry)]);
+        assert_eq!(added, vec![AudioConnection::stereo(dry)]);
+    }
+
+    #[test]
+    fn audio_connections_fan_out_and_disconnect() {
+        use crate::graph::reconcile::SpawnAudioNode;
+        use tutti::core::ecs::NodeKind;
+        use tutti::dsp::sine_hz;
+
+        let mut app = test_app();
+        app.add_systems(
+            bevy_app::Update,
+            reconcile_audio_connections.in_set(GraphReconcileSystems::Spawn),
+        );
+        let src = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
+            .id();
+        let targets: Vec<Entity> = (0..2)
+            .map(|_| {
+                app.world_mut()
+                    .commands()
+                    .spawn_audio_node(tutti::dsp::pass() | tutti::dsp::pass(), NodeKind::Generic)
+                    .id()
+            })
+            .collect();
+        app.update();
+
+        app.world_mut().entity_mut(src).insert(
+            AudioConnections::new()
+                .to(AudioConnection::stereo(targets[0]))
+                .to(AudioConnection::stereo(targets[1])),
+        );
+        app.update();
+        assert!(
+            !app.world().resource::<GraphDirty>().0,
+            "connected and committed"
+        );
+
+        app.world_mut()
+            .get_mut::<AudioConnections>(src)
+            .unwrap()
+            .0
+            .pop();
+        app.update();
+        app.world_mut().entity_mut(src).remove::<AudioConnections>();
+        app.update();
+        assert!(app.world().get::<AudioNode>(targets[1]).is_some());
+    }
+
+    #[test]
+    fn audio_feeds_to_rewires_on_change_only() {
+        use tutti::core::ecs::NodeKind;
+        use tutti::dsp::sine_hz;
+
+        // No commit_graph, so the dirty flag shows whether the reconciler
+        // touched the graph.
+        let mut app = routing_only_app();
+
+        let (src_id, first_id, second_id) = {
+            let mut graph = app
+                .world_mut()
+                .resource_mut::<crate::resources::TuttiGraphRes>();
+            (
+                graph.0.add(sine_hz::<f32>(440.0)),
+                graph.0.add(tutti::dsp::pass() | tutti::dsp::pass()),
+                graph.0.add(tutti::dsp::pass() | tutti::dsp::pass()),
+            )
+        };
+        let first = app
+            .world_mut()
+            .spawn((AudioNode(first_id), NodeKind::Generic))
+            .id();
+        let second = app
+            .world_mut()
+            .spawn((AudioNode(second_id), NodeKind::Generic))
+            .id();
+        let src = app
+            .world_mut()
+            .spawn((
+                AudioNode(src_id),
+                NodeKind::Generator,
+                AudioFeedsTo::mono(first),
+            ))
+            .id();
+        app.update();
+        assert!(app.world().resource::<GraphDirty>().0);
+
+        // Touched but unchanged: nothing to do.
+        app.world_mut().resource_mut::<GraphDirty>().0 = false;
+        app.world_mut()
+            .get_mut::<AudioFeedsTo>(src)
+            .unwrap()
+            .set_changed();
+        app.update();
+        assert!(!app.world().resource::<GraphDirty>().0);
+
+        // Port edited in place.
+        app.world_mut()
+            .get_mut::<AudioFeedsTo>(src)
+            .unwrap()
+            .dst_port = 1;
+        app.update();
+        assert!(app.world().resource::<GraphDirty>().0);
+
+        // Re-inserted with a new target.
+        app.world_mut().resource_mut::<GraphDirty>().0 = false;
+        app.world_mut()
+            .entity_mut(src)
+            .insert(AudioFeedsTo::stereo(second));
+        app.update();
+        assert!(app.world().resource::<GraphDirty>().0);
+        assert!(app
+            .world()
+            .get::<AudioFedBy>(first)
+            .map_or(true, |f| f.is_empty()));
+        assert_eq!(
+            app.world().get::<AudioFedBy>(second).map(|f| f.len()),
+            Some(1)
+        );
+    }
+
     #[test]
     fn audio_feeds_to_disconnects_on_remove() {
         use crate::graph::reconcile::SpawnAudioNode;
@@ -362,4 +1092,54 @@ mod tests {
         // acceptable, as long as the entry is gone).
         assert!(fed_by.map_or(true, |f| f.is_empty()));
     }
+
+    #[test]
+    fn audio_feeds_to_rejects_cycle_until_broken() {
+        use bevy_ecs::message::Messages;
+        use tutti::core::ecs::NodeKind;
+
+        let mut app = routing_only_app();
+
+        let (a_id, b_id) = {
+            let mut graph = app
+                .world_mut()
+                .resource_mut::<crate::resources::TuttiGraphRes>();
+            (
+                graph.0.add(tutti::dsp::pass()),
+                graph.0.add(tutti::dsp::pass()),
+            )
+        };
+        let a = app
+            .world_mut()
+            .spawn((AudioNode(a_id), NodeKind::Generic))
+            .id();
+        let b = app
+            .world_mut()
+            .spawn((AudioNode(b_id), NodeKind::Generic, AudioFeedsTo::mono(a)))
+            .id();
+        app.update();
+
+        app.world_mut().entity_mut(a).insert(AudioFeedsTo::mono(b));
+        app.update();
+        let errors = app.world().resource::<Messages<AudioRoutingError>>();
+        let reported: Vec<_> = errors.get_cursor().read(errors).cloned().collect();
+        assert_eq!(
+            reported,
+            vec![AudioRoutingError::Cycle {
+                source: a,
+                target: b,
+                path: vec![b, a],
+            }]
+        );
+        let routed = app.world().resource::<RoutedEdges>();
+        assert!(routed.targets(a).next().is_none(), "loop left unwired");
+
+        // Retried quietly, then wired once the other direction goes away.
+        app.update();
+        app.world_mut().entity_mut(b).remove::<AudioFeedsTo>();
+        app.update();
+        let routed = app.world().resource::<RoutedEdges>();
+        assert_eq!(routed.targets(a).collect::<Vec<_>>(), vec![b]);
+        assert!(routed.targets(b).next().is_none());
+    }
 }
@@ -10,9 +10,10 @@
 //!   entity. Insert it once at synth-spawn time (reading
 //!   `PolySynth::midi_unit_id()` / `SoundFontUnit::midi_unit_id()`); the
 //!   marker means "this entity *is* the MIDI sink for that unit-id."
-//! - [`ScheduledMidi`] — "fire this MIDI event in `remaining_secs` at
-//!   the synth on `target`." [`tick_scheduled_midi`] counts the timer
-//!   down and dispatches via [`MidiBusRes`](crate::MidiBusRes).
+//! - [`ScheduledMidi`] — "fire this MIDI event at the synth on `target`
+//!   after N seconds / at beat N / after N beats." [`tick_scheduled_midi`]
+//!   tracks the timer and dispatches via [`MidiBusRes`](crate::MidiBusRes),
+//!   with a sample offset for beat timings.
 //!
 //! The host owns scheduling (`commands.spawn(ScheduledMidi { ... })`);
 //! the system owns delivery. Once fired, the entity is despawned.
@@ -24,7 +25,9 @@ use bevy_ecs::prelude::*;
 use tutti::core::MidiUnitId;
 use tutti::midi::MidiEvent;
 
-use crate::resources::MidiBusRes;
+use crate::resources::{AudioConfig, MidiBusRes, TransportRes};
+use crate::tempo_map::TempoMap;
+use crate::transport::BeatWindow;
 
 /// "This entity owns the audio-graph node whose MIDI sink id is `midi_unit_id`."
 ///
@@ -51,12 +54,31 @@ impl MidiSynthMarker {
     }
 }
 
-/// "Fire `event` at the synth on `target` in `remaining_secs`."
+/// When a [`ScheduledMidi`] fires.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MidiTiming {
+    /// After this many wall-clock seconds, regardless of the transport.
+    /// Frame-accurate.
+    AfterSecs(f32),
+    /// When the transport playhead reaches this beat. Loop-aware: a beat
+    /// inside the loop behind the playhead fires after the wrap, and a
+    /// beat past the loop end fires at the wrap, on the next pass.
+    AtBeat(f64),
+    /// After the transport has played this many beats. Counts across
+    /// loop wraps and stops counting while the transport is paused.
+    AfterBeats(f64),
+}
+
+/// "Fire `event` at the synth on `target` at `timing`."
 ///
 /// `target` should be an entity carrying [`MidiSynthMarker`]; if it
-/// doesn't, [`tick_scheduled_midi`] logs and despawns. `remaining_secs`
-/// is decremented by the per-frame transport delta time and the event
-/// fires when it hits zero.
+/// doesn't, [`tick_scheduled_midi`] logs and despawns.
+///
+/// Beat timings follow the transport (tempo, pause, loop wrap) and are
+/// delivered ahead of time with a sample offset, so they land on the
+/// right sample rather than the next frame. A beat timing whose beat the
+/// playhead will never reach (behind it, outside any loop) fires
+/// straight away.
 ///
 /// One scheduled event per entity. Despawn happens automatically after
 /// dispatch, so the host can spawn an unbounded series without manual
@@ -67,20 +89,97 @@ impl MidiSynthMarker {
 pub struct ScheduledMidi {
     pub target: Entity,
     pub event: MidiEvent,
-    pub remaining_secs: f32,
+    pub timing: MidiTiming,
+}
+
+impl ScheduledMidi {
+    /// Fire after `remaining_secs` wall-clock seconds. Same as
+    /// [`ScheduledMidi::after_secs`].
+    pub fn new(target: Entity, event: MidiEvent, remaining_secs: f32) -> Self {
+        Self::after_secs(target, event, remaining_secs)
+    }
+
+    pub fn after_secs(target: Entity, event: MidiEvent, secs: f32) -> Self {
+        Self {
+            target,
+            event,
+            timing: MidiTiming::AfterSecs(secs),
+        }
+    }
+
+    pub fn at_beat(target: Entity, event: MidiEvent, beat: f64) -> Self {
+        Self {
+            target,
+            event,
+            timing: MidiTiming::AtBeat(beat),
+        }
+    }
+
+    pub fn after_beats(target: Entity, event: MidiEvent, beats: f64) -> Self {
+        Self {
+            target,
+            event,
+            timing: MidiTiming::AfterBeats(beats),
+        }
+    }
+
+    /// Seconds left on an [`MidiTiming::AfterSecs`] timer; `None` for beat
+    /// timings.
+    pub fn remaining_secs(&self) -> Option<f32> {
+        match self.timing {
+            MidiTiming::AfterSecs(secs) => Some(secs),
+            _ => None,
+        }
+    }
+}
+
+/// Beats from the window's playhead to `at`, or `None` when the playhead
+/// will never reach it. A beat past the end of the loop the playhead is
+/// in is taken as the wrap itself.
+fn at_beat_ahead(w: &BeatWindow, at: f64) -> Option<f64> {
+    match (w.beats_until(at), w.loop_range) {
+        (None, Some((start, end))) if start < end && w.beat < end && at >= end => {
+            Some(end - w.beat)
+        }
+        (ahead, _) => ahead,
+    }
 }
 
-/// Counts down [`ScheduledMidi`] timers and dispatches the event when
-/// they reach zero. Fires through [`MidiBusRes`].
+/// Sample offset into the next block for a point `beats_ahead` of the
+/// playhead, or `None` when it falls in a later block of `block_size`
+/// frames. Without a block size, anything inside the window counts.
+fn frame_offset(w: &BeatWindow, beats_ahead: f64, block_size: Option<usize>) -> Option<usize> {
+    let offset = w.samples_ahead(beats_ahead);
+    match block_size {
+        Some(block_size) if offset >= block_size => None,
+        _ => Some(offset),
+    }
+}
+
+/// Counts down [`ScheduledMidi`] timers and dispatches each event through
+/// [`MidiBusRes`] when it comes due.
 ///
 /// Wall-clock dt is measured per-system via a [`Local<Instant>`] so we
-/// don't force the host to install `bevy_time`. Hosts that prefer
-/// transport-beat scheduling (loop-aware, tempo-aware) should use
-/// `tutti-core`'s sequencer instead.
+/// don't force the host to install `bevy_time`; `AfterSecs` counts that
+/// down. Beat timings are checked against a [`BeatWindow`] one frame
+/// long: anything due inside the next block ([`AudioConfig::block_size`])
+/// is queued now with its sample offset into that block, and the rest
+/// stays scheduled for a later tick. With a live stream the block size
+/// isn't known, so everything due before the next frame goes out with
+/// its offset from the window start. Without [`TransportRes`] /
+/// [`AudioConfig`], beat timings wait.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Bevy systems take their dependencies as parameters"
+)]
 pub fn tick_scheduled_midi(
     mut commands: Commands,
     midi: Option<Res<MidiBusRes>>,
+    transport: Option<Res<TransportRes>>,
+    config: Option<Res<AudioConfig>>,
+    tempo_map: Option<Res<TempoMap>>,
     mut last_tick: Local<Option<Instant>>,
+    mut last_beat: Local<Option<f64>>,
     mut scheduled: Query<(Entity, &mut ScheduledMidi)>,
     targets: Query<&MidiSynthMarker>,
 ) {
@@ -93,11 +192,56 @@ pub fn tick_scheduled_midi(
     };
     *last_tick = Some(now);
 
-    for (entity, mut sched) in scheduled.iter_mut() {
-        sched.remaining_secs -= dt;
-        if sched.remaining_secs > 0.0 {
-            continue;
+    let beat = transport.as_ref().map(|t| t.current_beat());
+    let window = match (&transport, &config) {
+        (Some(transport), Some(config)) if transport.is_playing() => {
+            let window = BeatWindow::from_transport(&transport.0, config.sample_rate, dt as f64);
+            Some(match &tempo_map {
+                Some(map) => window.with_tempo_map(map),
+                None => window,
+            })
         }
+        _ => None,
+    };
+    let advanced = match (window, *last_beat) {
+        (Some(w), Some(prev)) => w.advanced_since(prev),
+        _ => 0.0,
+    };
+    *last_beat = beat;
+    let block_size = config.as_ref().and_then(|config| config.block_size);
+
+    for (entity, mut sched) in scheduled.iter_mut() {
+        let offset = match &mut sched.timing {
+            MidiTiming::AfterSecs(remaining) => {
+                *remaining -= dt;
+                if *remaining > 0.0 {
+                    continue;
+                }
+                0
+            }
+            MidiTiming::AtBeat(at) => {
+                let Some(w) = window else { continue };
+                match at_beat_ahead(&w, *at) {
+                    Some(ahead) if !w.is_due(ahead) => continue,
+                    Some(ahead) => match frame_offset(&w, ahead, block_size) {
+                        Some(offset) => offset,
+                        None => continue,
+                    },
+                    None => 0,
+                }
+            }
+            MidiTiming::AfterBeats(remaining) => {
+                let Some(w) = window else { continue };
+                *remaining -= advanced;
+                if !w.is_due(*remaining) {
+                    continue;
+                }
+                match frame_offset(&w, *remaining, block_size) {
+                    Some(offset) => offset,
+                    None => continue,
+                }
+            }
+        };
 
         let Ok(marker) = targets.get(sched.target) else {
             bevy_log::warn!(
@@ -108,7 +252,61 @@ pub fn tick_scheduled_midi(
             continue;
         };
 
-        midi.0.queue(marker.midi_unit_id, &[sched.event]);
+        let mut event = sched.event;
+        event.frame_offset = offset;
+        midi.0.queue(marker.midi_unit_id, &[event]);
         commands.entity(entity).despawn();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(beat: f64, loop_range: Option<(f64, f64)>) -> BeatWindow {
+        BeatWindow {
+            beat,
+            length_beats: 0.5,
+            tempo: 120.0,
+            sample_rate: 48_000.0,
+            loop_range,
+        }
+    }
+
+    #[test]
+    fn at_beat_past_loop_end_fires_at_the_wrap() {
+        let w = window(3.75, Some((0.0, 4.0)));
+        assert_eq!(at_beat_ahead(&w, 6.0), Some(0.25));
+        assert_eq!(at_beat_ahead(&w, 1.0), Some(1.25));
+        // Early in the loop it waits for the wrap rather than firing now.
+        assert!(!w.is_due(at_beat_ahead(&window(1.0, Some((0.0, 4.0))), 6.0).unwrap()));
+        // Not looping: behind the playhead is never reached.
+        assert_eq!(at_beat_ahead(&window(3.75, None), 1.0), None);
+    }
+
+    #[test]
+    fn frame_offset_only_covers_the_next_block() {
+        let w = window(0.0, None);
+        // 120 BPM at 48 kHz: a hundredth of a beat is 240 samples.
+        assert_eq!(frame_offset(&w, 0.01, Some(256)), Some(240));
+        // Past the next block: left for a later tick.
+        assert_eq!(frame_offset(&w, 0.01, Some(64)), None);
+        assert_eq!(frame_offset(&w, 0.01, Some(240)), None);
+        // Without a block size, anything in the window goes out.
+        assert_eq!(frame_offset(&w, 0.01, None), Some(240));
+    }
+
+    #[test]
+    fn remaining_secs_reads_wall_clock_timers_only() {
+        let target = Entity::PLACEHOLDER;
+        let event = MidiEvent::note_on(0, 0, 60, 100 << 9);
+        assert_eq!(
+            ScheduledMidi::new(target, event, 0.5).remaining_secs(),
+            Some(0.5)
+        );
+        assert_eq!(
+            ScheduledMidi::at_beat(target, event, 4.0).remaining_secs(),
+            None
+        );
+    }
+}
@@ -0,0 +1,629 @@
+//! Beat-timed parameter changes.
+//!
+//! Writing `Frequency` or `Volume` from a system lands whenever that
+//! frame runs, so a tempo-synced filter step or a gain cut on the
+//! downbeat drifts with the frame rate. [`ScheduledParamChange`] says
+//! "set this parameter to this value at transport beat N" instead, using
+//! the same parameter names as [`ParamSmoothing`](super::ParamSmoothing)
+//! ([`SmoothedParam`]) and writing the same components (`Volume`,
+//! `Frequency`, `WetMix`, …) when the beat arrives.
+//!
+//! Changes are handed to the audio thread as soon as they're scheduled
+//! and applied on the sample the transport crosses `at_beat`:
+//!
+//! - `Volume` and `Pan` on an entity with a [`ChannelStrip`] are queued
+//!   on the strip.
+//! - Unit parameters (`Frequency`, `WetMix`, `Volume` on a sampler, …)
+//!   are queued on the [`ParamRamps`] driver, which calls the unit's
+//!   typed setter on that sample. The unit picks its parameters up once
+//!   per block, so these land on the block boundary next to the beat
+//!   rather than on its exact sample.
+//! - Anything else — a parameter neither can apply on the entity's node
+//!   kind — is dropped with a warning.
+//!
+//! Either way, the component is updated on the first frame after the
+//! beat so ECS state stays in step with what's audible. Scheduled changes
+//! are steps: they bypass the entity's `ParamSmoothing`.
+//!
+//! A change for a beat already behind the playhead fires on the next
+//! frame, even with the transport stopped; anything else waits for the
+//! transport to roll past it. Removing an entity's [`ScheduledParamQueue`]
+//! cancels everything on it, including changes already handed to the
+//! audio thread.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::EntityCommands;
+use bevy_reflect::prelude::*;
+
+use tutti::core::ecs::{AudioNode, Mute, NodeKind, Pan, Volume};
+
+use super::smoothing::{ParamRamps, ParamSmoothing, ParamTarget, SmoothedParam};
+use super::strip::{has_typed_gain, strip_gain, ChannelStrip};
+use crate::resources::{TransportRes, TuttiGraphRes};
+
+/// "Set `param` on this entity to `value` at transport beat `at_beat`."
+///
+/// A trigger: insert it on the node entity and
+/// [`collect_scheduled_param_changes`] moves it into the entity's
+/// [`ScheduledParamQueue`]. To schedule several changes in one frame use
+/// [`ScheduleParamChange::schedule_param_change`], which appends to the
+/// queue directly.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Clone)]
+pub struct ScheduledParamChange {
+    pub param: SmoothedParam,
+    pub value: f32,
+    pub at_beat: f64,
+}
+
+impl ScheduledParamChange {
+    pub fn new(param: SmoothedParam, value: f32, at_beat: f64) -> Self {
+        Self {
+            param,
+            value,
+            at_beat,
+        }
+    }
+}
+
+/// Pending [`ScheduledParamChange`]s for this entity, in insertion order.
+///
+/// Maintained by [`collect_scheduled_param_changes`] and
+/// [`dispatch_scheduled_param_changes`]; removed once empty.
+#[derive(Component, Debug, Default, Clone, PartialEq, Reflect)]
+#[reflect(Component, Default, Clone)]
+pub struct ScheduledParamQueue {
+    changes: Vec<QueuedChange>,
+}
+
+impl ScheduledParamQueue {
+    /// The changes still waiting for their beat.
+    pub fn pending(&self) -> impl Iterator<Item = &ScheduledParamChange> {
+        self.changes.iter().map(|c| &c.change)
+    }
+
+    fn push(&mut self, change: ScheduledParamChange) {
+        self.changes.push(QueuedChange {
+            change,
+            sent_to_strip: false,
+            sent_to_driver: false,
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+struct QueuedChange {
+    change: ScheduledParamChange,
+    /// Already queued on the entity's `ChannelStrip` audio-thread side.
+    sent_to_strip: bool,
+    /// Already queued on the `ParamRamps` driver.
+    sent_to_driver: bool,
+}
+
+/// Extension trait on [`EntityCommands`] for scheduling parameter changes.
+pub trait ScheduleParamChange {
+    /// Queue `param` = `value` at transport beat `at_beat`. Can be called
+    /// any number of times per frame.
+    fn schedule_param_change(
+        &mut self,
+        param: SmoothedParam,
+        value: f32,
+        at_beat: f64,
+    ) -> &mut Self;
+}
+
+impl ScheduleParamChange for EntityCommands<'_> {
+    fn schedule_param_change(
+        &mut self,
+        param: SmoothedParam,
+        value: f32,
+        at_beat: f64,
+    ) -> &mut Self {
+        let change = ScheduledParamChange::new(param, value, at_beat);
+        self.entry::<ScheduledParamQueue>()
+            .or_default()
+            .and_modify(move |mut queue| queue.push(change));
+        self
+    }
+}
+
+/// Moves [`ScheduledParamChange`] triggers into the entity's
+/// [`ScheduledParamQueue`]. Removes the trigger.
+pub fn collect_scheduled_param_changes(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &ScheduledParamChange,
+            Option<&mut ScheduledParamQueue>,
+        ),
+        Added<ScheduledParamChange>,
+    >,
+) {
+    for (entity, change, queue) in query.iter_mut() {
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.remove::<ScheduledParamChange>();
+        match queue {
+            Some(mut queue) => queue.push(*change),
+            None => {
+                let mut queue = ScheduledParamQueue::default();
+                queue.push(*change);
+                entity_commands.insert(queue);
+            }
+        }
+    }
+}
+
+type ScheduleTarget<'w> = (
+    Entity,
+    &'w mut ScheduledParamQueue,
+    Option<&'w AudioNode>,
+    Option<&'w NodeKind>,
+    Option<&'w ChannelStrip>,
+    Option<&'w Volume>,
+    Option<&'w Mute>,
+    Option<&'w Pan>,
+);
+
+/// Delivers queued [`ScheduledParamChange`]s against the transport.
+///
+/// Runs after [`sync_param_ramps`](super::sync_param_ramps) (so freshly
+/// built strips and the [`ParamRamps`] driver can take changes) and
+/// before `Params` (so the component writes are reconciled the same
+/// frame).
+///
+/// Changes are handed to the audio thread straight away: `Volume` /
+/// `Pan` on strip entities to [`ChannelStrip::schedule`], everything
+/// else to [`ParamRamps`]. Changes neither can apply on the entity's
+/// node are dropped with a warning. Once the transport reaches a
+/// change's beat its component is written and [`ParamRamps`] is pointed
+/// at the value so the reconcilers don't ramp towards it.
+///
+/// A removed queue cancels the pending audio-thread changes. So does a
+/// change delivered here while the transport is stopped (the audio
+/// thread would otherwise replay it on the next play); the rest are
+/// re-sent.
+pub fn dispatch_scheduled_param_changes(
+    mut commands: Commands,
+    transport: Option<Res<TransportRes>>,
+    mut ramps: Option<ResMut<ParamRamps>>,
+    mut graph: Option<ResMut<TuttiGraphRes>>,
+    mut query: Query<ScheduleTarget>,
+    strips: Query<&ChannelStrip>,
+    mut removed: RemovedComponents<ScheduledParamQueue>,
+) {
+    // Our own removals only happen once a queue is empty, when the audio
+    // thread has already applied everything, so cancelling is harmless.
+    for entity in removed.read() {
+        if let Ok(strip) = strips.get(entity) {
+            strip.cancel_scheduled();
+        }
+        if let Some(ramps) = ramps.as_mut() {
+            ramps.cancel_scheduled(entity);
+        }
+    }
+
+    let Some(transport) = transport else { return };
+    let beat = transport.current_beat();
+    let playing = transport.is_playing();
+
+    for (entity, mut queue, node, kind, strip, volume, mute, pan) in query.iter_mut() {
+        let target = node.zip(kind).map(|(node, kind)| (entity, node, kind));
+        if let Some(ramps) = ramps.as_mut() {
+            if ramps.take_lost(entity) {
+                for queued in queue.changes.iter_mut() {
+                    queued.sent_to_driver = false;
+                }
+            }
+        }
+        if let Some(kind) = kind.filter(|_| node.is_some()) {
+            reject_unsupported(&mut queue, kind, strip.is_some());
+        }
+        if let (Some(strip), Some(kind)) = (strip, kind) {
+            send_to_strip(&mut queue, strip, kind, volume, mute, pan);
+        }
+        if let (Some(target), Some(ramps), Some(graph)) = (target, ramps.as_mut(), graph.as_mut()) {
+            send_to_driver(&mut queue, ramps, graph, target, mute);
+        }
+
+        let (due, waiting): (Vec<QueuedChange>, Vec<QueuedChange>) = queue
+            .changes
+            .drain(..)
+            .partition(|c| c.change.at_beat <= beat && (playing || c.change.at_beat < beat));
+        queue.changes = waiting;
+
+        if let (Some(strip), Some(kind)) = (strip, kind) {
+            if !playing && due.iter().any(|c| c.sent_to_strip) {
+                strip.cancel_scheduled();
+                for queued in queue.changes.iter_mut() {
+                    queued.sent_to_strip = false;
+                }
+                // Re-send against the values being delivered now, not the
+                // components they're about to replace.
+                let latest = |param| {
+                    due.iter()
+                        .filter(|c| c.change.param == param)
+                        .max_by(|a, b| a.change.at_beat.total_cmp(&b.change.at_beat))
+                        .map(|c| c.change.value)
+                };
+                let volume = latest(SmoothedParam::Volume)
+                    .or(volume.map(|v| v.0))
+                    .map(Volume);
+                let pan = latest(SmoothedParam::Pan).or(pan.map(|p| p.0)).map(Pan);
+                send_to_strip(&mut queue, strip, kind, volume.as_ref(), mute, pan.as_ref());
+            }
+        }
+        if let Some(ramps) = ramps.as_mut() {
+            if !playing && due.iter().any(|c| c.sent_to_driver) {
+                // The rest go again next frame.
+                ramps.cancel_scheduled(entity);
+                for queued in queue.changes.iter_mut() {
+                    queued.sent_to_driver = false;
+                }
+            }
+        }
+
+        for queued in due {
+            let change = queued.change;
+            if let Some(ramps) = ramps.as_mut() {
+                let value = unit_value(&change, mute);
+                match (target, graph.as_mut()) {
+                    _ if queued.sent_to_driver && playing => {
+                        ramps.scheduled_landed(entity, change.param, value);
+                    }
+                    (Some(target), Some(graph))
+                        if !queued.sent_to_strip
+                            && ParamRamps::supports(target.2, change.param) =>
+                    {
+                        if playing {
+                            bevy_log::warn!(
+                                "ScheduledParamChange: parameter driver full; {:?} at beat {} lands frame-accurate",
+                                change.param,
+                                change.at_beat
+                            );
+                        }
+                        ramps.write(graph, target, change.param, value, ParamSmoothing::OFF);
+                    }
+                    _ if queued.sent_to_strip => {
+                        ramps.set_current(entity, change.param, change.value)
+                    }
+                    _ => ramps.step_next(entity, change.param),
+                }
+            }
+            insert_param(&mut commands.entity(entity), change.param, change.value);
+        }
+        if queue.changes.is_empty() {
+            commands.entity(entity).remove::<ScheduledParamQueue>();
+        }
+    }
+}
+
+/// Whether `param` is applied by the entity's [`ChannelStrip`]: `Pan`
+/// always, `Volume` unless the unit has a typed gain of its own.
+fn on_strip(param: SmoothedParam, kind: &NodeKind, has_strip: bool) -> bool {
+    has_strip
+        && match param {
+            SmoothedParam::Pan => true,
+            SmoothedParam::Volume => !has_typed_gain(kind),
+            _ => false,
+        }
+}
+
+/// Drop changes neither the strip nor the [`ParamRamps`] driver can apply
+/// on a `kind` node, rather than landing them up to a frame late.
+fn reject_unsupported(queue: &mut ScheduledParamQueue, kind: &NodeKind, has_strip: bool) {
+    queue.changes.retain(|queued| {
+        let param = queued.change.param;
+        let supported = on_strip(param, kind, has_strip) || ParamRamps::supports(kind, param);
+        if !supported {
+            bevy_log::warn!(
+                "ScheduledParamChange: {:?} can't be applied on the audio thread to a {:?} node; dropped",
+                param,
+                kind
+            );
+        }
+        supported
+    });
+}
+
+/// The value the unit is set to for `change`: a muted sampler stays
+/// silent.
+fn unit_value(change: &ScheduledParamChange, mute: Option<&Mute>) -> f32 {
+    match change.param {
+        SmoothedParam::Volume if mute.is_some_and(|m| m.0) => 0.0,
+        _ => change.value,
+    }
+}
+
+/// Hand not-yet-sent changes the [`ParamRamps`] driver applies to it, in
+/// beat order. Whatever it can't take yet is retried next frame.
+fn send_to_driver(
+    queue: &mut ScheduledParamQueue,
+    ramps: &mut ParamRamps,
+    graph: &mut TuttiGraphRes,
+    target: ParamTarget,
+    mute: Option<&Mute>,
+) {
+    let mut order: Vec<usize> = (0..queue.changes.len())
+        .filter(|&i| {
+            let queued = &queue.changes[i];
+            !queued.sent_to_strip
+                && !queued.sent_to_driver
+                && ParamRamps::supports(target.2, queued.change.param)
+        })
+        .collect();
+    order.sort_by(|&a, &b| {
+        queue.changes[a]
+            .change
+            .at_beat
+            .total_cmp(&queue.changes[b].change.at_beat)
+    });
+    for i in order {
+        let queued = &mut queue.changes[i];
+        let change = queued.change;
+        let value = unit_value(&change, mute);
+        if !ramps.schedule(graph, target, change.param, change.at_beat, value) {
+            break;
+        }
+        queued.sent_to_driver = true;
+    }
+}
+
+/// Hand not-yet-sent `Volume` / `Pan` changes to the strip, in beat
+/// order, each carrying the other parameter's value as of that beat.
+fn send_to_strip(
+    queue: &mut ScheduledParamQueue,
+    strip: &ChannelStrip,
+    kind: &NodeKind,
+    volume: Option<&Volume>,
+    mute: Option<&Mute>,
+    pan: Option<&Pan>,
+) {
+    let mut order: Vec<usize> = (0..queue.changes.len())
+        .filter(|&i| on_strip(queue.changes[i].change.param, kind, true))
+        .collect();
+    if order.iter().all(|&i| queue.changes[i].sent_to_strip) {
+        return;
+    }
+    order.sort_by(|&a, &b| {
+        queue.changes[a]
+            .change
+            .at_beat
+            .total_cmp(&queue.changes[b].change.at_beat)
+    });
+
+    let muted = mute.is_some_and(|m| m.0);
+    let mut volume = volume.map_or(1.0, |v| v.0);
+    let mut pan = pan.map_or(0.0, |p| p.0);
+    for i in order {
+        let queued = &mut queue.changes[i];
+        match queued.change.param {
+            SmoothedParam::Volume => volume = queued.change.value,
+            _ => pan = queued.change.value,
+        }
+        if queued.sent_to_strip {
+            continue;
+        }
+        let gain = strip_gain(kind, volume, muted);
+        if !strip.schedule(queued.change.at_beat, gain, pan) {
+            bevy_log::warn!(
+                "ScheduledParamChange: strip queue full; {:?} at beat {} lands frame-accurate",
+                queued.change.param,
+                queued.change.at_beat
+            );
+        }
+        queued.sent_to_strip = true;
+    }
+}
+
+/// Write `value` into the component `param` names.
+fn insert_param(entity: &mut EntityCommands, param: SmoothedParam, value: f32) {
+    #[cfg(feature = "dsp")]
+    use tutti::core::ecs::{
+        DelayTime, Feedback, FilterQ, Frequency, GainDb, ModDepth, ModRate, WetMix,
+    };
+
+    match param {
+        SmoothedParam::Volume => {
+            entity.insert(Volume(value));
+        }
+        SmoothedParam::Pan => {
+            entity.insert(Pan(value));
+        }
+        #[cfg(feature = "sampler")]
+        SmoothedParam::SamplerSpeed => {
+            entity.insert(tutti::core::ecs::SamplerSpeed(value));
+        }
+        #[cfg(feature = "dsp")]
+        SmoothedParam::Frequency => {
+            entity.insert(Frequency(value));
+        }
+        #[cfg(feature = "dsp")]
+        SmoothedParam::FilterQ => {
+            entity.insert(FilterQ(value));
+        }
+        #[cfg(feature = "dsp")]
+        SmoothedParam::GainDb => {
+            entity.insert(GainDb(value));
+        }
+        #[cfg(feature = "dsp")]
+        SmoothedParam::DelayTime => {
+            entity.insert(DelayTime(value));
+        }
+        #[cfg(feature = "dsp")]
+        SmoothedParam::Feedback => {
+            entity.insert(Feedback(value));
+        }
+        #[cfg(feature = "dsp")]
+        SmoothedParam::WetMix => {
+            entity.insert(WetMix(value));
+        }
+        #[cfg(feature = "dsp")]
+        SmoothedParam::ModRate => {
+            entity.insert(ModRate(value));
+        }
+        #[cfg(feature = "dsp")]
+        SmoothedParam::ModDepth => {
+            entity.insert(ModDepth(value));
+        }
+        #[allow(unreachable_patterns)]
+        other => {
+            bevy_log::warn!(
+                "ScheduledParamChange: {:?} needs a feature that isn't enabled; dropped",
+                other
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::reconcile::GraphDirty;
+    use crate::graph::smoothing::sync_param_ramps;
+    use bevy_app::App;
+    use tutti::TuttiEngine;
+
+    fn test_app() -> App {
+        let engine = TuttiEngine::builder()
+            .inputs(0)
+            .outputs(2)
+            .build()
+            .expect("build engine");
+        let TuttiEngine {
+            graph, transport, ..
+        } = engine;
+
+        let mut app = App::new();
+        app.insert_resource(TransportRes(transport));
+        app.insert_resource(TuttiGraphRes(graph));
+        app.init_resource::<GraphDirty>();
+        app.init_resource::<ParamRamps>();
+        app.add_systems(
+            bevy_app::Update,
+            (
+                sync_param_ramps,
+                collect_scheduled_param_changes,
+                dispatch_scheduled_param_changes,
+            )
+                .chain(),
+        );
+        app
+    }
+
+    #[test]
+    fn due_change_writes_component_and_future_change_waits() {
+        let mut app = test_app();
+        let entity = app.world_mut().spawn(Volume(1.0)).id();
+        app.world_mut()
+            .commands()
+            .entity(entity)
+            .schedule_param_change(SmoothedParam::Volume, 0.25, -1.0)
+            .schedule_param_change(SmoothedParam::Pan, 0.5, 1024.0);
+        app.update();
+        app.update();
+
+        assert_eq!(app.world().get::<Volume>(entity).map(|v| v.0), Some(0.25));
+        assert!(app.world().get::<Pan>(entity).is_none());
+        let queue = app
+            .world()
+            .get::<ScheduledParamQueue>(entity)
+            .expect("queue");
+        assert_eq!(queue.pending().count(), 1);
+        // Cleared, so the reconcilers land it as a step.
+        assert_eq!(
+            app.world()
+                .resource::<ParamRamps>()
+                .current(entity, SmoothedParam::Volume),
+            None
+        );
+    }
+
+    #[test]
+    fn trigger_component_is_collected_into_queue() {
+        let mut app = test_app();
+        let entity = app
+            .world_mut()
+            .spawn(ScheduledParamChange::new(SmoothedParam::Pan, -1.0, 512.0))
+            .id();
+        app.update();
+
+        assert!(app.world().get::<ScheduledParamChange>(entity).is_none());
+        let queue = app
+            .world()
+            .get::<ScheduledParamQueue>(entity)
+            .expect("queue");
+        assert_eq!(
+            queue.pending().next(),
+            Some(&ScheduledParamChange::new(SmoothedParam::Pan, -1.0, 512.0))
+        );
+    }
+
+    #[test]
+    fn change_no_audio_thread_path_can_apply_is_dropped() {
+        let mut app = test_app();
+        let entity = app
+            .world_mut()
+            .spawn((AudioNode(tutti::NodeId::new()), NodeKind::Filter))
+            .id();
+        app.world_mut()
+            .commands()
+            .entity(entity)
+            .schedule_param_change(SmoothedParam::Pan, 0.5, 1024.0);
+        app.update();
+        app.update();
+
+        assert!(app.world().get::<ScheduledParamQueue>(entity).is_none());
+        assert!(app.world().get::<Pan>(entity).is_none());
+    }
+
+    #[cfg(feature = "dsp")]
+    #[test]
+    fn filter_change_is_queued_on_the_driver() {
+        use tutti::core::ecs::Frequency;
+        use tutti::units::{StereoSvfFilterNode, SvfType};
+
+        let mut app = test_app();
+        let node = StereoSvfFilterNode::<f64>::new(SvfType::LowPass, 440.0, 0.7);
+        let id = app.world_mut().resource_mut::<TuttiGraphRes>().0.add(node);
+        let entity = app
+            .world_mut()
+            .spawn((AudioNode(id), NodeKind::Filter, Frequency(440.0)))
+            .id();
+        app.world_mut()
+            .commands()
+            .entity(entity)
+            .schedule_param_change(SmoothedParam::Frequency, 2_000.0, 1024.0);
+        app.update();
+        app.update();
+
+        let sent = |app: &App| {
+            let queue = app
+                .world()
+                .get::<ScheduledParamQueue>(entity)
+                .expect("queue");
+            queue.changes.iter().all(|c| c.sent_to_driver)
+        };
+        assert!(sent(&app));
+
+        // Behind the playhead with the transport stopped: lands now, and
+        // the future change is cancelled and handed over again.
+        app.world_mut()
+            .commands()
+            .entity(entity)
+            .schedule_param_change(SmoothedParam::Frequency, 880.0, -1.0);
+        app.update();
+        assert_eq!(
+            app.world().get::<Frequency>(entity).map(|f| f.0),
+            Some(880.0)
+        );
+        assert_eq!(
+            app.world()
+                .resource::<ParamRamps>()
+                .current(entity, SmoothedParam::Frequency),
+            Some(880.0)
+        );
+        app.update();
+        assert!(sent(&app));
+    }
+}
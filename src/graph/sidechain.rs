@@ -7,28 +7,38 @@
 //! on the target side.
 //!
 //! The reconcile system [`reconcile_sidechain_links`] runs in
-//! [`GraphReconcileSystems::Spawn`]: on `Added<SidechainOf>` it looks up
+//! [`GraphReconcileSystems::Spawn`]: on `Changed<SidechainOf>` it looks up
 //! both entities' [`AudioNode`] and calls
-//! `graph.connect(src_node, 0, target_node, 1)`. On
+//! `graph.connect(src_node, 0, target_node, 1)`, disconnecting the
+//! previous target first when the link was re-pointed. On
 //! `RemovedComponents<SidechainOf>` it disconnects the same port.
 //!
 //! Pure graph-op binding — no DAW vocabulary. The DAW concept of "this
 //! compressor's sidechain follows this kick drum's bus" is built on
 //! top of this primitive in dawai/mixer.
 
+use bevy_ecs::entity::{EntityMapper, MapEntities};
+use bevy_ecs::message::MessageWriter;
 use bevy_ecs::prelude::*;
+use bevy_ecs::reflect::{ReflectComponent, ReflectMapEntities};
+use bevy_reflect::Reflect;
 
 use tutti::core::ecs::AudioNode;
 
+use super::feedback::{report_blocked, AudioRoutingError, RoutedEdges};
 use super::reconcile::GraphDirty;
+use super::strip::{output_node, ChannelStrip};
 use crate::resources::TuttiGraphRes;
 
 /// "This entity's audio drives `0`'s sidechain input (port 1)."
 ///
 /// Insert on the *source* entity. The target side automatically grows a
 /// [`SidechainSources`] component listing every source pointing at it.
-#[derive(Component, Debug, Clone, Copy)]
+/// Reflected with `MapEntities` so it round-trips through `DynamicScene`
+/// like [`super::routing::AudioFeedsTo`].
+#[derive(Component, Reflect, Debug, Clone, Copy)]
 #[relationship(relationship_target = SidechainSources)]
+#[reflect(Component, MapEntities)]
 pub struct SidechainOf(pub Entity);
 
 impl SidechainOf {
@@ -39,6 +49,12 @@ impl SidechainOf {
     }
 }
 
+impl MapEntities for SidechainOf {
+    fn map_entities<M: EntityMapper>(&mut self, mapper: &mut M) {
+        self.0 = mapper.get_mapped(self.0);
+    }
+}
+
 /// Auto-maintained list of every entity sidechained into this one.
 ///
 /// Bevy's relationship infrastructure keeps this in sync with
@@ -64,44 +80,122 @@ impl SidechainSources {
 
 /// Reconciles sidechain wiring into graph operations.
 ///
-/// `Added<SidechainOf>`: looks up `(src_node, target_node)` from each side's
-/// `AudioNode` component and calls `graph.connect(src_node, 0, target_node, 1)`.
-/// `RemovedComponents<SidechainOf>`: disconnects port 1 on the target.
+/// `Changed<SidechainOf>` (including `Added`): looks up `(src_node,
+/// target_node)` from each side's `AudioNode` component and calls
+/// `graph.connect(src_node, 0, target_node, 1)`. A link re-pointed at a
+/// new target (edited in place or re-inserted) first disconnects port 1
+/// on the old one. `RemovedComponents<SidechainOf>`: disconnects port 1
+/// on the target.
 ///
 /// We track `(src_entity, target_entity)` pairs in a [`Local`] map keyed by
 /// source entity so we know which target to disconnect from when the
 /// component disappears (the despawn path can't read the removed value).
+/// Links whose endpoints don't carry `AudioNode` yet are deferred and
+/// retried every frame, same as [`super::routing::reconcile_audio_routing`];
+/// sources that gain a [`ChannelStrip`] are re-wired from the strip, and
+/// links are re-wired when either endpoint gets a new `AudioNode` (which
+/// also retries a target skipped for lacking port 1). A link that would
+/// close a loop is reported once as an [`AudioRoutingError`] and retried
+/// every frame.
+#[allow(
+    clippy::type_complexity,
+    reason = "Bevy queries are tuple-shaped by design"
+)]
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Bevy systems take their dependencies as parameters"
+)]
 pub fn reconcile_sidechain_links(
     mut tracked: Local<std::collections::HashMap<Entity, Entity>>,
+    mut deferred: Local<std::collections::HashSet<Entity>>,
+    mut blocked: Local<std::collections::HashSet<Entity>>,
     graph: Option<ResMut<TuttiGraphRes>>,
     mut dirty: ResMut<GraphDirty>,
-    added: Query<(Entity, &SidechainOf), Added<SidechainOf>>,
-    nodes: Query<&AudioNode>,
+    mut routed: ResMut<RoutedEdges>,
+    mut errors: MessageWriter<AudioRoutingError>,
+    changed: Query<Entity, Changed<SidechainOf>>,
+    restripped: Query<Entity, (Added<ChannelStrip>, With<SidechainOf>)>,
+    renoded: Query<Entity, Changed<AudioNode>>,
+    links: Query<(Entity, &SidechainOf)>,
+    nodes: Query<(&AudioNode, Option<&ChannelStrip>)>,
     mut removed: RemovedComponents<SidechainOf>,
 ) {
     let Some(mut graph) = graph else {
         for entity in removed.read() {
             tracked.remove(&entity);
+            deferred.remove(&entity);
+            blocked.remove(&entity);
         }
         return;
     };
 
-    for (src_entity, link) in added.iter() {
-        let target_entity = link.0;
-        let Ok(src_node) = nodes.get(src_entity) else {
-            bevy_log::warn!(
-                "SidechainOf: source {:?} has no AudioNode; skipping connect",
-                src_entity
-            );
+    for src_entity in removed.read() {
+        deferred.remove(&src_entity);
+        blocked.remove(&src_entity);
+        let Some(target_entity) = tracked.remove(&src_entity) else {
             continue;
         };
-        let Ok(target_node) = nodes.get(target_entity) else {
-            bevy_log::warn!(
-                "SidechainOf: target {:?} has no AudioNode; skipping connect",
-                target_entity
-            );
+        routed.remove(src_entity, target_entity);
+        if disconnect_sidechain(&mut graph, &nodes, target_entity) {
+            dirty.0 = true;
+        }
+    }
+
+    let renoded: std::collections::HashSet<Entity> = renoded.iter().collect();
+    let mut candidates: Vec<Entity> = changed.iter().collect();
+    let retries = deferred.iter().chain(blocked.iter()).copied();
+    for entity in restripped.iter().chain(retries) {
+        if !candidates.contains(&entity) {
+            candidates.push(entity);
+        }
+    }
+    if !renoded.is_empty() {
+        for (entity, link) in links.iter() {
+            let touched = renoded.contains(&entity) || renoded.contains(&link.0);
+            if touched && !candidates.contains(&entity) {
+                candidates.push(entity);
+            }
+        }
+    }
+
+    for src_entity in candidates {
+        let Ok((_, link)) = links.get(src_entity) else {
+            deferred.remove(&src_entity);
+            blocked.remove(&src_entity);
             continue;
         };
+        let target_entity = link.0;
+        let (Ok((src_node, src_strip)), Ok((target_node, _))) =
+            (nodes.get(src_entity), nodes.get(target_entity))
+        else {
+            if deferred.insert(src_entity) {
+                bevy_log::warn!(
+                    "SidechainOf: {:?} -> {:?} has an endpoint without AudioNode; deferring connect",
+                    src_entity,
+                    target_entity
+                );
+            }
+            continue;
+        };
+        deferred.remove(&src_entity);
+        let rebuilt = restripped.contains(src_entity)
+            || renoded.contains(&src_entity)
+            || renoded.contains(&target_entity);
+        if let Some(previous) = tracked.remove(&src_entity) {
+            if previous == target_entity && !rebuilt {
+                tracked.insert(src_entity, previous);
+                continue;
+            }
+            routed.remove(src_entity, previous);
+            if previous != target_entity && disconnect_sidechain(&mut graph, &nodes, previous) {
+                dirty.0 = true;
+            }
+        }
+        if let Err(error) = routed.check(src_entity, target_entity) {
+            report_blocked(&mut blocked, src_entity, error, &mut errors);
+            continue;
+        }
+        blocked.remove(&src_entity);
         // Bare oscillators / generators have no input port 1; calling
         // connect on them panics inside fundsp's Net. Skip with a warning
         // so misconfigured wiring is loud but not fatal.
@@ -113,26 +207,32 @@ pub fn reconcile_sidechain_links(
             );
             continue;
         }
-        graph.0.connect(src_node.0, 0, target_node.0, 1);
+        graph
+            .0
+            .connect(output_node(src_node, src_strip), 0, target_node.0, 1);
+        routed.insert(src_entity, target_entity);
         tracked.insert(src_entity, target_entity);
         dirty.0 = true;
     }
+}
 
-    for src_entity in removed.read() {
-        let Some(target_entity) = tracked.remove(&src_entity) else {
-            continue;
-        };
-        let Ok(target_node) = nodes.get(target_entity) else {
-            // Target despawned along with the link; nothing to disconnect.
-            continue;
-        };
-        if graph.0.inputs(target_node.0) < 2 {
-            // We never connected (target had no sidechain input); nothing to undo.
-            continue;
-        }
-        graph.0.disconnect(target_node.0, 1);
-        dirty.0 = true;
+/// Disconnects port 1 on `target_entity`'s node. `false` when there was
+/// nothing to disconnect.
+fn disconnect_sidechain(
+    graph: &mut TuttiGraphRes,
+    nodes: &Query<(&AudioNode, Option<&ChannelStrip>)>,
+    target_entity: Entity,
+) -> bool {
+    let Ok((target_node, _)) = nodes.get(target_entity) else {
+        // Target despawned along with the link; nothing to disconnect.
+        return false;
+    };
+    if !graph.0.contains(target_node.0) || graph.0.inputs(target_node.0) < 2 {
+        // We never connected (target had no sidechain input); nothing to undo.
+        return false;
     }
+    graph.0.disconnect(target_node.0, 1);
+    true
 }
 
 #[cfg(test)]
@@ -148,7 +248,9 @@ mod tests {
 
         let mut app = App::new();
         app.insert_resource(crate::resources::TuttiGraphRes(graph));
-        app.init_resource::<GraphDirty>();
+        app.init_resource::<GraphDirty>()
+            .init_resource::<RoutedEdges>()
+            .add_message::<AudioRoutingError>();
         app.configure_sets(
             bevy_app::Update,
             (
@@ -183,4 +285,97 @@ mod tests {
         assert_eq!(sources.len(), 1);
         assert_eq!(sources.iter().next(), Some(src));
     }
+
+    #[test]
+    fn sidechain_repoints_and_retries_deferred_links() {
+        use tutti::core::ecs::NodeKind;
+        use tutti::dsp::sine_hz;
+
+        // No commit_graph, so the dirty flag shows whether the reconciler
+        // touched the graph.
+        let mut app = test_app();
+        let (src_id, ids) = {
+            let mut graph = app.world_mut().resource_mut::<TuttiGraphRes>();
+            let src_id = graph.0.add(sine_hz::<f32>(440.0));
+            let ids: Vec<_> = (0..3)
+                .map(|_| graph.0.add(tutti::dsp::pass() | tutti::dsp::pass()))
+                .collect();
+            (src_id, ids)
+        };
+        let targets: Vec<Entity> = ids
+            .iter()
+            .map(|&id| {
+                app.world_mut()
+                    .spawn((AudioNode(id), NodeKind::Generic))
+                    .id()
+            })
+            .collect();
+        let src = app
+            .world_mut()
+            .spawn((
+                AudioNode(src_id),
+                NodeKind::Generator,
+                SidechainOf(targets[0]),
+            ))
+            .id();
+        app.update();
+        let routed = |app: &App| {
+            app.world()
+                .resource::<RoutedEdges>()
+                .targets(src)
+                .collect::<Vec<_>>()
+        };
+        assert!(app.world().resource::<GraphDirty>().0);
+        assert_eq!(routed(&app), vec![targets[0]]);
+
+        // Re-pointed in place: the old target is let go.
+        app.world_mut().resource_mut::<GraphDirty>().0 = false;
+        app.world_mut().get_mut::<SidechainOf>(src).unwrap().0 = targets[1];
+        app.update();
+        assert!(app.world().resource::<GraphDirty>().0);
+        assert_eq!(routed(&app), vec![targets[1]]);
+
+        // Re-inserted with a new target: same.
+        app.world_mut().resource_mut::<GraphDirty>().0 = false;
+        app.world_mut()
+            .entity_mut(src)
+            .insert(SidechainOf(targets[2]));
+        app.update();
+        assert!(app.world().resource::<GraphDirty>().0);
+        assert_eq!(routed(&app), vec![targets[2]]);
+
+        // Endpoints without AudioNode defer the link until they're built.
+        let late_src = app.world_mut().spawn_empty().id();
+        let late_target = app.world_mut().spawn_empty().id();
+        app.world_mut()
+            .entity_mut(late_src)
+            .insert(SidechainOf(late_target));
+        app.world_mut().resource_mut::<GraphDirty>().0 = false;
+        app.update();
+        assert!(!app.world().resource::<GraphDirty>().0);
+
+        let (late_src_id, late_target_id) = {
+            let mut graph = app.world_mut().resource_mut::<TuttiGraphRes>();
+            (
+                graph.0.add(sine_hz::<f32>(220.0)),
+                graph.0.add(tutti::dsp::pass() | tutti::dsp::pass()),
+            )
+        };
+        app.world_mut()
+            .entity_mut(late_src)
+            .insert((AudioNode(late_src_id), NodeKind::Generator));
+        app.world_mut()
+            .entity_mut(late_target)
+            .insert((AudioNode(late_target_id), NodeKind::Generic));
+        app.update();
+        assert!(
+            app.world().resource::<GraphDirty>().0,
+            "deferred link connected"
+        );
+        let routed = app.world().resource::<RoutedEdges>();
+        assert_eq!(
+            routed.targets(late_src).collect::<Vec<_>>(),
+            vec![late_target]
+        );
+    }
 }
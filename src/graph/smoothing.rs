@@ -0,0 +1,1099 @@
+//! Click-free parameter changes: per-entity smoothing policy + ramps.
+//!
+//! The typed setters behind `reconcile_*_params` jump straight to the new
+//! value, so a UI drag delivering one value per frame steps audibly
+//! (zipper noise), worst on `Frequency` and `DelayTime`. [`ParamSmoothing`]
+//! turns each change into a ramp from the last written value to the new
+//! one, walked on the audio thread:
+//!
+//! - `Volume` and `Pan` on a [`ChannelStrip`](super::ChannelStrip)
+//!   entity ramp inside the strip node.
+//! - Everything else ramps in the [`ParamRamps`] driver, a silent node
+//!   that holds a clone of each unit it drives and calls the unit's typed
+//!   setter every sample. tutti's setters store into atomics the clone
+//!   shares with the graph's instance, so the unit follows the ramp from
+//!   block to block however often frames run.
+//!
+//! Entities without a [`ParamSmoothing`] use the [`DefaultParamSmoothing`]
+//! resource, seeded from [`TuttiGraphPlugin`](super::TuttiGraphPlugin) and
+//! editable at runtime. The built-in default is [`ParamSmoothing::OFF`],
+//! which keeps the old write-through behaviour.
+//!
+//! Covered parameters are listed on [`SmoothedParam`]. Dynamics
+//! parameters (threshold, ratio, attack, release) and `PluginParam` still
+//! write through immediately: they aren't continuous-audio controls, and
+//! plugins smooth their own automation.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use bevy_reflect::prelude::*;
+
+use crossbeam_channel::{Receiver, Sender};
+
+use tutti::core::ecs::{AudioNode, NodeKind};
+use tutti::dsp::{An, AudioNode as DspNode, Frame, U0};
+
+use super::reconcile::GraphDirty;
+use crate::resources::{TransportRes, TuttiGraphRes};
+
+/// Ramp shape for [`ParamSmoothing`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum SmoothingCurve {
+    /// Straight line from the old value to the new one.
+    #[default]
+    Linear,
+    /// Geometric interpolation — even steps per octave, the natural
+    /// choice for `Frequency` and `DelayTime`. Falls back to linear when
+    /// either end is zero or the signs differ.
+    Exponential,
+    /// Smoothstep: eases in and out of the ramp.
+    SCurve,
+}
+
+/// How parameter changes on this entity are ramped.
+///
+/// Insert alongside the parameter components. A `time_secs` of `0.0`
+/// writes straight through.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Default, Clone)]
+pub struct ParamSmoothing {
+    pub time_secs: f32,
+    pub curve: SmoothingCurve,
+}
+
+impl ParamSmoothing {
+    /// No smoothing: every change is written as-is.
+    pub const OFF: Self = Self {
+        time_secs: 0.0,
+        curve: SmoothingCurve::Linear,
+    };
+
+    pub fn linear(time_secs: f32) -> Self {
+        Self {
+            time_secs,
+            curve: SmoothingCurve::Linear,
+        }
+    }
+
+    pub fn exponential(time_secs: f32) -> Self {
+        Self {
+            time_secs,
+            curve: SmoothingCurve::Exponential,
+        }
+    }
+
+    pub fn s_curve(time_secs: f32) -> Self {
+        Self {
+            time_secs,
+            curve: SmoothingCurve::SCurve,
+        }
+    }
+
+    pub fn is_off(&self) -> bool {
+        self.time_secs <= 0.0
+    }
+
+    /// Value `t` (`0.0..=1.0`) of the way from `from` to `to`.
+    pub fn interpolate(&self, from: f32, to: f32, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self.curve {
+            SmoothingCurve::Linear => from + (to - from) * t,
+            SmoothingCurve::Exponential if from * to > 0.0 => from * (to / from).powf(t),
+            SmoothingCurve::Exponential => from + (to - from) * t,
+            SmoothingCurve::SCurve => from + (to - from) * (t * t * (3.0 - 2.0 * t)),
+        }
+    }
+}
+
+impl Default for ParamSmoothing {
+    fn default() -> Self {
+        Self::OFF
+    }
+}
+
+/// Smoothing applied to entities without their own [`ParamSmoothing`].
+///
+/// Seeded from [`TuttiGraphPlugin::default_smoothing`](super::TuttiGraphPlugin::default_smoothing).
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource, Default, Clone)]
+pub struct DefaultParamSmoothing(pub ParamSmoothing);
+
+/// Parameters that honour [`ParamSmoothing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum SmoothedParam {
+    Volume,
+    Pan,
+    SamplerSpeed,
+    Frequency,
+    FilterQ,
+    GainDb,
+    DelayTime,
+    Feedback,
+    WetMix,
+    ModRate,
+    ModDepth,
+}
+
+/// Lanes the [`ParamRamps`] driver can hold. Sized so the audio thread
+/// never allocates; past it, changes write through.
+const PARAM_LANE_CAPACITY: usize = 256;
+
+/// Commands in flight from the reconcilers to the driver.
+const PARAM_COMMAND_CAPACITY: usize = 1024;
+
+/// Scheduled writes the driver holds at once. [`ParamRamps::schedule`]
+/// stops at this many in flight; the audio side has room for twice as
+/// many, since the main thread only learns a write has landed a block or
+/// so after the fact.
+const PARAM_SCHEDULE_CAPACITY: usize = 256;
+
+/// Parameter target for [`ParamSmoother::write`]: the entity and the node
+/// it owns.
+pub type ParamTarget<'a> = (Entity, &'a AudioNode, &'a NodeKind);
+
+/// Last written value of every smoothed parameter, and the channel to the
+/// audio-thread driver that ramps them and applies scheduled changes.
+///
+/// Maintained by the reconcilers, [`sync_param_ramps`] and
+/// [`dispatch_scheduled_param_changes`](super::dispatch_scheduled_param_changes);
+/// hosts only read it. `current` is the value a parameter is at or
+/// ramping towards.
+#[derive(Resource, Default)]
+pub struct ParamRamps {
+    current: HashMap<(Entity, SmoothedParam), f32>,
+    link: Option<RampLink>,
+    /// Entities whose scheduled writes the driver dropped (unit replaced,
+    /// driver rebuilt) and that need re-sending.
+    lost: HashSet<Entity>,
+}
+
+/// Main-thread end of the driver node.
+struct RampLink {
+    node: tutti::NodeId,
+    follows_transport: bool,
+    commands: Sender<RampCommand>,
+    retired: Receiver<Box<ParamUnit>>,
+    attached: HashSet<(Entity, SmoothedParam)>,
+    /// Scheduled writes handed over and not yet known to have landed.
+    in_flight: HashMap<Entity, usize>,
+}
+
+impl std::fmt::Debug for ParamRamps {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParamRamps")
+            .field("current", &self.current)
+            .field("driver", &self.link.as_ref().map(|l| l.node))
+            .finish()
+    }
+}
+
+impl ParamRamps {
+    /// The value `param` on `entity` was last pointed at, if any.
+    pub fn current(&self, entity: Entity, param: SmoothedParam) -> Option<f32> {
+        self.current.get(&(entity, param)).copied()
+    }
+
+    /// Whether the driver node is in the graph.
+    pub fn has_driver(&self) -> bool {
+        self.link.is_some()
+    }
+
+    /// Whether the driver can set `param` on a `kind` unit, i.e. ramp it
+    /// and apply scheduled changes to it on the audio thread.
+    pub fn supports(kind: &NodeKind, param: SmoothedParam) -> bool {
+        ParamUnit::supports(kind, param)
+    }
+
+    /// Point `param` at `value`.
+    ///
+    /// The first value seen for a parameter, and any change with
+    /// smoothing off, lands immediately. Otherwise the driver ramps from
+    /// the current value. Writes through the typed setter when there's
+    /// no driver (or it's full).
+    pub(crate) fn write(
+        &mut self,
+        graph: &mut TuttiGraphRes,
+        target: ParamTarget,
+        param: SmoothedParam,
+        value: f32,
+        smoothing: ParamSmoothing,
+    ) {
+        let (entity, node, kind) = target;
+        let from = match self.current.insert((entity, param), value) {
+            Some(from) if from == value => return,
+            from => from,
+        };
+        let smoothing = if from.is_some() {
+            smoothing
+        } else {
+            ParamSmoothing::OFF
+        };
+        if !self.send_ramp(graph, target, param, from, value, smoothing) {
+            write_effect_param(graph, node, kind, param, value);
+        }
+    }
+
+    /// Hand a ramp to the driver, attaching a lane for `param` first if
+    /// the ramp needs one. `false` means the caller writes through.
+    fn send_ramp(
+        &mut self,
+        graph: &mut TuttiGraphRes,
+        target: ParamTarget,
+        param: SmoothedParam,
+        from: Option<f32>,
+        to: f32,
+        smoothing: ParamSmoothing,
+    ) -> bool {
+        let entity = target.0;
+        let attached = self
+            .link
+            .as_ref()
+            .is_some_and(|l| l.attached.contains(&(entity, param)));
+        // A step needs no lane.
+        if !attached && (smoothing.is_off() || !self.attach(graph, target, param, from)) {
+            return false;
+        }
+        let Some(link) = self.link.as_ref() else {
+            return false;
+        };
+        link.commands
+            .try_send(RampCommand::Ramp {
+                entity,
+                param,
+                target: to,
+                smoothing,
+            })
+            .is_ok()
+    }
+
+    /// Give the driver a lane for `param` on `target`, starting at
+    /// `value` (left alone when `None`). `true` if it has one.
+    fn attach(
+        &mut self,
+        graph: &mut TuttiGraphRes,
+        (entity, node, kind): ParamTarget,
+        param: SmoothedParam,
+        value: Option<f32>,
+    ) -> bool {
+        let Some(link) = self.link.as_mut() else {
+            return false;
+        };
+        let key = (entity, param);
+        if link.attached.contains(&key) {
+            return true;
+        }
+        if link.attached.len() >= PARAM_LANE_CAPACITY {
+            return false;
+        }
+        let Some(unit) = ParamUnit::from_graph(graph, node, kind) else {
+            return false;
+        };
+        let attach = RampCommand::Attach {
+            entity,
+            param,
+            unit: Box::new(unit),
+            value,
+        };
+        if link.commands.try_send(attach).is_err() {
+            return false;
+        }
+        link.attached.insert(key);
+        true
+    }
+
+    /// Queue `param` = `value` on `target` for the sample the transport
+    /// crosses `at_beat`. The driver writes it through the unit's typed
+    /// setter on the audio thread; a beat already behind the playhead
+    /// lands on the next block.
+    ///
+    /// `false` when there's no driver, it can't set `param` on this unit,
+    /// or it's full; try again next frame.
+    pub(crate) fn schedule(
+        &mut self,
+        graph: &mut TuttiGraphRes,
+        target: ParamTarget,
+        param: SmoothedParam,
+        at_beat: f64,
+        value: f32,
+    ) -> bool {
+        let entity = target.0;
+        let full = self.link.as_ref().is_none_or(|l| {
+            !l.follows_transport || l.in_flight.values().sum::<usize>() >= PARAM_SCHEDULE_CAPACITY
+        });
+        let current = self.current(entity, param);
+        if full || !self.attach(graph, target, param, current) {
+            return false;
+        }
+        let Some(link) = self.link.as_mut() else {
+            return false;
+        };
+        let write = ScheduledWrite {
+            entity,
+            param,
+            at_beat,
+            value,
+        };
+        if link
+            .commands
+            .try_send(RampCommand::Schedule(write))
+            .is_err()
+        {
+            return false;
+        }
+        *link.in_flight.entry(entity).or_default() += 1;
+        true
+    }
+
+    /// Record `value` as current without writing it, because the audio
+    /// thread already has (a scheduled strip change).
+    pub(crate) fn set_current(&mut self, entity: Entity, param: SmoothedParam, value: f32) {
+        self.current.insert((entity, param), value);
+    }
+
+    /// A [`schedule`](Self::schedule)d write's beat has passed, so the
+    /// driver has applied it: record `value` as current so the
+    /// reconcilers don't write it again.
+    pub(crate) fn scheduled_landed(&mut self, entity: Entity, param: SmoothedParam, value: f32) {
+        self.set_current(entity, param, value);
+        let Some(link) = self.link.as_mut() else {
+            return;
+        };
+        if let Some(count) = link.in_flight.get_mut(&entity) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                link.in_flight.remove(&entity);
+            }
+        }
+    }
+
+    /// Drop every [`schedule`](Self::schedule)d write for `entity` the
+    /// driver hasn't applied yet.
+    pub(crate) fn cancel_scheduled(&mut self, entity: Entity) {
+        self.lost.remove(&entity);
+        let Some(link) = self.link.as_mut() else {
+            return;
+        };
+        if link.in_flight.remove(&entity).is_some() {
+            let _ = link.commands.try_send(RampCommand::Cancel { entity });
+        }
+    }
+
+    /// Whether `entity`'s scheduled writes were dropped by the driver
+    /// since the last call and need sending again.
+    pub(crate) fn take_lost(&mut self, entity: Entity) -> bool {
+        self.lost.remove(&entity)
+    }
+
+    /// Land the next write of `param` on `entity` as a step, bypassing
+    /// its smoothing (a scheduled change arriving).
+    pub(crate) fn step_next(&mut self, entity: Entity, param: SmoothedParam) {
+        self.current.remove(&(entity, param));
+    }
+
+    /// Stop driving `entity`'s unit, e.g. because it was replaced. Its
+    /// next smoothed change attaches the new unit; its pending scheduled
+    /// writes go with the old one and are re-sent.
+    pub fn detach(&mut self, entity: Entity) {
+        let Some(link) = self.link.as_mut() else {
+            return;
+        };
+        let before = link.attached.len();
+        link.attached.retain(|(e, _)| *e != entity);
+        if link.in_flight.remove(&entity).is_some() {
+            self.lost.insert(entity);
+        }
+        if link.attached.len() != before {
+            let _ = link.commands.try_send(RampCommand::Detach { entity });
+        }
+    }
+
+    /// Drop all state for `entity`.
+    pub fn forget(&mut self, entity: Entity) {
+        self.current.retain(|(e, _), _| *e != entity);
+        self.detach(entity);
+        self.lost.remove(&entity);
+    }
+
+    /// Add the driver node unless it's already in `graph` (and following
+    /// the transport, if there is one). Returns whether the graph
+    /// changed. A rebuilt driver starts empty: lanes re-attach on their
+    /// next change and scheduled writes are re-sent.
+    fn ensure_driver(
+        &mut self,
+        graph: &mut TuttiGraphRes,
+        transport: Option<&tutti::TransportHandle>,
+    ) -> bool {
+        if let Some(link) = &self.link {
+            let installed = graph.0.contains(link.node);
+            if installed && (link.follows_transport || transport.is_none()) {
+                return false;
+            }
+            if installed {
+                graph.0.remove(link.node);
+            }
+        }
+        if let Some(old) = self.link.take() {
+            self.lost.extend(old.in_flight.into_keys());
+        }
+        let (commands, receiver) = crossbeam_channel::bounded(PARAM_COMMAND_CAPACITY);
+        let (retire, retired) = crossbeam_channel::bounded(PARAM_LANE_CAPACITY);
+        let driver = ParamRampNode::new(receiver, retire, transport.cloned());
+        let node = graph.0.add(An(driver));
+        self.link = Some(RampLink {
+            node,
+            follows_transport: transport.is_some(),
+            commands,
+            retired,
+            attached: HashSet::new(),
+            in_flight: HashMap::new(),
+        });
+        true
+    }
+}
+
+/// Reconciler-side access to [`ParamRamps`] + the smoothing policy.
+///
+/// Both resources are optional so the reconcilers keep working in bare
+/// apps (and tests) that don't install [`TuttiGraphPlugin`](super::TuttiGraphPlugin):
+/// without them every write goes straight through.
+#[derive(SystemParam)]
+pub struct ParamSmoother<'w> {
+    ramps: Option<ResMut<'w, ParamRamps>>,
+    default: Option<Res<'w, DefaultParamSmoothing>>,
+}
+
+impl ParamSmoother<'_> {
+    /// Point `param` on `target` at `value`, ramped on the audio thread
+    /// per the entity's own [`ParamSmoothing`] (`smoothing`) or the
+    /// default. See [`ParamRamps`].
+    pub fn write(
+        &mut self,
+        graph: &mut TuttiGraphRes,
+        target: ParamTarget,
+        param: SmoothedParam,
+        value: f32,
+        smoothing: Option<&ParamSmoothing>,
+    ) {
+        let smoothing = self.resolve(smoothing);
+        match self.ramps.as_mut() {
+            Some(ramps) => ramps.write(graph, target, param, value, smoothing),
+            None => write_effect_param(graph, target.1, target.2, param, value),
+        }
+    }
+
+    /// For parameters ramped on the audio thread (strip `Volume` /
+    /// `Pan`): records `target` as current and returns the smoothing to
+    /// ramp with. Like [`write`](Self::write), the first value and a value
+    /// already in place (a scheduled change) land without a ramp.
+    pub fn policy(
+        &mut self,
+        entity: Entity,
+        param: SmoothedParam,
+        target: f32,
+        smoothing: Option<&ParamSmoothing>,
+    ) -> ParamSmoothing {
+        let smoothing = self.resolve(smoothing);
+        let Some(ramps) = self.ramps.as_mut() else {
+            return ParamSmoothing::OFF;
+        };
+        match ramps.current.insert((entity, param), target) {
+            Some(from) if from != target => smoothing,
+            _ => ParamSmoothing::OFF,
+        }
+    }
+
+    fn resolve(&self, smoothing: Option<&ParamSmoothing>) -> ParamSmoothing {
+        smoothing
+            .copied()
+            .or(self.default.as_ref().map(|d| d.0))
+            .unwrap_or(ParamSmoothing::OFF)
+    }
+}
+
+/// A clone of a unit the driver calls typed setters on.
+#[derive(Clone)]
+enum ParamUnit {
+    #[cfg(feature = "sampler")]
+    Sampler(tutti::sampler::SamplerUnit),
+    #[cfg(feature = "dsp")]
+    Filter(tutti::units::StereoSvfFilterNode<f64>),
+    #[cfg(feature = "dsp")]
+    Delay(tutti::units::StereoDelayLineNode),
+    #[cfg(feature = "dsp")]
+    Chorus(tutti::units::ChorusNode),
+}
+
+impl ParamUnit {
+    #[allow(unused_variables)]
+    fn supports(kind: &NodeKind, param: SmoothedParam) -> bool {
+        match *kind {
+            #[cfg(feature = "sampler")]
+            NodeKind::Sampler => {
+                matches!(param, SmoothedParam::Volume | SmoothedParam::SamplerSpeed)
+            }
+            #[cfg(feature = "dsp")]
+            NodeKind::Filter => matches!(
+                param,
+                SmoothedParam::Frequency | SmoothedParam::FilterQ | SmoothedParam::GainDb
+            ),
+            #[cfg(feature = "dsp")]
+            NodeKind::Delay => matches!(
+                param,
+                SmoothedParam::DelayTime | SmoothedParam::Feedback | SmoothedParam::WetMix
+            ),
+            #[cfg(feature = "dsp")]
+            NodeKind::Chorus => matches!(
+                param,
+                SmoothedParam::ModRate
+                    | SmoothedParam::ModDepth
+                    | SmoothedParam::Feedback
+                    | SmoothedParam::WetMix
+            ),
+            _ => false,
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn from_graph(graph: &mut TuttiGraphRes, node: &AudioNode, kind: &NodeKind) -> Option<Self> {
+        match *kind {
+            #[cfg(feature = "sampler")]
+            NodeKind::Sampler => graph
+                .0
+                .node_mut::<tutti::sampler::SamplerUnit>(node.0)
+                .map(|unit| Self::Sampler(unit.clone())),
+            #[cfg(feature = "dsp")]
+            NodeKind::Filter => graph
+                .0
+                .node_mut::<tutti::units::StereoSvfFilterNode<f64>>(node.0)
+                .map(|unit| Self::Filter(unit.clone())),
+            #[cfg(feature = "dsp")]
+            NodeKind::Delay => graph
+                .0
+                .node_mut::<tutti::units::StereoDelayLineNode>(node.0)
+                .map(|unit| Self::Delay(unit.clone())),
+            #[cfg(feature = "dsp")]
+            NodeKind::Chorus => graph
+                .0
+                .node_mut::<tutti::units::ChorusNode>(node.0)
+                .map(|unit| Self::Chorus(unit.clone())),
+            _ => None,
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn write(&mut self, param: SmoothedParam, value: f32) {
+        match *self {
+            #[cfg(feature = "sampler")]
+            Self::Sampler(ref mut unit) => write_sampler(unit, param, value),
+            #[cfg(feature = "dsp")]
+            Self::Filter(ref mut unit) => write_filter(unit, param, value),
+            #[cfg(feature = "dsp")]
+            Self::Delay(ref mut unit) => write_delay(unit, param, value),
+            #[cfg(feature = "dsp")]
+            Self::Chorus(ref mut unit) => write_chorus(unit, param, value),
+        }
+    }
+}
+
+enum RampCommand {
+    /// Start driving `param` on `entity` through `unit`, from `value`
+    /// (left alone when `None`).
+    Attach {
+        entity: Entity,
+        param: SmoothedParam,
+        unit: Box<ParamUnit>,
+        value: Option<f32>,
+    },
+    /// Ramp an attached lane to `target` (a step when `smoothing` is off).
+    Ramp {
+        entity: Entity,
+        param: SmoothedParam,
+        target: f32,
+        smoothing: ParamSmoothing,
+    },
+    /// Write an attached lane when the transport crosses a beat.
+    Schedule(ScheduledWrite),
+    /// Drop `entity`'s pending scheduled writes.
+    Cancel { entity: Entity },
+    /// Release every lane on `entity`, with its scheduled writes.
+    Detach { entity: Entity },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ScheduledWrite {
+    entity: Entity,
+    param: SmoothedParam,
+    at_beat: f64,
+    value: f32,
+}
+
+/// One sample-by-sample ramp, `from` → `to` over `samples`.
+#[derive(Debug, Clone, Copy)]
+struct Ramp {
+    from: f32,
+    to: f32,
+    elapsed: f64,
+    samples: f64,
+    smoothing: ParamSmoothing,
+}
+
+impl Ramp {
+    fn new(from: f32, to: f32, smoothing: ParamSmoothing, sample_rate: f64) -> Self {
+        Self {
+            from,
+            to,
+            elapsed: 0.0,
+            samples: (smoothing.time_secs as f64 * sample_rate).round().max(1.0),
+            smoothing,
+        }
+    }
+
+    /// Advance one sample. Returns the value and whether the ramp is done.
+    fn step(&mut self) -> (f32, bool) {
+        self.elapsed += 1.0;
+        let t = (self.elapsed / self.samples) as f32;
+        (self.smoothing.interpolate(self.from, self.to, t), t >= 1.0)
+    }
+}
+
+#[derive(Clone)]
+struct Lane {
+    entity: Entity,
+    param: SmoothedParam,
+    unit: Box<ParamUnit>,
+    /// Last value written; `NaN` until the lane's first write.
+    value: f32,
+    ramp: Option<Ramp>,
+}
+
+impl Lane {
+    fn set(&mut self, value: f32) {
+        self.ramp = None;
+        self.value = value;
+        self.unit.write(self.param, value);
+    }
+}
+
+/// Audio-thread side of [`ParamRamps`]: zero inputs, zero outputs, ticked
+/// every block whether or not anything is connected.
+///
+/// Walks each lane's ramp sample by sample, and applies scheduled writes
+/// on the sample its transport position crosses their beat — following
+/// the transport the same way [`ChannelStrip`](super::ChannelStrip)'s
+/// node does, interpolating between the once-per-block beat publishes.
+/// Unit clones it lets go of go back to the main thread through
+/// `retired`, so nothing is freed on the audio thread.
+#[derive(Clone)]
+struct ParamRampNode {
+    commands: Receiver<RampCommand>,
+    retired: Sender<Box<ParamUnit>>,
+    lanes: Vec<Lane>,
+    scheduled: Vec<ScheduledWrite>,
+    sample_rate: f64,
+    transport: Option<tutti::TransportHandle>,
+    beat: f64,
+    published_beat: f64,
+}
+
+impl ParamRampNode {
+    fn new(
+        commands: Receiver<RampCommand>,
+        retired: Sender<Box<ParamUnit>>,
+        transport: Option<tutti::TransportHandle>,
+    ) -> Self {
+        Self {
+            commands,
+            retired,
+            lanes: Vec::with_capacity(PARAM_LANE_CAPACITY),
+            scheduled: Vec::with_capacity(PARAM_SCHEDULE_CAPACITY * 2),
+            sample_rate: tutti::dsp::DEFAULT_SR,
+            transport,
+            beat: 0.0,
+            published_beat: f64::NAN,
+        }
+    }
+
+    fn lane(&self, entity: Entity, param: SmoothedParam) -> Option<usize> {
+        self.lanes
+            .iter()
+            .position(|l| l.entity == entity && l.param == param)
+    }
+
+    fn apply(&mut self, command: RampCommand) {
+        match command {
+            RampCommand::Attach {
+                entity,
+                param,
+                mut unit,
+                value,
+            } => {
+                if let Some(value) = value {
+                    unit.write(param, value);
+                }
+                let value = value.unwrap_or(f32::NAN);
+                match self.lane(entity, param) {
+                    Some(i) => {
+                        let lane = &mut self.lanes[i];
+                        lane.value = value;
+                        lane.ramp = None;
+                        let old = std::mem::replace(&mut lane.unit, unit);
+                        let _ = self.retired.try_send(old);
+                    }
+                    None if self.lanes.len() < PARAM_LANE_CAPACITY => self.lanes.push(Lane {
+                        entity,
+                        param,
+                        unit,
+                        value,
+                        ramp: None,
+                    }),
+                    None => {
+                        let _ = self.retired.try_send(unit);
+                    }
+                }
+            }
+            RampCommand::Ramp {
+                entity,
+                param,
+                target,
+                smoothing,
+            } => {
+                let Some(i) = self.lane(entity, param) else {
+                    return;
+                };
+                let sample_rate = self.sample_rate;
+                let lane = &mut self.lanes[i];
+                if smoothing.is_off() || lane.value.is_nan() {
+                    lane.set(target);
+                } else {
+                    lane.ramp = Some(Ramp::new(lane.value, target, smoothing, sample_rate));
+                }
+            }
+            RampCommand::Schedule(write) => {
+                if self.scheduled.len() < self.scheduled.capacity() {
+                    self.scheduled.push(write);
+                } else if let Some(i) = self.lane(write.entity, write.param) {
+                    // Out of room: land it now rather than never.
+                    self.lanes[i].set(write.value);
+                }
+            }
+            RampCommand::Cancel { entity } => {
+                self.scheduled.retain(|w| w.entity != entity);
+            }
+            RampCommand::Detach { entity } => {
+                self.scheduled.retain(|w| w.entity != entity);
+                let mut i = 0;
+                while i < self.lanes.len() {
+                    if self.lanes[i].entity == entity {
+                        let lane = self.lanes.swap_remove(i);
+                        let _ = self.retired.try_send(lane.unit);
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Advance the beat clock by one sample and apply any due writes.
+    fn advance_transport(&mut self) {
+        let Some(transport) = &self.transport else {
+            return;
+        };
+        if !transport.is_playing() || self.scheduled.is_empty() {
+            self.published_beat = f64::NAN;
+            return;
+        }
+        let published = transport.current_beat();
+        if published != self.published_beat {
+            self.published_beat = published;
+            self.beat = published;
+        } else {
+            self.beat += transport.get_tempo().get() / 60.0 / self.sample_rate;
+        }
+
+        let beat = self.beat;
+        let mut i = 0;
+        while i < self.scheduled.len() {
+            let write = self.scheduled[i];
+            if write.at_beat > beat {
+                i += 1;
+                continue;
+            }
+            // `remove`, not `swap_remove`: writes for one lane must land
+            // in the order they were sent (beat order).
+            self.scheduled.remove(i);
+            if let Some(lane) = self.lane(write.entity, write.param) {
+                self.lanes[lane].set(write.value);
+            }
+        }
+    }
+}
+
+impl DspNode for ParamRampNode {
+    const ID: u64 = 0x7475_7474_7072_6d70;
+    type Inputs = U0;
+    type Outputs = U0;
+
+    fn reset(&mut self) {
+        self.published_beat = f64::NAN;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(&mut self, _input: &Frame<f32, Self::Inputs>) -> Frame<f32, Self::Outputs> {
+        while let Ok(command) = self.commands.try_recv() {
+            self.apply(command);
+        }
+        self.advance_transport();
+        for lane in self.lanes.iter_mut() {
+            let Some(ramp) = &mut lane.ramp else {
+                continue;
+            };
+            let (value, done) = ramp.step();
+            lane.value = value;
+            lane.unit.write(lane.param, value);
+            if done {
+                lane.ramp = None;
+            }
+        }
+        Frame::default()
+    }
+}
+
+/// Keeps the [`ParamRamps`] driver in the graph (re-adding it after an
+/// engine rebuild), releases lanes of units that were removed or
+/// replaced, and drops the unit clones the driver handed back.
+///
+/// Runs after [`super::GraphReconcileSystems::Spawn`] and before
+/// `Params`, so the reconcilers and the scheduled-change dispatch find
+/// the driver in place.
+pub fn sync_param_ramps(
+    ramps: Option<ResMut<ParamRamps>>,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    transport: Option<Res<TransportRes>>,
+    mut dirty: Option<ResMut<GraphDirty>>,
+    replaced: Query<Entity, Changed<AudioNode>>,
+    mut removed: RemovedComponents<AudioNode>,
+) {
+    let Some(mut ramps) = ramps else { return };
+    for entity in removed.read() {
+        ramps.forget(entity);
+    }
+    for entity in replaced.iter() {
+        ramps.detach(entity);
+    }
+    let Some(mut graph) = graph else { return };
+    if ramps.ensure_driver(&mut graph, transport.as_ref().map(|t| &t.0)) {
+        if let Some(dirty) = dirty.as_mut() {
+            dirty.0 = true;
+        }
+    }
+    if let Some(link) = &ramps.link {
+        link.retired.try_iter().for_each(drop);
+    }
+}
+
+/// Typed-setter dispatch for the [`SmoothedParam`]s units apply themselves
+/// (everything but strip `Volume` / `Pan`).
+#[allow(unused_variables)]
+pub(crate) fn write_effect_param(
+    graph: &mut TuttiGraphRes,
+    node: &AudioNode,
+    kind: &NodeKind,
+    param: SmoothedParam,
+    value: f32,
+) {
+    match *kind {
+        #[cfg(feature = "sampler")]
+        NodeKind::Sampler => {
+            if let Some(unit) = graph.0.node_mut::<tutti::sampler::SamplerUnit>(node.0) {
+                write_sampler(unit, param, value);
+            }
+        }
+        #[cfg(feature = "dsp")]
+        NodeKind::Filter => {
+            if let Some(unit) = graph
+                .0
+                .node_mut::<tutti::units::StereoSvfFilterNode<f64>>(node.0)
+            {
+                write_filter(unit, param, value);
+            }
+        }
+        #[cfg(feature = "dsp")]
+        NodeKind::Delay => {
+            if let Some(unit) = graph
+                .0
+                .node_mut::<tutti::units::StereoDelayLineNode>(node.0)
+            {
+                write_delay(unit, param, value);
+            }
+        }
+        #[cfg(feature = "dsp")]
+        NodeKind::Chorus => {
+            if let Some(unit) = graph.0.node_mut::<tutti::units::ChorusNode>(node.0) {
+                write_chorus(unit, param, value);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(feature = "sampler")]
+fn write_sampler(unit: &mut tutti::sampler::SamplerUnit, param: SmoothedParam, value: f32) {
+    match param {
+        SmoothedParam::Volume => unit.set_gain(value),
+        SmoothedParam::SamplerSpeed => unit.set_speed(value),
+        _ => {}
+    }
+}
+
+#[cfg(feature = "dsp")]
+fn write_filter(
+    unit: &mut tutti::units::StereoSvfFilterNode<f64>,
+    param: SmoothedParam,
+    value: f32,
+) {
+    match param {
+        SmoothedParam::Frequency => unit.set_frequency(value),
+        SmoothedParam::FilterQ => unit.set_q(value),
+        SmoothedParam::GainDb => unit.set_gain_db(value),
+        _ => {}
+    }
+}
+
+#[cfg(feature = "dsp")]
+fn write_delay(unit: &mut tutti::units::StereoDelayLineNode, param: SmoothedParam, value: f32) {
+    match param {
+        SmoothedParam::DelayTime => unit.set_delay_time(value),
+        SmoothedParam::Feedback => unit.set_feedback(value),
+        SmoothedParam::WetMix => unit.set_mix(value),
+        _ => {}
+    }
+}
+
+#[cfg(feature = "dsp")]
+fn write_chorus(unit: &mut tutti::units::ChorusNode, param: SmoothedParam, value: f32) {
+    match param {
+        SmoothedParam::ModRate => unit.set_rate(value),
+        SmoothedParam::ModDepth => unit.set_depth(value),
+        SmoothedParam::Feedback => unit.set_feedback(value),
+        SmoothedParam::WetMix => unit.set_mix(value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::App;
+    use tutti::TuttiEngine;
+
+    fn test_app() -> App {
+        let engine = TuttiEngine::builder()
+            .inputs(0)
+            .outputs(2)
+            .build()
+            .expect("build engine");
+        let TuttiEngine { graph, .. } = engine;
+
+        let mut app = App::new();
+        app.insert_resource(TuttiGraphRes(graph));
+        app.init_resource::<GraphDirty>();
+        app.init_resource::<ParamRamps>();
+        app.add_systems(bevy_app::Update, sync_param_ramps);
+        app
+    }
+
+    #[test]
+    fn ramp_reaches_target_in_smoothing_time() {
+        // 0.1 s at 100 Hz: ten samples.
+        let mut ramp = Ramp::new(0.0, 1.0, ParamSmoothing::linear(0.1), 100.0);
+        for _ in 0..4 {
+            assert!(!ramp.step().1);
+        }
+        let (value, done) = ramp.step();
+        assert!((value - 0.5).abs() < 1e-6);
+        assert!(!done);
+        for _ in 0..4 {
+            ramp.step();
+        }
+        assert_eq!(ramp.step(), (1.0, true));
+    }
+
+    #[test]
+    fn sub_sample_ramp_lands_on_the_next_sample() {
+        let mut ramp = Ramp::new(0.0, 1.0, ParamSmoothing::linear(1e-6), 48_000.0);
+        assert_eq!(ramp.step(), (1.0, true));
+    }
+
+    #[test]
+    fn exponential_ramp_is_geometric() {
+        let s = ParamSmoothing::exponential(1.0);
+        assert!((s.interpolate(100.0, 400.0, 0.5) - 200.0).abs() < 1e-3);
+        // Zero endpoint falls back to linear.
+        assert!((s.interpolate(0.0, 1.0, 0.5) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn driver_is_added_and_re_added_after_removal() {
+        let mut app = test_app();
+        app.update();
+        let node = {
+            let ramps = app.world().resource::<ParamRamps>();
+            assert!(ramps.has_driver());
+            ramps.link.as_ref().unwrap().node
+        };
+        assert!(app.world().resource::<TuttiGraphRes>().0.contains(node));
+        assert!(app.world().resource::<GraphDirty>().0);
+
+        app.world_mut()
+            .resource_mut::<TuttiGraphRes>()
+            .0
+            .remove(node);
+        app.update();
+        let ramps = app.world().resource::<ParamRamps>();
+        let replacement = ramps.link.as_ref().unwrap().node;
+        assert_ne!(replacement, node);
+        assert!(app
+            .world()
+            .resource::<TuttiGraphRes>()
+            .0
+            .contains(replacement));
+    }
+
+    #[test]
+    fn first_value_and_repeats_are_recorded_once() {
+        let mut app = test_app();
+        let entity = app.world_mut().spawn_empty().id();
+        let node = AudioNode(tutti::NodeId::new());
+        let world = app.world_mut();
+        world.resource_scope(|world, mut ramps: Mut<ParamRamps>| {
+            let mut graph = world.resource_mut::<TuttiGraphRes>();
+            let p = SmoothedParam::Frequency;
+            let smoothing = ParamSmoothing::linear(0.1);
+            ramps.write(
+                &mut graph,
+                (entity, &node, &NodeKind::Filter),
+                p,
+                440.0,
+                smoothing,
+            );
+            assert_eq!(ramps.current(entity, p), Some(440.0));
+            ramps.write(
+                &mut graph,
+                (entity, &node, &NodeKind::Filter),
+                p,
+                880.0,
+                smoothing,
+            );
+            assert_eq!(ramps.current(entity, p), Some(880.0));
+            ramps.forget(entity);
+            assert_eq!(ramps.current(entity, p), None);
+        });
+    }
+}
@@ -0,0 +1,862 @@
+//! Per-entity channel strip: a stereo gain/pan node downstream of the unit.
+//!
+//! Most units have no gain or pan control of their own, so [`Volume`],
+//! [`Mute`] and [`Pan`] can't be written through a typed setter the way
+//! `SamplerSpeed` or `Frequency` are. Instead, an entity that carries any
+//! of them (or opts in with [`WithGainStage`]) gets a [`ChannelStrip`]: a
+//! small 2-in/2-out node fed by the unit's outputs, whose left/right
+//! gains are lock-free `Shared` values smoothed on the audio thread.
+//! The strip also follows the transport on the audio thread, so
+//! beat-timed gain changes ([`ChannelStrip::schedule`], used by
+//! [`super::ScheduledParamChange`]) land on the exact sample.
+//!
+//! Samplers keep writing `Volume`/`Mute` through `SamplerUnit::set_gain`
+//! (see [`super::reconcile_params`]); they only get a strip for `Pan` or
+//! when [`WithGainStage`] asks for one, and that strip stays at unity gain.
+//!
+//! Once a strip exists it *is* the entity's audible output. Routing
+//! ([`super::AudioFeedsTo`]) and sidechain ([`super::SidechainOf`]) take
+//! their source from [`ChannelStrip::node`] automatically, and
+//! [`pipe_audio_node_output`] sends it to the master bus. Hosts that pipe
+//! by hand should pipe [`output_node`] instead of `AudioNode`.
+//!
+//! - [`ensure_channel_strips`] — `Spawn`: builds and splices strips.
+//! - [`reconcile_strip_levels`] — `Params`: `Changed<Volume | Mute | Pan>`
+//!   → strip gains.
+//! - [`reconcile_strip_despawn`] — `Despawn`: removes orphaned strips.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+
+use tutti::core::ecs::{AudioNode, Mute, NodeKind, Pan, Volume};
+use tutti::dsp::{An, AudioNode as DspNode, Frame, Shared, Source, U2};
+
+use super::reconcile::GraphDirty;
+use super::smoothing::{ParamSmoother, ParamSmoothing, SmoothedParam, SmoothingCurve};
+use crate::resources::{TransportRes, TuttiGraphRes};
+
+/// Time constant for the strip's gain smoothing, in seconds.
+const STRIP_SMOOTHING_SECS: f32 = 0.005;
+
+/// Beat-timed gain changes a strip can hold before `schedule` refuses
+/// more. Sized so the audio thread never allocates.
+const STRIP_EVENT_CAPACITY: usize = 64;
+
+/// Opt-in marker: give this entity a [`ChannelStrip`] as soon as its
+/// `AudioNode` exists, even before any `Volume` / `Mute` / `Pan` is set.
+///
+/// Useful when the host wants to wire the strip (e.g. pipe it to master)
+/// on the spawn frame and adjust levels later.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Default)]
+pub struct WithGainStage;
+
+/// The gain/pan node spliced after an entity's unit.
+///
+/// Inserted by [`ensure_channel_strips`]; don't insert it manually.
+///
+/// Not `Reflect`: `node` wraps a foreign fundsp `NodeId` and the gains
+/// are fundsp `Shared` atomics. Scenes rebuild it from `Volume` / `Mute`
+/// / `Pan` / [`WithGainStage`].
+#[derive(Component, Clone)]
+pub struct ChannelStrip {
+    /// The strip's own graph node (2 inputs, 2 outputs).
+    pub node: tutti::NodeId,
+    /// Whether the upstream unit is mono (its single output feeds both
+    /// strip inputs). Selects the pan law.
+    pub mono_source: bool,
+    left: Shared,
+    right: Shared,
+    ramp_secs: Shared,
+    ramp_curve: Shared,
+    events: Sender<StripEvent>,
+    generation: Arc<AtomicU64>,
+}
+
+impl ChannelStrip {
+    /// Publish a new gain and pan position. Takes effect on the audio
+    /// thread within a few milliseconds, ramped to avoid clicks.
+    pub fn set(&self, gain: f32, pan: f32) {
+        self.set_smoothed(gain, pan, ParamSmoothing::OFF);
+    }
+
+    /// Like [`set`](Self::set), but the audio thread ramps the left/right
+    /// gains sample by sample over `smoothing`. [`ParamSmoothing::OFF`]
+    /// keeps the strip's own few-millisecond smoothing.
+    pub fn set_smoothed(&self, gain: f32, pan: f32, smoothing: ParamSmoothing) {
+        // Ramp shape first: the audio thread starts the ramp when it sees
+        // the gains move.
+        self.ramp_secs.set_value(smoothing.time_secs.max(0.0));
+        self.ramp_curve.set_value(curve_index(smoothing.curve));
+        let (l, r) = pan_gains(pan, self.mono_source);
+        self.left.set_value(gain * l);
+        self.right.set_value(gain * r);
+    }
+
+    /// Queue a gain and pan position to take effect on the audio thread
+    /// at transport beat `at_beat`, on the exact sample the transport
+    /// crosses it.
+    ///
+    /// Needs a running transport; a change for a beat that has already
+    /// passed applies on the next block. Returns `false` when the strip's
+    /// queue is full. Later [`set`](Self::set) calls override it as usual;
+    /// [`cancel_scheduled`](Self::cancel_scheduled) withdraws it.
+    pub fn schedule(&self, at_beat: f64, gain: f32, pan: f32) -> bool {
+        let (l, r) = pan_gains(pan, self.mono_source);
+        let event = StripEvent {
+            at_beat,
+            left: gain * l,
+            right: gain * r,
+            generation: self.generation.load(Ordering::Relaxed),
+        };
+        match self.events.try_send(event) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+
+    /// Drop every [`schedule`](Self::schedule)d change the audio thread
+    /// hasn't applied yet.
+    pub fn cancel_scheduled(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl std::fmt::Debug for ChannelStrip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelStrip")
+            .field("node", &self.node)
+            .field("mono_source", &self.mono_source)
+            .field("left", &self.left.value())
+            .field("right", &self.right.value())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StripEvent {
+    at_beat: f64,
+    left: f32,
+    right: f32,
+    /// [`ChannelStrip::cancel_scheduled`] count when sent; stale events
+    /// are dropped.
+    generation: u64,
+}
+
+/// An audio-thread gain ramp started by [`ChannelStrip::set_smoothed`].
+#[derive(Debug, Clone, Copy)]
+struct StripRamp {
+    from: (f32, f32),
+    elapsed: f64,
+    samples: f64,
+    smoothing: ParamSmoothing,
+}
+
+fn curve_index(curve: SmoothingCurve) -> f32 {
+    match curve {
+        SmoothingCurve::Linear => 0.0,
+        SmoothingCurve::Exponential => 1.0,
+        SmoothingCurve::SCurve => 2.0,
+    }
+}
+
+fn curve_from_index(index: f32) -> SmoothingCurve {
+    match index as u8 {
+        1 => SmoothingCurve::Exponential,
+        2 => SmoothingCurve::SCurve,
+        _ => SmoothingCurve::Linear,
+    }
+}
+
+/// Audio-thread side of a [`ChannelStrip`].
+///
+/// Follows the ECS-written `Shared` gains with a one-pole smoother (or a
+/// [`StripRamp`] when the write asked for one), and applies queued
+/// [`StripEvent`]s when its transport position reaches their beat. The
+/// transport only publishes its beat once per block, so the strip
+/// interpolates in between from the tempo and resyncs whenever the
+/// published value moves (block boundary, seek, loop wrap).
+#[derive(Clone)]
+struct StripNode {
+    left: Shared,
+    right: Shared,
+    ramp_secs: Shared,
+    ramp_curve: Shared,
+    seen: (f32, f32),
+    target: (f32, f32),
+    gain: (f32, f32),
+    ramp: Option<StripRamp>,
+    coeff: f32,
+    sample_rate: f64,
+    events: Receiver<StripEvent>,
+    pending: Vec<StripEvent>,
+    generation: Arc<AtomicU64>,
+    transport: Option<tutti::TransportHandle>,
+    beat: f64,
+    published_beat: f64,
+}
+
+impl StripNode {
+    fn new(
+        left: Shared,
+        right: Shared,
+        ramp_secs: Shared,
+        ramp_curve: Shared,
+        events: Receiver<StripEvent>,
+        generation: Arc<AtomicU64>,
+        transport: Option<tutti::TransportHandle>,
+    ) -> Self {
+        let seen = (left.value(), right.value());
+        let mut node = Self {
+            left,
+            right,
+            ramp_secs,
+            ramp_curve,
+            seen,
+            target: seen,
+            gain: seen,
+            ramp: None,
+            coeff: 0.0,
+            sample_rate: tutti::dsp::DEFAULT_SR,
+            events,
+            pending: Vec::with_capacity(STRIP_EVENT_CAPACITY),
+            generation,
+            transport,
+            beat: 0.0,
+            published_beat: f64::NAN,
+        };
+        node.update_coeff();
+        node
+    }
+
+    fn update_coeff(&mut self) {
+        let samples = STRIP_SMOOTHING_SECS as f64 * self.sample_rate;
+        self.coeff = (1.0 - (-1.0 / samples.max(1.0)).exp()) as f32;
+    }
+
+    /// Head for `target`: along a [`StripRamp`] if `smoothed` and the
+    /// last write asked for one, otherwise with the one-pole smoother.
+    fn retarget(&mut self, target: (f32, f32), smoothed: bool) {
+        self.target = target;
+        let secs = if smoothed {
+            self.ramp_secs.value()
+        } else {
+            0.0
+        };
+        self.ramp = (secs > 0.0).then(|| StripRamp {
+            from: self.gain,
+            elapsed: 0.0,
+            samples: (secs as f64 * self.sample_rate).max(1.0),
+            smoothing: ParamSmoothing {
+                time_secs: secs,
+                curve: curve_from_index(self.ramp_curve.value()),
+            },
+        });
+    }
+
+    /// Advance the beat clock by one sample and apply any due events.
+    fn advance_transport(&mut self) {
+        let Some(transport) = &self.transport else {
+            return;
+        };
+        if !transport.is_playing() {
+            return;
+        }
+        let published = transport.current_beat();
+        if published != self.published_beat {
+            self.published_beat = published;
+            self.beat = published;
+        } else {
+            self.beat += transport.get_tempo().get() / 60.0 / self.sample_rate;
+        }
+
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.pending.retain(|e| e.generation == generation);
+        while self.pending.len() < STRIP_EVENT_CAPACITY {
+            match self.events.try_recv() {
+                Ok(event) if event.generation == generation => self.pending.push(event),
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        let beat = self.beat;
+        let mut due: Option<StripEvent> = None;
+        for event in self.pending.iter().filter(|e| e.at_beat <= beat) {
+            if due.is_none_or(|d| event.at_beat >= d.at_beat) {
+                due = Some(*event);
+            }
+        }
+        if let Some(event) = due {
+            self.pending.retain(|e| e.at_beat > beat);
+            self.retarget((event.left, event.right), false);
+        }
+    }
+}
+
+impl DspNode for StripNode {
+    const ID: u64 = 0x7475_7474_6973_7472;
+    type Inputs = U2;
+    type Outputs = U2;
+
+    fn reset(&mut self) {
+        self.gain = self.target;
+        self.ramp = None;
+        self.published_beat = f64::NAN;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+        self.update_coeff();
+    }
+
+    #[inline]
+    fn tick(&mut self, input: &Frame<f32, Self::Inputs>) -> Frame<f32, Self::Outputs> {
+        let shared = (self.left.value(), self.right.value());
+        if shared != self.seen {
+            self.seen = shared;
+            self.retarget(shared, true);
+        }
+        self.advance_transport();
+        match &mut self.ramp {
+            Some(ramp) => {
+                ramp.elapsed += 1.0;
+                let t = (ramp.elapsed / ramp.samples) as f32;
+                self.gain.0 = ramp.smoothing.interpolate(ramp.from.0, self.target.0, t);
+                self.gain.1 = ramp.smoothing.interpolate(ramp.from.1, self.target.1, t);
+                if t >= 1.0 {
+                    self.ramp = None;
+                }
+            }
+            None => {
+                self.gain.0 += (self.target.0 - self.gain.0) * self.coeff;
+                self.gain.1 += (self.target.1 - self.gain.1) * self.coeff;
+            }
+        }
+        [input[0] * self.gain.0, input[1] * self.gain.1].into()
+    }
+}
+
+/// Left/right gains for `pan` in `[-1, 1]`.
+///
+/// Mono sources use a constant-power law normalised to unity at centre
+/// (so adding a centred `Pan` doesn't change loudness). Stereo sources
+/// use a balance law: centre passes both channels untouched and panning
+/// attenuates the opposite side linearly.
+pub fn pan_gains(pan: f32, mono_source: bool) -> (f32, f32) {
+    let pan = pan.clamp(-1.0, 1.0);
+    if mono_source {
+        let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+        (
+            angle.cos() * std::f32::consts::SQRT_2,
+            angle.sin() * std::f32::consts::SQRT_2,
+        )
+    } else {
+        ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
+    }
+}
+
+/// Whether `kind` applies `Volume` / `Mute` through a typed setter of its
+/// own, so its strip (if any) must stay at unity gain.
+pub(crate) fn has_typed_gain(kind: &NodeKind) -> bool {
+    #[cfg(feature = "sampler")]
+    if matches!(*kind, NodeKind::Sampler) {
+        return true;
+    }
+    let _ = kind;
+    false
+}
+
+/// Linear gain the strip applies for `kind` given its volume and mute.
+pub(crate) fn strip_gain(kind: &NodeKind, volume: f32, muted: bool) -> f32 {
+    if has_typed_gain(kind) {
+        1.0
+    } else if muted {
+        0.0
+    } else {
+        volume
+    }
+}
+
+/// The node carrying `entity`'s audible output: its [`ChannelStrip`] if
+/// one has been spliced in, otherwise its `AudioNode`.
+pub fn output_node(node: &AudioNode, strip: Option<&ChannelStrip>) -> tutti::NodeId {
+    strip.map_or(node.0, |s| s.node)
+}
+
+/// Pipe an entity's output node to the master bus.
+///
+/// Queues a deferred world command, like
+/// [`crossfade_audio_node`](super::crossfade_audio_node). Pipes the
+/// [`ChannelStrip`] when the entity has one so gain and pan stay audible;
+/// call it after the strip exists (insert [`WithGainStage`] at spawn to
+/// have it the next frame) or use [`super::AudioFeedsTo`], which
+/// re-wires automatically.
+pub fn pipe_audio_node_output(commands: &mut Commands<'_, '_>, entity: Entity) {
+    commands.queue(move |world: &mut World| {
+        let Some(node) = world.get::<AudioNode>(entity).copied() else {
+            bevy_log::warn!(
+                "pipe_audio_node_output: entity {:?} has no AudioNode; nothing to pipe",
+                entity
+            );
+            return;
+        };
+        let id = output_node(&node, world.get::<ChannelStrip>(entity));
+        let Some(mut graph) = world.get_resource_mut::<TuttiGraphRes>() else {
+            return;
+        };
+        graph.0.pipe_output(id);
+        if let Some(mut dirty) = world.get_resource_mut::<GraphDirty>() {
+            dirty.0 = true;
+        }
+    });
+}
+
+/// Hands the master-bus channels `unit` feeds over to `strip`.
+///
+/// A unit piped to master before it had a strip (`PlaySoundFont`, or a
+/// host calling `pipe_output` on the `AudioNode`) would otherwise keep
+/// bypassing it, leaving `Volume` / `Pan` / `Mute` inaudible. The strip
+/// is stereo, so only master channels 0 and 1 can move.
+fn move_master_output(
+    graph: &mut TuttiGraphRes,
+    unit: tutti::NodeId,
+    strip: tutti::NodeId,
+) -> bool {
+    let mut moved = false;
+    for channel in 0..2 {
+        if matches!(graph.0.output_source(channel), Source::Local(id, _) if id == unit) {
+            graph.0.connect_output(strip, channel, channel);
+            moved = true;
+        }
+    }
+    moved
+}
+
+/// Builds a [`ChannelStrip`] for every entity that needs one and splices
+/// it after the unit.
+///
+/// An entity needs a strip once it has an `AudioNode` plus `Pan`,
+/// [`WithGainStage`], or — for kinds without a typed gain setter —
+/// `Volume` / `Mute`.
+///
+/// Runs in [`super::GraphReconcileSystems::Spawn`]. Units with one
+/// output feed both strip inputs; units with two or more feed their
+/// first two outputs straight across. Units with no outputs (MIDI-only
+/// plugins, sinks) are skipped with a warning.
+///
+/// If the unit was already piped to master, that connection moves to the
+/// strip. Component links (`AudioFeedsTo`, `SidechainOf`, post-fader
+/// `SendTo`, `MeterTap`) re-wire themselves from the strip; pre-fader
+/// sends keep tapping the unit on purpose.
+#[allow(
+    clippy::type_complexity,
+    reason = "Bevy queries are tuple-shaped by design"
+)]
+pub fn ensure_channel_strips(
+    mut commands: Commands,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    transport: Option<Res<TransportRes>>,
+    mut dirty: ResMut<GraphDirty>,
+    query: Query<
+        (
+            Entity,
+            &AudioNode,
+            &NodeKind,
+            Option<&Volume>,
+            Option<&Mute>,
+            Option<&Pan>,
+            Has<WithGainStage>,
+        ),
+        (
+            Without<ChannelStrip>,
+            Or<(
+                Added<AudioNode>,
+                Added<Volume>,
+                Added<Mute>,
+                Added<Pan>,
+                Added<WithGainStage>,
+            )>,
+        ),
+    >,
+) {
+    let Some(mut graph) = graph else { return };
+
+    for (entity, node, kind, volume, mute, pan, opted_in) in query.iter() {
+        let wants_gain = !has_typed_gain(kind) && (volume.is_some() || mute.is_some());
+        if !(opted_in || pan.is_some() || wants_gain) {
+            continue;
+        }
+        let outputs = graph.0.outputs(node.0);
+        if outputs == 0 {
+            bevy_log::warn!(
+                "ChannelStrip: {:?} has no audio outputs; Volume/Pan ignored",
+                entity
+            );
+            continue;
+        }
+        let mono_source = outputs == 1;
+
+        let left = Shared::new(1.0);
+        let right = Shared::new(1.0);
+        let ramp_secs = Shared::new(0.0);
+        let ramp_curve = Shared::new(0.0);
+        let (events, receiver) = crossbeam_channel::bounded(STRIP_EVENT_CAPACITY);
+        let generation = Arc::new(AtomicU64::new(0));
+        let unit = An(StripNode::new(
+            left.clone(),
+            right.clone(),
+            ramp_secs.clone(),
+            ramp_curve.clone(),
+            receiver,
+            generation.clone(),
+            transport.as_ref().map(|t| t.0.clone()),
+        ));
+        let strip_id = graph.0.add(unit);
+
+        graph.0.connect(node.0, 0, strip_id, 0);
+        graph
+            .0
+            .connect(node.0, if mono_source { 0 } else { 1 }, strip_id, 1);
+        move_master_output(&mut graph, node.0, strip_id);
+        dirty.0 = true;
+
+        let strip = ChannelStrip {
+            node: strip_id,
+            mono_source,
+            left,
+            right,
+            ramp_secs,
+            ramp_curve,
+            events,
+            generation,
+        };
+        strip.set(
+            strip_gain(kind, volume.map_or(1.0, |v| v.0), mute.is_some_and(|m| m.0)),
+            pan.map_or(0.0, |p| p.0),
+        );
+        commands.entity(entity).insert(strip);
+    }
+}
+
+type ChangedStripLevels<'w> = (
+    Entity,
+    &'w ChannelStrip,
+    &'w NodeKind,
+    Option<&'w Volume>,
+    Option<Ref<'w, Mute>>,
+    Option<&'w Pan>,
+    Option<&'w ParamSmoothing>,
+);
+type ChangedStripFilter = Or<(
+    Changed<Volume>,
+    Changed<Mute>,
+    Changed<Pan>,
+    Added<ChannelStrip>,
+)>;
+
+/// Reconciles `Changed<Volume>` / `Changed<Mute>` / `Changed<Pan>` into
+/// the entity's [`ChannelStrip`].
+///
+/// A freshly built strip also counts as a change, so values set on the
+/// spawn frame land. `Volume` and `Pan` follow [`ParamSmoothing`], ramped
+/// per sample on the audio thread by [`ChannelStrip::set_smoothed`];
+/// `Mute` ramps to zero over the strip's own 5 ms smoothing instead of
+/// cutting. No graph mutation — the gains are atomics — so `GraphDirty`
+/// stays untouched.
+pub fn reconcile_strip_levels(
+    mut smoother: ParamSmoother,
+    changed: Query<ChangedStripLevels, ChangedStripFilter>,
+) {
+    for (entity, strip, kind, volume, mute, pan, smoothing) in changed.iter() {
+        let volume = volume.map_or(1.0, |v| v.0);
+        let pan = pan.map_or(0.0, |p| p.0);
+        // Typed-gain kinds ramp `Volume` in the unit; the strip stays at
+        // unity and leaves the `Volume` ramp state to `reconcile_params`.
+        let volume_ramp = if has_typed_gain(kind) {
+            ParamSmoothing::OFF
+        } else {
+            smoother.policy(entity, SmoothedParam::Volume, volume, smoothing)
+        };
+        let pan_ramp = smoother.policy(entity, SmoothedParam::Pan, pan, smoothing);
+        let ramp = if mute.as_ref().is_some_and(|m| m.is_changed()) {
+            ParamSmoothing::OFF
+        } else if volume_ramp.is_off() {
+            pan_ramp
+        } else {
+            volume_ramp
+        };
+        let muted = mute.is_some_and(|m| m.0);
+        strip.set_smoothed(strip_gain(kind, volume, muted), pan, ramp);
+    }
+}
+
+/// Removes strip nodes whose entity lost its `AudioNode` or
+/// [`ChannelStrip`] (including despawned entities).
+///
+/// Mirrors [`super::reconcile_node_despawn`]: the removed component
+/// can't be read back, so `(Entity, NodeId)` pairs are tracked locally.
+pub fn reconcile_strip_despawn(
+    mut tracked: Local<std::collections::HashMap<Entity, tutti::NodeId>>,
+    added: Query<(Entity, &ChannelStrip), Added<ChannelStrip>>,
+    mut removed_nodes: RemovedComponents<AudioNode>,
+    mut removed_strips: RemovedComponents<ChannelStrip>,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    mut dirty: ResMut<GraphDirty>,
+) {
+    for (entity, strip) in added.iter() {
+        tracked.insert(entity, strip.node);
+    }
+
+    let gone: Vec<Entity> = removed_nodes.read().chain(removed_strips.read()).collect();
+    let Some(mut graph) = graph else {
+        for entity in gone {
+            tracked.remove(&entity);
+        }
+        return;
+    };
+
+    for entity in gone {
+        if let Some(id) = tracked.remove(&entity) {
+            if graph.0.contains(id) {
+                graph.0.remove(id);
+                dirty.0 = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::reconcile::{
+        commit_graph, reconcile_node_despawn, GraphReconcileSystems, SpawnAudioNode,
+    };
+    use bevy_app::App;
+    use tutti::core::ecs::NodeKind;
+    use tutti::dsp::sine_hz;
+    use tutti::TuttiEngine;
+
+    fn test_app() -> App {
+        let engine = TuttiEngine::builder()
+            .inputs(0)
+            .outputs(2)
+            .build()
+            .expect("build engine");
+        let TuttiEngine { graph, .. } = engine;
+
+        let mut app = App::new();
+        app.insert_resource(crate::resources::TuttiGraphRes(graph));
+        app.init_resource::<GraphDirty>();
+        app.configure_sets(
+            bevy_app::Update,
+            (
+                GraphReconcileSystems::Spawn,
+                GraphReconcileSystems::Params,
+                GraphReconcileSystems::Despawn,
+                GraphReconcileSystems::Commit,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            bevy_app::Update,
+            (
+                ensure_channel_strips.in_set(GraphReconcileSystems::Spawn),
+                reconcile_strip_levels.in_set(GraphReconcileSystems::Params),
+                reconcile_node_despawn.in_set(GraphReconcileSystems::Despawn),
+                reconcile_strip_despawn.in_set(GraphReconcileSystems::Despawn),
+                commit_graph.in_set(GraphReconcileSystems::Commit),
+            ),
+        );
+        app
+    }
+
+    #[test]
+    fn pan_law_is_unity_at_centre() {
+        assert_eq!(pan_gains(0.0, false), (1.0, 1.0));
+        let (l, r) = pan_gains(0.0, true);
+        assert!((l - 1.0).abs() < 1e-6 && (r - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pan_law_hard_left_silences_right() {
+        let (l, r) = pan_gains(-1.0, true);
+        assert!(l > 1.0 && r.abs() < 1e-6);
+        assert_eq!(pan_gains(-1.0, false), (1.0, 0.0));
+        // Out-of-range values clamp.
+        assert_eq!(pan_gains(3.0, false), (0.0, 1.0));
+    }
+
+    #[test]
+    fn smoothed_set_ramps_per_sample() {
+        let (left, right) = (Shared::new(1.0), Shared::new(1.0));
+        let (ramp_secs, ramp_curve) = (Shared::new(0.0), Shared::new(0.0));
+        let (events, receiver) = crossbeam_channel::bounded(STRIP_EVENT_CAPACITY);
+        let generation = Arc::new(AtomicU64::new(0));
+        let strip = ChannelStrip {
+            node: tutti::NodeId::new(),
+            mono_source: false,
+            left: left.clone(),
+            right: right.clone(),
+            ramp_secs: ramp_secs.clone(),
+            ramp_curve: ramp_curve.clone(),
+            events,
+            generation: generation.clone(),
+        };
+        let mut node = StripNode::new(
+            left, right, ramp_secs, ramp_curve, receiver, generation, None,
+        );
+        node.set_sample_rate(1_000.0);
+
+        // 10 ms at 1 kHz: ten samples from 1.0 down to 0.0.
+        strip.set_smoothed(0.0, 0.0, ParamSmoothing::linear(0.01));
+        let input: Frame<f32, U2> = [1.0, 1.0].into();
+        let out: Vec<f32> = (0..10).map(|_| node.tick(&input)[0]).collect();
+        assert!((out[4] - 0.5).abs() < 1e-6);
+        assert_eq!(out[9], 0.0);
+        assert!(out.windows(2).all(|w| w[1] < w[0]));
+    }
+
+    #[test]
+    fn pan_splices_strip_and_despawn_removes_it() {
+        let mut app = test_app();
+        let entity = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
+            .insert(Pan(-0.5))
+            .id();
+        app.update();
+        app.update();
+
+        let strip = app
+            .world()
+            .get::<ChannelStrip>(entity)
+            .expect("ChannelStrip")
+            .clone();
+        assert!(strip.mono_source);
+        let (l, r) = pan_gains(-0.5, true);
+        assert!((strip.left.value() - l).abs() < 1e-6);
+        assert!((strip.right.value() - r).abs() < 1e-6);
+        assert!(app
+            .world()
+            .resource::<crate::resources::TuttiGraphRes>()
+            .0
+            .contains(strip.node));
+
+        app.world_mut().get_mut::<Pan>(entity).unwrap().0 = 1.0;
+        app.update();
+        let strip = app.world().get::<ChannelStrip>(entity).unwrap();
+        assert!(strip.left.value().abs() < 1e-6);
+
+        let strip_node = strip.node;
+        app.world_mut().despawn(entity);
+        app.update();
+        assert!(!app
+            .world()
+            .resource::<crate::resources::TuttiGraphRes>()
+            .0
+            .contains(strip_node));
+    }
+
+    #[test]
+    fn generator_volume_and_mute_drive_strip_gain() {
+        let mut app = test_app();
+        let entity = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
+            .insert(Volume(0.5))
+            .id();
+        app.update();
+        app.update();
+
+        let strip = app
+            .world()
+            .get::<ChannelStrip>(entity)
+            .expect("ChannelStrip");
+        assert!((strip.left.value() - 0.5).abs() < 1e-6);
+        assert!((strip.right.value() - 0.5).abs() < 1e-6);
+
+        app.world_mut().entity_mut(entity).insert(Mute(true));
+        app.update();
+        let strip = app.world().get::<ChannelStrip>(entity).unwrap();
+        assert_eq!(strip.left.value(), 0.0);
+        assert_eq!(strip.right.value(), 0.0);
+    }
+
+    #[test]
+    fn with_gain_stage_builds_strip_without_params() {
+        let mut app = test_app();
+        let entity = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
+            .insert(WithGainStage)
+            .id();
+        app.update();
+        app.update();
+
+        let strip = app
+            .world()
+            .get::<ChannelStrip>(entity)
+            .expect("ChannelStrip");
+        assert!((strip.left.value() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn strip_takes_over_existing_master_pipe() {
+        let mut app = test_app();
+        let entity = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(
+                sine_hz::<f32>(440.0) | sine_hz::<f32>(440.0),
+                NodeKind::Generator,
+            )
+            .id();
+        app.update();
+        let node = app.world().get::<AudioNode>(entity).expect("AudioNode").0;
+        {
+            let mut graph = app
+                .world_mut()
+                .resource_mut::<crate::resources::TuttiGraphRes>();
+            graph.0.pipe_output(node);
+            graph.0.commit();
+        }
+
+        app.world_mut().entity_mut(entity).insert(Volume(0.5));
+        app.update();
+
+        let strip = app
+            .world()
+            .get::<ChannelStrip>(entity)
+            .expect("ChannelStrip")
+            .node;
+        let graph = app.world().resource::<crate::resources::TuttiGraphRes>();
+        for channel in 0..2 {
+            let source = graph.0.output_source(channel);
+            assert!(
+                matches!(source, Source::Local(id, port) if id == strip && port == channel),
+                "master channel {channel} still fed by {source:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn bare_node_gets_no_strip() {
+        let mut app = test_app();
+        let entity = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
+            .id();
+        app.update();
+        app.update();
+        assert!(app.world().get::<ChannelStrip>(entity).is_none());
+    }
+}
@@ -49,10 +49,15 @@
 //! }
 //! ```
 
+mod beat_trigger;
 mod loader;
 mod metering;
+mod metronome;
+mod offline;
+mod tempo_map;
 mod transport;
 mod device_state;
+mod engine_status;
 mod plugin;
 mod prelude;
 mod resources;
@@ -65,6 +70,8 @@ pub mod dsp;
 mod analysis;
 #[cfg(feature = "automation")]
 pub mod automation;
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
 #[cfg(feature = "export")]
 mod export;
 #[cfg(feature = "midi")]
@@ -91,5 +98,5 @@ mod live_resize;
 #[cfg(all(feature = "plugin", feature = "vst2"))]
 pub mod vst2_load;
 
-pub use plugin::TuttiPlugin;
+pub use plugin::{HeadlessConfig, TuttiPlugin};
 pub use prelude::*;
@@ -24,3 +24,118 @@ pub fn metering_sync_system(
     levels.rms_left = l_rms;
     levels.rms_right = r_rms;
 }
+
+/// Master loudness per ITU-R BS.1770 / EBU R128, mirrored from the
+/// engine's loudness meter every frame.
+///
+/// LUFS values are `-inf` until enough audio has been measured
+/// (400 ms for momentary, 3 s for short-term and loudness range). The
+/// integrated value and loudness range accumulate from engine start or
+/// the last [`ResetLoudness`]; true-peak is the highest since then.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource, Default, Clone)]
+pub struct MasterLoudness {
+    /// 400 ms window.
+    pub momentary_lufs: f32,
+    /// 3 s window.
+    pub short_term_lufs: f32,
+    /// Gated programme loudness.
+    pub integrated_lufs: f32,
+    /// Loudness range (LRA), in LU.
+    pub loudness_range_lu: f32,
+    pub true_peak_left_dbtp: f32,
+    pub true_peak_right_dbtp: f32,
+}
+
+impl Default for MasterLoudness {
+    fn default() -> Self {
+        Self {
+            momentary_lufs: f32::NEG_INFINITY,
+            short_term_lufs: f32::NEG_INFINITY,
+            integrated_lufs: f32::NEG_INFINITY,
+            loudness_range_lu: 0.0,
+            true_peak_left_dbtp: f32::NEG_INFINITY,
+            true_peak_right_dbtp: f32::NEG_INFINITY,
+        }
+    }
+}
+
+impl MasterLoudness {
+    /// Higher of the two channels' true-peak.
+    pub fn true_peak_dbtp(&self) -> f32 {
+        self.true_peak_left_dbtp.max(self.true_peak_right_dbtp)
+    }
+
+    /// Whether the integrated loudness and true-peak meet `target`.
+    pub fn meets(&self, target: &LoudnessTarget) -> bool {
+        (self.integrated_lufs - target.integrated_lufs).abs() <= target.tolerance_lu
+            && self.true_peak_dbtp() <= target.max_true_peak_dbtp
+    }
+}
+
+/// A delivery spec for [`MasterLoudness::meets`].
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct LoudnessTarget {
+    pub integrated_lufs: f32,
+    pub tolerance_lu: f32,
+    pub max_true_peak_dbtp: f32,
+}
+
+impl LoudnessTarget {
+    /// EBU R128: -23 LUFS ±0.5 LU, true-peak at most -1 dBTP.
+    pub const EBU_R128: Self = Self {
+        integrated_lufs: -23.0,
+        tolerance_lu: 0.5,
+        max_true_peak_dbtp: -1.0,
+    };
+    /// ATSC A/85: -24 LKFS ±2 dB, true-peak at most -2 dBTP.
+    pub const ATSC_A85: Self = Self {
+        integrated_lufs: -24.0,
+        tolerance_lu: 2.0,
+        max_true_peak_dbtp: -2.0,
+    };
+}
+
+/// Trigger component: restart integrated loudness, loudness range and
+/// true-peak measurement (e.g. at the start of a programme).
+///
+/// Processed by `reset_loudness_system`, which removes it.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Default)]
+pub struct ResetLoudness;
+
+pub fn loudness_sync_system(
+    metering: Option<Res<MeteringRes>>,
+    mut loudness: ResMut<MasterLoudness>,
+) {
+    let Some(metering) = metering else { return };
+    loudness.momentary_lufs = metering.loudness_momentary() as f32;
+    loudness.short_term_lufs = metering.loudness_shortterm() as f32;
+    loudness.integrated_lufs = metering.loudness_global() as f32;
+    loudness.loudness_range_lu = metering.loudness_range() as f32;
+    loudness.true_peak_left_dbtp = amplitude_to_db(metering.true_peak(0));
+    loudness.true_peak_right_dbtp = amplitude_to_db(metering.true_peak(1));
+}
+
+/// Processes [`ResetLoudness`] triggers.
+pub fn reset_loudness_system(
+    mut commands: Commands,
+    metering: Option<Res<MeteringRes>>,
+    mut loudness: ResMut<MasterLoudness>,
+    query: Query<Entity, Added<ResetLoudness>>,
+) {
+    let Some(metering) = metering else { return };
+    for entity in query.iter() {
+        metering.reset_loudness();
+        *loudness = MasterLoudness::default();
+        commands.entity(entity).remove::<ResetLoudness>();
+    }
+}
+
+fn amplitude_to_db(amplitude: f64) -> f32 {
+    if amplitude > 0.0 {
+        (20.0 * amplitude.log10()) as f32
+    } else {
+        f32::NEG_INFINITY
+    }
+}
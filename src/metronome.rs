@@ -0,0 +1,618 @@
+//! Transport-following metronome with count-in.
+//!
+//! Insert a [`Metronome`] resource and [`metronome_system`] adds a click
+//! node to the graph, piped to the master bus. The node follows the
+//! transport on the audio thread, so clicks land on the exact sample of
+//! each meter beat, accented on the downbeat. Time signature comes from
+//! the [`TempoMap`] when one is inserted, `TransportState` otherwise.
+//! Remove the resource to take the node out again.
+//!
+//! Clicks are a short built-in sine blip unless [`Metronome::accent_sample`]
+//! / [`Metronome::click_sample`] point at loaded waves, which are played
+//! at the engine rate without resampling.
+//!
+//! With `count_in_bars > 0`, a `StartRecording` issued while the
+//! transport is stopped first clicks that many bars on the node's own
+//! clock, then starts the transport on the sample the count-in ends and
+//! records from where the playhead was. [`MetronomeClick::count_in`]
+//! does the same for hosts driving recording themselves.
+
+use std::sync::Arc;
+
+use bevy_asset::{AssetId, Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use crossbeam_channel::{Receiver, Sender};
+use tutti::core::WaveAsset;
+use tutti::dsp::{An, AudioNode as DspNode, Frame, U0, U2};
+use tutti::{NodeId, Wave};
+
+use crate::graph::GraphDirty;
+use crate::resources::{TransportRes, TuttiGraphRes};
+use crate::tempo_map::TempoMap;
+use crate::transport::TransportState;
+
+const CLICK_COMMAND_CAPACITY: usize = 16;
+/// Built-in click: a decaying sine blip.
+const CLICK_SECS: f64 = 0.04;
+const CLICK_DECAY_SECS: f64 = 0.008;
+const ACCENT_HZ: f64 = 1_760.0;
+const CLICK_HZ: f64 = 1_320.0;
+/// A published beat further than this from the node's own estimate is a
+/// seek or loop wrap rather than block-to-block drift.
+const JUMP_TOLERANCE_BEATS: f64 = 0.01;
+
+/// Metronome settings. Present = metronome on.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource, Default, Clone)]
+pub struct Metronome {
+    /// Gain of the first beat of each bar.
+    pub accent_gain: f32,
+    /// Gain of the other beats.
+    pub click_gain: f32,
+    /// Bars to count in before `StartRecording` from a stopped transport.
+    /// `0` starts recording straight away.
+    pub count_in_bars: u32,
+    /// Only click while recording (count-ins always click).
+    pub only_while_recording: bool,
+    /// Sample for the downbeat; the built-in click when `None`.
+    pub accent_sample: Option<Handle<WaveAsset>>,
+    /// Sample for the other beats; the built-in click when `None`.
+    pub click_sample: Option<Handle<WaveAsset>>,
+}
+
+impl Default for Metronome {
+    fn default() -> Self {
+        Self {
+            accent_gain: 0.8,
+            click_gain: 0.5,
+            count_in_bars: 1,
+            only_while_recording: false,
+            accent_sample: None,
+            click_sample: None,
+        }
+    }
+}
+
+impl Metronome {
+    pub fn gains(mut self, accent: f32, click: f32) -> Self {
+        self.accent_gain = accent;
+        self.click_gain = click;
+        self
+    }
+
+    pub fn count_in(mut self, bars: u32) -> Self {
+        self.count_in_bars = bars;
+        self
+    }
+
+    pub fn only_while_recording(mut self) -> Self {
+        self.only_while_recording = true;
+        self
+    }
+
+    pub fn samples(mut self, accent: Handle<WaveAsset>, click: Handle<WaveAsset>) -> Self {
+        self.accent_sample = Some(accent);
+        self.click_sample = Some(click);
+        self
+    }
+}
+
+enum ClickCommand {
+    Enabled(bool),
+    Gains {
+        accent: f32,
+        click: f32,
+    },
+    Meter {
+        origin_beat: f64,
+        numerator: u8,
+        denominator: u8,
+    },
+    Samples {
+        accent: Option<Arc<Wave>>,
+        click: Option<Arc<Wave>>,
+    },
+    CountIn {
+        bars: u32,
+        tempo: f64,
+    },
+    CancelCountIn,
+}
+
+/// What [`metronome_system`] last sent, so it only sends changes.
+#[derive(Default)]
+struct SentClickState {
+    enabled: Option<bool>,
+    gains: Option<(f32, f32)>,
+    meter: Option<(f64, u8, u8)>,
+    samples: Option<(Option<AssetId<WaveAsset>>, Option<AssetId<WaveAsset>>)>,
+}
+
+/// The metronome's click node, inserted by [`metronome_system`] while a
+/// [`Metronome`] exists.
+///
+/// Not `Reflect`: holds the channel to the audio thread.
+#[derive(Resource)]
+pub struct MetronomeClick {
+    node: NodeId,
+    commands: Sender<ClickCommand>,
+    follows_transport: bool,
+    sent: SentClickState,
+}
+
+impl MetronomeClick {
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+
+    /// Click `bars` bars at `tempo` on the node's own clock, then start
+    /// the transport on the sample the last bar ends. `false` when the
+    /// node has no transport to start or its queue is full.
+    pub fn count_in(&self, bars: u32, tempo: f64) -> bool {
+        self.follows_transport
+            && tempo > 0.0
+            && self
+                .commands
+                .try_send(ClickCommand::CountIn { bars, tempo })
+                .is_ok()
+    }
+
+    /// Abandon a running count-in without starting the transport.
+    pub fn cancel_count_in(&self) {
+        let _ = self.commands.try_send(ClickCommand::CancelCountIn);
+    }
+
+    fn send(&self, command: ClickCommand) -> bool {
+        self.commands.try_send(command).is_ok()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ClickVoice {
+    accent: bool,
+    pos: usize,
+}
+
+#[derive(Clone, Copy)]
+struct CountIn {
+    elapsed: f64,
+    total: f64,
+    tempo: f64,
+    last_line: Option<i64>,
+}
+
+/// Audio-thread side of the metronome.
+///
+/// Tracks the transport like the channel strip does: the beat is
+/// published once per block, so the node interpolates in between from
+/// the tempo and resyncs when the published value moves. Landing on a
+/// grid line after a seek or loop wrap clicks it.
+#[derive(Clone)]
+struct ClickNode {
+    commands: Receiver<ClickCommand>,
+    transport: Option<tutti::TransportHandle>,
+    sample_rate: f64,
+    enabled: bool,
+    accent_gain: f32,
+    click_gain: f32,
+    origin_beat: f64,
+    numerator: u8,
+    denominator: u8,
+    accent_sample: Option<Arc<Wave>>,
+    click_sample: Option<Arc<Wave>>,
+    beat: f64,
+    published_beat: f64,
+    last_line: Option<i64>,
+    count_in: Option<CountIn>,
+    voice: Option<ClickVoice>,
+}
+
+impl ClickNode {
+    fn new(commands: Receiver<ClickCommand>, transport: Option<tutti::TransportHandle>) -> Self {
+        Self {
+            commands,
+            transport,
+            sample_rate: tutti::dsp::DEFAULT_SR,
+            enabled: true,
+            accent_gain: 0.8,
+            click_gain: 0.5,
+            origin_beat: 0.0,
+            numerator: 4,
+            denominator: 4,
+            accent_sample: None,
+            click_sample: None,
+            beat: 0.0,
+            published_beat: f64::NAN,
+            last_line: None,
+            count_in: None,
+            voice: None,
+        }
+    }
+
+    /// Quarter-note beats per meter beat.
+    fn unit(&self) -> f64 {
+        4.0 / self.denominator.max(1) as f64
+    }
+
+    fn drain_commands(&mut self) {
+        while let Ok(command) = self.commands.try_recv() {
+            match command {
+                ClickCommand::Enabled(enabled) => self.enabled = enabled,
+                ClickCommand::Gains { accent, click } => {
+                    self.accent_gain = accent;
+                    self.click_gain = click;
+                }
+                ClickCommand::Meter {
+                    origin_beat,
+                    numerator,
+                    denominator,
+                } => {
+                    self.origin_beat = origin_beat;
+                    self.numerator = numerator.max(1);
+                    self.denominator = denominator.max(1);
+                }
+                ClickCommand::Samples { accent, click } => {
+                    self.accent_sample = accent;
+                    self.click_sample = click;
+                }
+                ClickCommand::CountIn { bars, tempo } => {
+                    let playing = self.transport.as_ref().is_some_and(|t| t.is_playing());
+                    if !playing && bars > 0 {
+                        self.count_in = Some(CountIn {
+                            elapsed: 0.0,
+                            total: bars as f64 * self.numerator as f64 * self.unit(),
+                            tempo,
+                            last_line: None,
+                        });
+                    }
+                }
+                ClickCommand::CancelCountIn => self.count_in = None,
+            }
+        }
+    }
+
+    fn start_click(&mut self, line: i64) {
+        self.voice = Some(ClickVoice {
+            accent: line.rem_euclid(self.numerator as i64) == 0,
+            pos: 0,
+        });
+    }
+
+    /// Advance the count-in by one sample. `true` while it is running.
+    fn advance_count_in(&mut self) -> bool {
+        let Some(mut count) = self.count_in else {
+            return false;
+        };
+        if self.transport.as_ref().is_some_and(|t| t.is_playing()) {
+            // Started by someone else: the count-in is moot.
+            self.count_in = None;
+            return false;
+        }
+        if count.elapsed >= count.total {
+            self.count_in = None;
+            self.published_beat = f64::NAN;
+            if let Some(transport) = &self.transport {
+                transport.play();
+            }
+            return false;
+        }
+        let line = (count.elapsed / self.unit() + 1e-9).floor() as i64;
+        if count.last_line.is_none_or(|last| line > last) {
+            count.last_line = Some(line);
+            self.start_click(line);
+        }
+        count.elapsed += count.tempo / 60.0 / self.sample_rate;
+        self.count_in = Some(count);
+        true
+    }
+
+    /// Advance the transport beat by one sample and click grid lines.
+    fn advance_transport(&mut self) {
+        let Some(transport) = &self.transport else {
+            return;
+        };
+        if !transport.is_playing() {
+            self.published_beat = f64::NAN;
+            self.last_line = None;
+            return;
+        }
+        let per_sample = transport.get_tempo().get() / 60.0 / self.sample_rate;
+        let published = transport.current_beat();
+        if published != self.published_beat {
+            let jumped = self.published_beat.is_nan()
+                || (published - self.beat).abs() > JUMP_TOLERANCE_BEATS;
+            self.published_beat = published;
+            self.beat = published;
+            if jumped {
+                // Click a grid line we've landed on (within a couple of
+                // samples), but not one we've jumped past.
+                let line = self.line_at(published);
+                let on_line = published - self.line_beat(line) < 2.0 * per_sample;
+                self.last_line = Some(if on_line { line - 1 } else { line });
+            }
+        } else {
+            self.beat += per_sample;
+        }
+
+        let line = self.line_at(self.beat);
+        if self.last_line.is_some_and(|last| line > last) {
+            self.last_line = Some(line);
+            if self.enabled {
+                self.start_click(line);
+            }
+        }
+    }
+
+    fn line_at(&self, beat: f64) -> i64 {
+        ((beat - self.origin_beat) / self.unit() + 1e-9).floor() as i64
+    }
+
+    fn line_beat(&self, line: i64) -> f64 {
+        self.origin_beat + line as f64 * self.unit()
+    }
+
+    fn render(&mut self) -> (f32, f32) {
+        let Some(mut voice) = self.voice else {
+            return (0.0, 0.0);
+        };
+        let (gain, sample) = if voice.accent {
+            (self.accent_gain, &self.accent_sample)
+        } else {
+            (self.click_gain, &self.click_sample)
+        };
+        let out = match sample {
+            Some(wave) if voice.pos < wave.len() => {
+                let right = (wave.channels() - 1).min(1);
+                Some((wave.at(0, voice.pos), wave.at(right, voice.pos)))
+            }
+            Some(_) => None,
+            None => {
+                let t = voice.pos as f64 / self.sample_rate;
+                (t < CLICK_SECS).then(|| {
+                    let hz = if voice.accent { ACCENT_HZ } else { CLICK_HZ };
+                    let v = ((std::f64::consts::TAU * hz * t).sin() * (-t / CLICK_DECAY_SECS).exp())
+                        as f32;
+                    (v, v)
+                })
+            }
+        };
+        match out {
+            Some((left, right)) => {
+                voice.pos += 1;
+                self.voice = Some(voice);
+                (left * gain, right * gain)
+            }
+            None => {
+                self.voice = None;
+                (0.0, 0.0)
+            }
+        }
+    }
+}
+
+impl DspNode for ClickNode {
+    const ID: u64 = 0x7475_7474_6963_6c6b;
+    type Inputs = U0;
+    type Outputs = U2;
+
+    fn reset(&mut self) {
+        self.voice = None;
+        self.published_beat = f64::NAN;
+        self.last_line = None;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+
+    #[inline]
+    fn tick(&mut self, _input: &Frame<f32, Self::Inputs>) -> Frame<f32, Self::Outputs> {
+        self.drain_commands();
+        if !self.advance_count_in() {
+            self.advance_transport();
+        }
+        let (left, right) = self.render();
+        [left, right].into()
+    }
+}
+
+/// `(origin beat, numerator, denominator)` of the meter at `beat`.
+fn meter_at(beat: f64, tempo_map: Option<&TempoMap>, state: &TransportState) -> (f64, u8, u8) {
+    match tempo_map {
+        Some(map) => {
+            let meter = map.meter_at(beat);
+            (
+                map.bar_start_beat(meter.bar),
+                meter.numerator,
+                meter.denominator,
+            )
+        }
+        None => (0.0, state.time_sig_numerator, state.time_sig_denominator),
+    }
+}
+
+/// Adds, updates and removes the metronome's click node to match the
+/// [`Metronome`] resource.
+///
+/// Settings, the meter at the playhead and loaded click samples are sent
+/// to the node only when they change. Runs after `transport_sync_system`,
+/// before the graph commit.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Bevy systems take their dependencies as parameters"
+)]
+pub fn metronome_system(
+    mut commands: Commands,
+    graph: Option<ResMut<TuttiGraphRes>>,
+    transport: Option<Res<TransportRes>>,
+    metronome: Option<Res<Metronome>>,
+    click: Option<ResMut<MetronomeClick>>,
+    state: Res<TransportState>,
+    tempo_map: Option<Res<TempoMap>>,
+    waves: Option<Res<Assets<WaveAsset>>>,
+    mut dirty: ResMut<GraphDirty>,
+    #[cfg(feature = "sampler")] sampler_takes: Query<(), With<crate::RecordingActive>>,
+    #[cfg(feature = "midi")] midi_takes: Query<(), With<crate::MidiRecordingActive>>,
+) {
+    let Some(mut graph) = graph else { return };
+
+    let Some(metronome) = metronome else {
+        if let Some(click) = click {
+            if graph.0.contains(click.node) {
+                graph.0.remove(click.node);
+                dirty.0 = true;
+            }
+            commands.remove_resource::<MetronomeClick>();
+        }
+        return;
+    };
+
+    let mut created = None;
+    let click = match click {
+        Some(click) => click.into_inner(),
+        None => {
+            let (sender, receiver) = crossbeam_channel::bounded(CLICK_COMMAND_CAPACITY);
+            let transport = transport.as_ref().map(|t| t.0.clone());
+            let follows_transport = transport.is_some();
+            let id = graph.0.add(An(ClickNode::new(receiver, transport)));
+            graph.0.pipe_output(id);
+            dirty.0 = true;
+            created.insert(MetronomeClick {
+                node: id,
+                commands: sender,
+                follows_transport,
+                sent: SentClickState::default(),
+            })
+        }
+    };
+
+    #[allow(unused_mut, reason = "only reassigned with recording features enabled")]
+    let mut recording = state.is_recording;
+    #[cfg(feature = "sampler")]
+    {
+        recording |= !sampler_takes.is_empty();
+    }
+    #[cfg(feature = "midi")]
+    {
+        recording |= !midi_takes.is_empty();
+    }
+    let enabled = !metronome.only_while_recording || recording;
+    if click.sent.enabled != Some(enabled) && click.send(ClickCommand::Enabled(enabled)) {
+        click.sent.enabled = Some(enabled);
+    }
+
+    let gains = (metronome.accent_gain, metronome.click_gain);
+    if click.sent.gains != Some(gains)
+        && click.send(ClickCommand::Gains {
+            accent: gains.0,
+            click: gains.1,
+        })
+    {
+        click.sent.gains = Some(gains);
+    }
+
+    let meter = meter_at(state.beat, tempo_map.as_deref(), &state);
+    if click.sent.meter != Some(meter)
+        && click.send(ClickCommand::Meter {
+            origin_beat: meter.0,
+            numerator: meter.1,
+            denominator: meter.2,
+        })
+    {
+        click.sent.meter = Some(meter);
+    }
+
+    // Wait until both configured samples are loaded, then swap together.
+    let ids = (
+        metronome.accent_sample.as_ref().map(Handle::id),
+        metronome.click_sample.as_ref().map(Handle::id),
+    );
+    if click.sent.samples != Some(ids) {
+        let resolve = |id: Option<AssetId<WaveAsset>>| match id {
+            None => Some(None),
+            Some(id) => waves
+                .as_ref()
+                .and_then(|waves| waves.get(id))
+                .map(|asset| Some(asset.0.clone())),
+        };
+        if let (Some(accent), Some(click_wave)) = (resolve(ids.0), resolve(ids.1)) {
+            if click.send(ClickCommand::Samples {
+                accent,
+                click: click_wave,
+            }) {
+                click.sent.samples = Some(ids);
+            }
+        }
+    }
+
+    if let Some(created) = created {
+        commands.insert_resource(created);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node() -> (Sender<ClickCommand>, ClickNode) {
+        let (sender, receiver) = crossbeam_channel::bounded(CLICK_COMMAND_CAPACITY);
+        let mut node = ClickNode::new(receiver, None);
+        node.set_sample_rate(1_000.0);
+        (sender, node)
+    }
+
+    /// Sample indices where a click starts, and whether it's accented.
+    fn onsets(node: &mut ClickNode, samples: usize) -> Vec<(usize, bool)> {
+        let mut onsets = Vec::new();
+        for i in 0..samples {
+            node.tick(&Default::default());
+            if let Some(voice) = node.voice {
+                if voice.pos == 1 {
+                    onsets.push((i, voice.accent));
+                }
+            }
+        }
+        onsets
+    }
+
+    #[test]
+    fn count_in_clicks_each_meter_beat_with_accent() {
+        let (sender, mut node) = node();
+        // 60 BPM at 1 kHz: 1000 samples per quarter note.
+        sender
+            .try_send(ClickCommand::CountIn {
+                bars: 1,
+                tempo: 60.0,
+            })
+            .unwrap();
+        assert_eq!(
+            onsets(&mut node, 4_500),
+            vec![(0, true), (1_000, false), (2_000, false), (3_000, false)]
+        );
+        assert!(node.count_in.is_none());
+    }
+
+    #[test]
+    fn count_in_follows_compound_meter() {
+        let (sender, mut node) = node();
+        sender
+            .try_send(ClickCommand::Meter {
+                origin_beat: 0.0,
+                numerator: 6,
+                denominator: 8,
+            })
+            .unwrap();
+        sender
+            .try_send(ClickCommand::CountIn {
+                bars: 1,
+                tempo: 60.0,
+            })
+            .unwrap();
+        // Six eighth-note clicks, 500 samples apart.
+        let clicks = onsets(&mut node, 3_200);
+        assert_eq!(clicks.len(), 6);
+        assert_eq!(clicks[1], (500, false));
+        assert!(clicks[0].1);
+    }
+}
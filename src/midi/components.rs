@@ -3,8 +3,11 @@ use bevy_reflect::prelude::*;
 use tutti::NodeId;
 
 /// A single note within a [`MidiSequence`].
+///
+/// Non-exhaustive: build with [`MidiSequenceNote::new`].
 #[cfg(feature = "midi")]
 #[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[non_exhaustive]
 pub struct MidiSequenceNote {
     pub note: u8,
     pub velocity: u8,
@@ -12,23 +15,130 @@ pub struct MidiSequenceNote {
     pub start: f64,
     /// Duration in beats.
     pub duration: f64,
+    /// MIDI channel, 0–15.
+    pub channel: u8,
+}
+
+#[cfg(feature = "midi")]
+impl MidiSequenceNote {
+    /// A note on channel 0.
+    pub fn new(note: u8, velocity: u8, start: f64, duration: f64) -> Self {
+        Self {
+            note,
+            velocity,
+            start,
+            duration,
+            channel: 0,
+        }
+    }
+
+    pub fn on_channel(mut self, channel: u8) -> Self {
+        self.channel = channel;
+        self
+    }
+}
+
+/// A non-note channel message in a [`MidiSequence`]. Values are MIDI 1
+/// resolution; playback upconverts them to tutti's MIDI 2 events.
+#[cfg(feature = "midi")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum MidiSequenceMessage {
+    ControlChange {
+        controller: u8,
+        value: u8,
+    },
+    /// 14-bit bend, `-8192..=8191`; `0` is centre.
+    PitchBend(i16),
+    /// Channel aftertouch.
+    ChannelPressure(u8),
+    /// Polyphonic (per-note) aftertouch.
+    PolyPressure {
+        note: u8,
+        pressure: u8,
+    },
+    ProgramChange(u8),
+}
+
+/// A [`MidiSequenceMessage`] at a point in a [`MidiSequence`].
+#[cfg(feature = "midi")]
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct MidiSequenceEvent {
+    /// Time in beats, relative to the sequence start.
+    pub beat: f64,
+    /// MIDI channel, 0–15.
+    pub channel: u8,
+    pub message: MidiSequenceMessage,
+}
+
+#[cfg(feature = "midi")]
+impl MidiSequenceEvent {
+    pub fn new(beat: f64, channel: u8, message: MidiSequenceMessage) -> Self {
+        Self {
+            beat,
+            channel,
+            message,
+        }
+    }
 }
 
 /// Persistent MIDI sequence that fires note_on/note_off based on transport beat.
 ///
-/// Ticked every frame by [`super::systems::midi_sequence_tick_system`].
+/// Ticked every frame by [`super::systems::midi_sequence_tick_system`],
+/// which queues each note one frame ahead with a sample offset.
+///
+/// Notes and `events` each carry their own channel, so one sequence can
+/// drive every part of a multitimbral instrument (a `PlaySoundFont` per
+/// channel, or a plugin synth). At the same instant, note-offs go out
+/// first, then `events` (so a program change or CC applies to the note
+/// it precedes), then note-ons.
+///
+/// Non-exhaustive: build with [`MidiSequence::new`] and the builder
+/// methods, then edit the public fields as needed.
 ///
 /// Not `Reflect`: `target` wraps a foreign fundsp `NodeId`.
 #[cfg(feature = "midi")]
 #[derive(Component, Debug, Clone)]
+#[non_exhaustive]
 pub struct MidiSequence {
     pub target: NodeId,
     pub notes: Vec<MidiSequenceNote>,
+    /// Control change, pitch bend, aftertouch and program change events.
+    pub events: Vec<MidiSequenceEvent>,
     pub start_beat: f64,
     pub duration_beats: f64,
     pub loop_enabled: bool,
 }
 
+#[cfg(feature = "midi")]
+impl MidiSequence {
+    /// A non-looping sequence of `notes` starting at beat 0.
+    pub fn new(target: NodeId, notes: Vec<MidiSequenceNote>, duration_beats: f64) -> Self {
+        Self {
+            target,
+            notes,
+            events: Vec::new(),
+            start_beat: 0.0,
+            duration_beats,
+            loop_enabled: false,
+        }
+    }
+
+    pub fn with_events(mut self, events: Vec<MidiSequenceEvent>) -> Self {
+        self.events = events;
+        self
+    }
+
+    pub fn starting_at(mut self, start_beat: f64) -> Self {
+        self.start_beat = start_beat;
+        self
+    }
+
+    pub fn looping(mut self, loop_enabled: bool) -> Self {
+        self.loop_enabled = loop_enabled;
+        self
+    }
+}
+
 /// Routes hardware MIDI input to an audio graph node via `MidiRoutingTable`.
 /// The routing table is rebuilt automatically when these components change.
 ///
@@ -1,13 +1,19 @@
-//! MIDI: input observation, sequence playback, hardware I/O, MPE.
+//! MIDI: input observation, sequence playback and recording, `.mid`
+//! import/export, hardware I/O, MPE.
 //!
 //! Sub-modules use the role-axis split (components / events / systems)
 //! because the duty is small + event-heavy.
 
-use bevy_app::{App, Plugin, Startup, Update};
+use bevy_app::{App, Plugin, Update};
+use bevy_asset::AssetApp;
 use bevy_ecs::prelude::*;
 
+use crate::loader::TuttiLoader;
+
 pub mod components;
 pub mod events;
+pub mod recording;
+pub mod smf;
 pub mod systems;
 
 /// Bevy plugin: MIDI input + sequence playback + hardware I/O.
@@ -15,7 +21,15 @@ pub struct TuttiMidiPlugin;
 
 impl Plugin for TuttiMidiPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<components::MidiSequenceNote>();
+        app.register_type::<components::MidiSequenceNote>()
+            .register_type::<components::MidiSequenceEvent>()
+            .register_type::<components::MidiSequenceMessage>()
+            .register_type::<recording::StartMidiRecording>()
+            .register_type::<recording::StopMidiRecording>()
+            .register_type::<recording::MidiRecordMode>();
+
+        app.init_asset::<smf::MidiClipAsset>()
+            .register_asset_loader(TuttiLoader::<smf::MidiClipAsset>::default());
 
         #[cfg(feature = "midi-hardware")]
         app.register_type::<components::ConnectMidiDevice>()
@@ -29,12 +43,14 @@ impl Plugin for TuttiMidiPlugin {
 
         app.add_message::<events::MidiInputEvent>();
 
-        app.add_systems(Startup, systems::midi_observer_setup_system);
-
         app.add_systems(
             Update,
             (
+                systems::midi_observer_setup_system,
                 systems::midi_input_event_system,
+                recording::midi_recording_start_system,
+                recording::midi_recording_capture_system,
+                recording::midi_recording_stop_system,
                 systems::midi_routing_sync_system,
                 systems::midi_sequence_setup_system,
                 systems::midi_sequence_tick_system,
@@ -42,8 +58,15 @@ impl Plugin for TuttiMidiPlugin {
                 .chain(),
         );
 
+        // MPE setup runs whenever the bus is (re)inserted — on the first
+        // frame, and again after a `RetryAudioEngine` rebuild. Until then
+        // the expression resource reads as disabled.
         #[cfg(feature = "mpe")]
-        app.add_systems(Startup, systems::mpe_setup_system);
+        app.init_resource::<systems::MpeExpressionResource>()
+            .add_systems(
+                Update,
+                systems::mpe_setup_system.run_if(resource_added::<crate::resources::MidiBusRes>),
+            );
 
         #[cfg(feature = "midi-hardware")]
         {
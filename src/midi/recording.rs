@@ -0,0 +1,424 @@
+//! MIDI input recording: `StartMidiRecording` / `StopMidiRecording`
+//! triggers, mirroring the sampler's `StartRecording` flow.
+//!
+//! While a take is active, every note from [`MidiInputEvent`] is stamped
+//! with the transport beat. On stop the take is written into the target
+//! entity's [`MidiSequence`] (created if missing), with durations
+//! measured in played beats — a note held across a loop wrap keeps its
+//! full length and the sequence releases it in the next pass.
+//!
+//! With the transport looping, a take lands at its loop position, so
+//! [`MidiRecordMode::Overdub`] layers each pass onto the same bars and
+//! [`MidiRecordMode::Replace`] clears what the take covered first.
+
+use std::collections::HashMap;
+
+use bevy_ecs::message::MessageReader;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+use tutti::core::ecs::AudioNode;
+
+use super::components::{MidiSequence, MidiSequenceNote};
+use super::events::MidiInputEvent;
+use crate::resources::TransportRes;
+
+/// How a finished take combines with the target's existing sequence.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum MidiRecordMode {
+    /// Clear the notes and events the take's time span covered, then add
+    /// the take. A take as long as the loop clears the whole sequence.
+    #[default]
+    Replace,
+    /// Add the take on top of what's there.
+    Overdub,
+}
+
+/// Trigger component: spawn an entity with this to start recording MIDI
+/// input into `target_entity`'s [`MidiSequence`].
+///
+/// `midi_recording_start_system` replaces it with
+/// [`MidiRecordingActive`]. If the target has no `MidiSequence` when the
+/// take stops, one is created targeting its `AudioNode`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component, Clone)]
+pub struct StartMidiRecording {
+    pub target_entity: Entity,
+    /// Grid in beats to snap note starts to (`0.25` = sixteenths).
+    /// `None` records unquantized.
+    pub quantize: Option<f64>,
+    pub mode: MidiRecordMode,
+}
+
+impl StartMidiRecording {
+    pub fn new(target_entity: Entity) -> Self {
+        Self {
+            target_entity,
+            quantize: None,
+            mode: MidiRecordMode::Replace,
+        }
+    }
+
+    pub fn quantize(mut self, grid_beats: f64) -> Self {
+        self.quantize = Some(grid_beats);
+        self
+    }
+
+    pub fn mode(mut self, mode: MidiRecordMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+/// Trigger component: spawn or insert to stop the take recording into
+/// `target_entity`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Clone)]
+pub struct StopMidiRecording {
+    pub target_entity: Entity,
+}
+
+/// An in-progress MIDI take.
+///
+/// Added automatically by `midi_recording_start_system`; removed when
+/// [`StopMidiRecording`] is processed.
+///
+/// Not `Reflect`: a runtime capture buffer.
+#[derive(Component, Debug, Clone)]
+pub struct MidiRecordingActive {
+    pub target_entity: Entity,
+    pub quantize: Option<f64>,
+    pub mode: MidiRecordMode,
+    /// Transport beat when the take started.
+    pub start_beat: f64,
+    /// Beats played since the take started, across loop wraps.
+    elapsed: f64,
+    last_beat: f64,
+    /// Loop range active when the take started, if any.
+    loop_range: Option<(f64, f64)>,
+    /// Note-ons awaiting their note-off: `(channel, note)` →
+    /// `(transport beat, elapsed at note-on, velocity)`.
+    held: HashMap<(u8, u8), (f64, f64, u8)>,
+    /// Finished notes, `start` in transport beats.
+    notes: Vec<MidiSequenceNote>,
+}
+
+impl MidiRecordingActive {
+    /// Notes captured so far, `start` in transport beats.
+    pub fn notes(&self) -> &[MidiSequenceNote] {
+        &self.notes
+    }
+
+    /// Whether the take has played past the loop end at least once.
+    pub fn wrapped(&self) -> bool {
+        self.loop_range
+            .is_some_and(|(start, end)| self.start_beat + self.elapsed >= end && end > start)
+    }
+
+    fn advance(&mut self, beat: f64) {
+        self.elapsed += if beat >= self.last_beat {
+            beat - self.last_beat
+        } else if let Some((start, end)) = self.loop_range {
+            ((end - self.last_beat) + (beat - start)).max(0.0)
+        } else {
+            0.0
+        };
+        self.last_beat = beat;
+    }
+
+    fn release(&mut self, channel: u8, note: u8) {
+        if let Some((at, on_elapsed, velocity)) = self.held.remove(&(channel, note)) {
+            let duration = (self.elapsed - on_elapsed).max(0.0);
+            self.notes
+                .push(MidiSequenceNote::new(note, velocity, at, duration).on_channel(channel));
+        }
+    }
+}
+
+/// Processes [`StartMidiRecording`] triggers: stamps the transport beat
+/// and replaces the trigger with [`MidiRecordingActive`].
+pub fn midi_recording_start_system(
+    mut commands: Commands,
+    transport: Option<Res<TransportRes>>,
+    query: Query<(Entity, &StartMidiRecording), Added<StartMidiRecording>>,
+) {
+    let Some(transport) = transport else { return };
+
+    for (entity, start) in query.iter() {
+        let beat = transport.current_beat();
+        let loop_range = if transport.is_loop_enabled() {
+            transport.get_loop_range().filter(|(s, e)| e > s)
+        } else {
+            None
+        };
+        commands
+            .entity(entity)
+            .remove::<StartMidiRecording>()
+            .insert(MidiRecordingActive {
+                target_entity: start.target_entity,
+                quantize: start.quantize,
+                mode: start.mode,
+                start_beat: beat,
+                elapsed: 0.0,
+                last_beat: beat,
+                loop_range,
+                held: HashMap::new(),
+                notes: Vec::new(),
+            });
+        bevy_log::info!(
+            "MIDI recording started into {:?} at beat {:.2} ({:?})",
+            start.target_entity,
+            beat,
+            start.mode
+        );
+    }
+}
+
+/// Stamps incoming [`MidiInputEvent`] notes against the transport for
+/// every active take. Runs after `midi_input_event_system`.
+pub fn midi_recording_capture_system(
+    transport: Option<Res<TransportRes>>,
+    mut events: MessageReader<MidiInputEvent>,
+    mut takes: Query<&mut MidiRecordingActive>,
+) {
+    let Some(transport) = transport else {
+        events.clear();
+        return;
+    };
+    let beat = transport.current_beat();
+    let events: Vec<&MidiInputEvent> = events.read().collect();
+
+    for mut take in takes.iter_mut() {
+        take.advance(beat);
+        for event in &events {
+            let Some(note) = event.note() else { continue };
+            let channel = event.event().channel();
+            if event.is_note_on() && event.velocity().unwrap_or(0) > 0 {
+                // A re-strike without a note-off ends the previous note.
+                take.release(channel, note);
+                let elapsed = take.elapsed;
+                let velocity = event.velocity().unwrap_or(0);
+                take.held.insert((channel, note), (beat, elapsed, velocity));
+            } else if event.is_note_off() || event.is_note_on() {
+                take.release(channel, note);
+            }
+        }
+    }
+}
+
+/// Processes [`StopMidiRecording`] triggers: closes held notes, writes
+/// the take into the target's [`MidiSequence`] and removes
+/// [`MidiRecordingActive`].
+pub fn midi_recording_stop_system(
+    mut commands: Commands,
+    transport: Option<Res<TransportRes>>,
+    stops: Query<(Entity, &StopMidiRecording), Added<StopMidiRecording>>,
+    mut takes: Query<(Entity, &mut MidiRecordingActive)>,
+    mut sequences: Query<&mut MidiSequence>,
+    nodes: Query<&AudioNode>,
+) {
+    for (stop_entity, stop) in stops.iter() {
+        commands.entity(stop_entity).remove::<StopMidiRecording>();
+
+        for (take_entity, mut take) in takes.iter_mut() {
+            if take.target_entity != stop.target_entity {
+                continue;
+            }
+            if let Some(transport) = &transport {
+                take.advance(transport.current_beat());
+            }
+            let held: Vec<(u8, u8)> = take.held.keys().copied().collect();
+            for (channel, note) in held {
+                take.release(channel, note);
+            }
+            commands.entity(take_entity).remove::<MidiRecordingActive>();
+
+            if let Ok(mut sequence) = sequences.get_mut(take.target_entity) {
+                apply_take(&mut sequence, &take);
+            } else if let Ok(node) = nodes.get(take.target_entity) {
+                let mut sequence = new_sequence_for(&take, node.0);
+                apply_take(&mut sequence, &take);
+                commands.entity(take.target_entity).insert(sequence);
+            } else {
+                bevy_log::warn!(
+                    "StopMidiRecording: target {:?} has neither MidiSequence nor AudioNode; take dropped",
+                    take.target_entity
+                );
+                continue;
+            }
+            bevy_log::info!(
+                "MIDI recording stopped: {} notes into {:?}",
+                take.notes.len(),
+                take.target_entity
+            );
+        }
+    }
+}
+
+/// An empty sequence shaped to hold `take`: the loop when the transport
+/// was looping, otherwise whole beats from the take's start to its end.
+fn new_sequence_for(take: &MidiRecordingActive, target: tutti::NodeId) -> MidiSequence {
+    let (start_beat, duration_beats, loop_enabled) = match take.loop_range {
+        Some((start, end)) => (start, end - start, true),
+        None => {
+            let start = take.start_beat.floor();
+            (
+                start,
+                (take.start_beat + take.elapsed - start).ceil().max(1.0),
+                false,
+            )
+        }
+    };
+    MidiSequence {
+        target,
+        notes: Vec::new(),
+        events: Vec::new(),
+        start_beat,
+        duration_beats,
+        loop_enabled,
+    }
+}
+
+/// Merge a finished take into `sequence` per the take's mode.
+fn apply_take(sequence: &mut MidiSequence, take: &MidiRecordingActive) {
+    let looped = sequence.loop_enabled && sequence.duration_beats > 0.0;
+    let to_local = |beat: f64| {
+        let local = beat - sequence.start_beat;
+        if looped {
+            local.rem_euclid(sequence.duration_beats)
+        } else {
+            local
+        }
+    };
+
+    if take.mode == MidiRecordMode::Replace {
+        if looped && take.elapsed >= sequence.duration_beats {
+            sequence.notes.clear();
+            sequence.events.clear();
+        } else {
+            // The span the take played over, continuing past the loop end
+            // into the start of the next pass.
+            let from = to_local(take.start_beat);
+            let to = from + take.elapsed;
+            let duration = sequence.duration_beats;
+            let covered =
+                |beat: f64| (beat >= from && beat < to) || (looped && beat + duration < to);
+            sequence.notes.retain(|n| !covered(n.start));
+            sequence.events.retain(|e| !covered(e.beat));
+        }
+    }
+
+    for note in &take.notes {
+        let mut local = to_local(note.start);
+        if let Some(grid) = take.quantize.filter(|g| *g > 0.0) {
+            local = (local / grid).round() * grid;
+            if looped {
+                local = local.rem_euclid(sequence.duration_beats);
+            }
+        }
+        sequence.notes.push(MidiSequenceNote {
+            start: local.max(0.0),
+            ..*note
+        });
+    }
+    sequence
+        .notes
+        .sort_by(|a, b| a.start.total_cmp(&b.start).then(a.note.cmp(&b.note)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::components::{MidiSequenceEvent, MidiSequenceMessage};
+
+    fn take(mode: MidiRecordMode, quantize: Option<f64>) -> MidiRecordingActive {
+        MidiRecordingActive {
+            target_entity: Entity::PLACEHOLDER,
+            quantize,
+            mode,
+            start_beat: 3.0,
+            elapsed: 0.0,
+            last_beat: 3.0,
+            loop_range: Some((0.0, 4.0)),
+            held: HashMap::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    fn looped_sequence() -> MidiSequence {
+        MidiSequence {
+            target: tutti::NodeId::new(),
+            notes: vec![MidiSequenceNote::new(48, 90, 1.0, 1.0)],
+            events: Vec::new(),
+            start_beat: 0.0,
+            duration_beats: 4.0,
+            loop_enabled: true,
+        }
+    }
+
+    #[test]
+    fn note_held_across_loop_wrap_keeps_full_duration() {
+        let mut t = take(MidiRecordMode::Overdub, None);
+        t.advance(3.5);
+        t.held.insert((0, 60), (3.5, t.elapsed, 100));
+        t.advance(0.25); // wrapped: 0.5 + 0.25 beats later
+        t.release(0, 60);
+        assert_eq!(t.notes.len(), 1);
+        assert!((t.notes[0].duration - 0.75).abs() < 1e-9);
+        assert!(t.wrapped());
+    }
+
+    #[test]
+    fn overdub_quantizes_and_keeps_existing_notes() {
+        let mut t = take(MidiRecordMode::Overdub, Some(0.5));
+        t.notes.push(MidiSequenceNote::new(60, 100, 3.9, 0.5));
+        let mut seq = looped_sequence();
+        apply_take(&mut seq, &t);
+        // 3.9 snaps to 4.0, which wraps to the loop start.
+        assert_eq!(seq.notes.len(), 2);
+        assert_eq!(seq.notes[0].start, 0.0);
+        assert_eq!(seq.notes[0].note, 60);
+    }
+
+    #[test]
+    fn replace_clears_only_the_recorded_span() {
+        let mut t = take(MidiRecordMode::Replace, None);
+        t.start_beat = 0.5;
+        t.last_beat = 0.5;
+        t.advance(1.5);
+        t.notes.push(MidiSequenceNote::new(62, 100, 0.75, 0.25));
+        let mut seq = looped_sequence();
+        seq.notes.push(MidiSequenceNote::new(50, 90, 3.0, 0.5));
+        apply_take(&mut seq, &t);
+        let starts: Vec<(f64, u8)> = seq.notes.iter().map(|n| (n.start, n.note)).collect();
+        assert_eq!(starts, vec![(0.75, 62), (3.0, 50)]);
+    }
+
+    #[test]
+    fn short_replace_across_the_loop_end_keeps_the_rest_of_the_loop() {
+        // 3.5 → 4.5 in a 4-beat loop: wraps, but only covers one beat.
+        let mut t = take(MidiRecordMode::Replace, None);
+        t.start_beat = 3.5;
+        t.last_beat = 3.5;
+        t.advance(0.5);
+        assert!(t.wrapped());
+        let mut seq = looped_sequence();
+        seq.notes.push(MidiSequenceNote::new(50, 90, 0.25, 0.5));
+        seq.notes.push(MidiSequenceNote::new(52, 90, 3.75, 0.25));
+        seq.events.push(MidiSequenceEvent::new(
+            0.25,
+            0,
+            MidiSequenceMessage::ProgramChange(1),
+        ));
+        seq.events.push(MidiSequenceEvent::new(
+            2.0,
+            0,
+            MidiSequenceMessage::ProgramChange(2),
+        ));
+        apply_take(&mut seq, &t);
+        let notes: Vec<u8> = seq.notes.iter().map(|n| n.note).collect();
+        assert_eq!(notes, vec![48]);
+        let beats: Vec<f64> = seq.events.iter().map(|e| e.beat).collect();
+        assert_eq!(beats, vec![2.0]);
+    }
+}
@@ -0,0 +1,593 @@
+//! Standard MIDI File (`.mid`) import and export.
+//!
+//! [`MidiClipAsset`] is the parsed file: one [`MidiClipTrack`] per `MTrk`
+//! chunk (type 0 and type 1 files), plus the tempo and time-signature
+//! meta events. It loads through the generic
+//! [`TuttiLoader`](crate::TuttiLoader) like waves and SoundFonts, and
+//! converts to one [`MidiSequence`] per track:
+//!
+//! ```ignore
+//! let clip: Handle<MidiClipAsset> = asset_server.load("clips/groove.mid");
+//! // …once loaded:
+//! for seq in clips.get(&clip).unwrap().sequences(synth_node) {
+//!     commands.spawn(seq);
+//! }
+//! ```
+//!
+//! The writer goes the other way: [`MidiClipAsset::from_sequences`]
+//! collects sequences (authored, or recorded from MIDI input) and
+//! [`MidiClipAsset::to_smf`] encodes a type 1 file.
+//!
+//! Times are kept in beats (quarter notes), so a clip plays at whatever
+//! tempo the transport runs; the file's tempo map is carried along in
+//! [`MidiClipAsset::tempo_changes`] for hosts that want to apply it.
+//! SMPTE time division, SysEx and unknown meta events are not supported
+//! and are skipped (time division is an error).
+
+use std::collections::{HashMap, VecDeque};
+
+use bevy_asset::Asset;
+use bevy_reflect::TypePath;
+use tutti::NodeId;
+
+use super::components::{MidiSequence, MidiSequenceEvent, MidiSequenceMessage, MidiSequenceNote};
+
+/// Ticks per quarter note used by [`MidiClipAsset::from_sequences`].
+pub const DEFAULT_TICKS_PER_BEAT: u16 = 480;
+
+/// Errors from [`MidiClipAsset::parse`].
+#[derive(Debug, thiserror::Error)]
+pub enum SmfError {
+    #[error("not a Standard MIDI File (missing MThd header)")]
+    NotSmf,
+    #[error("file ends inside a chunk or event")]
+    Truncated,
+    #[error("unsupported SMF format {0}")]
+    UnsupportedFormat(u16),
+    #[error("SMPTE time division is not supported")]
+    SmpteTimeDivision,
+    #[error("invalid event at byte {0}")]
+    InvalidEvent(usize),
+}
+
+/// A tempo change from a `Set Tempo` meta event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiTempoChange {
+    pub beat: f64,
+    pub bpm: f64,
+}
+
+/// A meter change from a `Time Signature` meta event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MidiTimeSignature {
+    pub beat: f64,
+    pub numerator: u8,
+    pub denominator: u8,
+}
+
+/// One `MTrk` chunk, converted to beats.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MidiClipTrack {
+    /// From the track's `Sequence/Track Name` meta event.
+    pub name: Option<String>,
+    pub notes: Vec<MidiSequenceNote>,
+    pub events: Vec<MidiSequenceEvent>,
+    /// Position of the track's `End of Track` event, in beats.
+    pub length_beats: f64,
+}
+
+impl MidiClipTrack {
+    /// Whether the track has nothing to play (e.g. a type 1 tempo track).
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty() && self.events.is_empty()
+    }
+}
+
+/// A parsed Standard MIDI File.
+///
+/// Not `Reflect`: loaded from disk, never stored on entities.
+#[derive(Asset, TypePath, Debug, Clone, Default, PartialEq)]
+pub struct MidiClipAsset {
+    pub ticks_per_beat: u16,
+    pub tracks: Vec<MidiClipTrack>,
+    /// In file order. Tempo is global in type 0/1 files, so changes from
+    /// every track are merged here.
+    pub tempo_changes: Vec<MidiTempoChange>,
+    pub time_signatures: Vec<MidiTimeSignature>,
+}
+
+impl tutti_asset::TuttiAsset for MidiClipAsset {
+    type Error = SmfError;
+    const EXTENSIONS: &'static [&'static str] = &["mid", "midi", "smf"];
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::parse(bytes)
+    }
+}
+
+impl MidiClipAsset {
+    /// Initial tempo, if the file sets one.
+    pub fn tempo_bpm(&self) -> Option<f64> {
+        self.tempo_changes.first().map(|t| t.bpm)
+    }
+
+    /// Initial time signature as `(numerator, denominator)`, if set.
+    pub fn time_signature(&self) -> Option<(u8, u8)> {
+        self.time_signatures
+            .first()
+            .map(|t| (t.numerator, t.denominator))
+    }
+
+    /// Track `index` as a non-looping sequence targeting `target`,
+    /// starting at beat 0.
+    pub fn to_sequence(&self, index: usize, target: NodeId) -> Option<MidiSequence> {
+        let track = self.tracks.get(index)?;
+        let content_end = track
+            .notes
+            .iter()
+            .map(|n| n.start + n.duration)
+            .chain(track.events.iter().map(|e| e.beat))
+            .fold(0.0_f64, f64::max);
+        Some(MidiSequence {
+            target,
+            notes: track.notes.clone(),
+            events: track.events.clone(),
+            start_beat: 0.0,
+            duration_beats: track.length_beats.max(content_end),
+            loop_enabled: false,
+        })
+    }
+
+    /// One sequence per track that has notes or events, all targeting
+    /// `target`. Type 1 tempo tracks are skipped.
+    pub fn sequences(&self, target: NodeId) -> Vec<MidiSequence> {
+        (0..self.tracks.len())
+            .filter(|&i| !self.tracks[i].is_empty())
+            .filter_map(|i| self.to_sequence(i, target))
+            .collect()
+    }
+
+    /// Build a clip from sequences, one track each, at `tempo_bpm`.
+    ///
+    /// Sequence times are taken relative to the sequence (not
+    /// `start_beat`). Use [`to_smf`](Self::to_smf) to encode it.
+    pub fn from_sequences<'a>(
+        sequences: impl IntoIterator<Item = &'a MidiSequence>,
+        tempo_bpm: f64,
+    ) -> Self {
+        let tracks = sequences
+            .into_iter()
+            .map(|seq| MidiClipTrack {
+                name: None,
+                notes: seq.notes.clone(),
+                events: seq.events.clone(),
+                length_beats: seq.duration_beats,
+            })
+            .collect();
+        Self {
+            ticks_per_beat: DEFAULT_TICKS_PER_BEAT,
+            tracks,
+            tempo_changes: vec![MidiTempoChange {
+                beat: 0.0,
+                bpm: tempo_bpm,
+            }],
+            time_signatures: Vec::new(),
+        }
+    }
+
+    /// Parse a type 0 or type 1 Standard MIDI File.
+    pub fn parse(bytes: &[u8]) -> Result<Self, SmfError> {
+        let mut cursor = Cursor { bytes, pos: 0 };
+        if cursor.take(4)? != b"MThd" {
+            return Err(SmfError::NotSmf);
+        }
+        let header_len = cursor.u32()? as usize;
+        let header = cursor.take(header_len)?;
+        if header.len() < 6 {
+            return Err(SmfError::Truncated);
+        }
+        let format = u16::from_be_bytes([header[0], header[1]]);
+        let track_count = u16::from_be_bytes([header[2], header[3]]);
+        let division = u16::from_be_bytes([header[4], header[5]]);
+        if format > 1 {
+            return Err(SmfError::UnsupportedFormat(format));
+        }
+        if division & 0x8000 != 0 {
+            return Err(SmfError::SmpteTimeDivision);
+        }
+        let ticks_per_beat = division.max(1);
+
+        let mut clip = Self {
+            ticks_per_beat,
+            ..Default::default()
+        };
+        let mut tempo_ticks = Vec::new();
+        while clip.tracks.len() < track_count as usize && cursor.remaining() > 0 {
+            let id = cursor.take(4)?;
+            let len = cursor.u32()? as usize;
+            let start = cursor.pos;
+            let body = cursor.take(len)?;
+            if id != b"MTrk" {
+                continue;
+            }
+            let track = parse_track(body, start, ticks_per_beat, &mut clip, &mut tempo_ticks)?;
+            clip.tracks.push(track);
+        }
+        tempo_ticks.sort_by_key(|&(tick, _)| tick);
+        clip.tempo_changes = tempo_ticks
+            .into_iter()
+            .map(|(tick, bpm)| MidiTempoChange {
+                beat: tick as f64 / ticks_per_beat as f64,
+                bpm,
+            })
+            .collect();
+        clip.time_signatures
+            .sort_by(|a, b| a.beat.total_cmp(&b.beat));
+        Ok(clip)
+    }
+
+    /// Encode as a type 1 Standard MIDI File: a tempo track followed by
+    /// one track per [`MidiClipTrack`].
+    pub fn to_smf(&self) -> Vec<u8> {
+        let ppq = self.ticks_per_beat.max(1);
+        let to_ticks = |beat: f64| (beat.max(0.0) * ppq as f64).round() as u64;
+
+        let mut out = Vec::new();
+        out.extend_from_slice(b"MThd");
+        out.extend_from_slice(&6u32.to_be_bytes());
+        out.extend_from_slice(&1u16.to_be_bytes());
+        out.extend_from_slice(&((self.tracks.len() + 1) as u16).to_be_bytes());
+        out.extend_from_slice(&ppq.to_be_bytes());
+
+        // Tempo track.
+        let mut meta: Vec<(u64, u8, Vec<u8>)> = Vec::new();
+        for sig in &self.time_signatures {
+            let dd = sig.denominator.max(1).trailing_zeros() as u8;
+            meta.push((
+                to_ticks(sig.beat),
+                0,
+                vec![0xFF, 0x58, 4, sig.numerator, dd, 24, 8],
+            ));
+        }
+        for tempo in &self.tempo_changes {
+            let micros = (60_000_000.0 / tempo.bpm.max(1e-3)).round() as u32;
+            let b = micros.min(0xFF_FFFF).to_be_bytes();
+            meta.push((
+                to_ticks(tempo.beat),
+                0,
+                vec![0xFF, 0x51, 3, b[1], b[2], b[3]],
+            ));
+        }
+        write_track(&mut out, meta, 0);
+
+        for track in &self.tracks {
+            let mut events: Vec<(u64, u8, Vec<u8>)> = Vec::new();
+            if let Some(name) = &track.name {
+                let mut data = vec![0xFF, 0x03];
+                write_vlq(&mut data, name.len() as u32);
+                data.extend_from_slice(name.as_bytes());
+                events.push((0, 0, data));
+            }
+            for n in &track.notes {
+                let ch = n.channel & 0x0F;
+                let on = to_ticks(n.start);
+                // At least one tick long, so the off never sorts before its on.
+                let off = to_ticks(n.start + n.duration).max(on + 1);
+                events.push((
+                    on,
+                    2,
+                    vec![0x90 | ch, n.note & 0x7F, n.velocity.clamp(1, 127)],
+                ));
+                events.push((off, 0, vec![0x80 | ch, n.note & 0x7F, 0]));
+            }
+            for e in &track.events {
+                events.push((to_ticks(e.beat), 1, encode_message(e.channel, e.message)));
+            }
+            let end = to_ticks(track.length_beats);
+            write_track(&mut out, events, end);
+        }
+        out
+    }
+}
+
+/// Channel-voice bytes for a sequence message.
+fn encode_message(channel: u8, message: MidiSequenceMessage) -> Vec<u8> {
+    let ch = channel & 0x0F;
+    match message {
+        MidiSequenceMessage::ControlChange { controller, value } => {
+            vec![0xB0 | ch, controller & 0x7F, value & 0x7F]
+        }
+        MidiSequenceMessage::PitchBend(bend) => {
+            let raw = (bend.clamp(-8192, 8191) as i32 + 8192) as u16;
+            vec![0xE0 | ch, (raw & 0x7F) as u8, (raw >> 7) as u8]
+        }
+        MidiSequenceMessage::ChannelPressure(p) => vec![0xD0 | ch, p & 0x7F],
+        MidiSequenceMessage::PolyPressure { note, pressure } => {
+            vec![0xA0 | ch, note & 0x7F, pressure & 0x7F]
+        }
+        MidiSequenceMessage::ProgramChange(p) => vec![0xC0 | ch, p & 0x7F],
+    }
+}
+
+/// Append an `MTrk` chunk. Events are `(tick, order, bytes)`; `order`
+/// breaks ties so note-offs precede other events at the same tick.
+fn write_track(out: &mut Vec<u8>, mut events: Vec<(u64, u8, Vec<u8>)>, end_tick: u64) {
+    events.sort_by_key(|(tick, order, _)| (*tick, *order));
+    let mut body = Vec::new();
+    let mut last = 0;
+    for (tick, _, bytes) in &events {
+        write_vlq(&mut body, (tick - last) as u32);
+        body.extend_from_slice(bytes);
+        last = *tick;
+    }
+    write_vlq(&mut body, end_tick.saturating_sub(last) as u32);
+    body.extend_from_slice(&[0xFF, 0x2F, 0]);
+
+    out.extend_from_slice(b"MTrk");
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    out.extend_from_slice(&body);
+}
+
+fn write_vlq(out: &mut Vec<u8>, mut value: u32) {
+    let mut buf = [0u8; 5];
+    let mut i = buf.len() - 1;
+    buf[i] = (value & 0x7F) as u8;
+    value >>= 7;
+    while value > 0 {
+        i -= 1;
+        buf[i] = (value & 0x7F) as u8 | 0x80;
+        value >>= 7;
+    }
+    out.extend_from_slice(&buf[i..]);
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SmfError> {
+        let slice = self
+            .bytes
+            .get(self.pos..self.pos + n)
+            .ok_or(SmfError::Truncated)?;
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, SmfError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, SmfError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn vlq(&mut self) -> Result<u32, SmfError> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(SmfError::InvalidEvent(self.pos))
+    }
+}
+
+fn parse_track(
+    body: &[u8],
+    base: usize,
+    ticks_per_beat: u16,
+    clip: &mut MidiClipAsset,
+    tempo_ticks: &mut Vec<(u64, f64)>,
+) -> Result<MidiClipTrack, SmfError> {
+    let beats = |tick: u64| tick as f64 / ticks_per_beat as f64;
+    let mut cursor = Cursor {
+        bytes: body,
+        pos: 0,
+    };
+    let mut track = MidiClipTrack::default();
+    let mut tick = 0u64;
+    let mut running: Option<u8> = None;
+    // Note-ons waiting for their note-off, oldest first.
+    let mut held: HashMap<(u8, u8), VecDeque<(u64, u8)>> = HashMap::new();
+
+    while cursor.remaining() > 0 {
+        tick += cursor.vlq()? as u64;
+        let first = cursor.u8()?;
+        let status = if first & 0x80 != 0 {
+            first
+        } else {
+            // Running status: `first` is the first data byte.
+            cursor.pos -= 1;
+            running.ok_or(SmfError::InvalidEvent(base + cursor.pos))?
+        };
+
+        match status {
+            0xFF => {
+                running = None;
+                let kind = cursor.u8()?;
+                let len = cursor.vlq()? as usize;
+                let data = cursor.take(len)?;
+                match kind {
+                    0x03 => track.name = Some(String::from_utf8_lossy(data).into_owned()),
+                    0x51 if data.len() >= 3 => {
+                        let micros = u32::from_be_bytes([0, data[0], data[1], data[2]]).max(1);
+                        tempo_ticks.push((tick, 60_000_000.0 / micros as f64));
+                    }
+                    0x58 if data.len() >= 2 => clip.time_signatures.push(MidiTimeSignature {
+                        beat: beats(tick),
+                        numerator: data[0],
+                        denominator: 1u8.checked_shl(data[1] as u32).unwrap_or(4),
+                    }),
+                    0x2F => break,
+                    _ => {}
+                }
+            }
+            0xF0 | 0xF7 => {
+                running = None;
+                let len = cursor.vlq()? as usize;
+                cursor.take(len)?;
+            }
+            0x80..=0xEF => {
+                running = Some(status);
+                let channel = status & 0x0F;
+                let d1 = cursor.u8()? & 0x7F;
+                let d2 = if matches!(status & 0xF0, 0xC0 | 0xD0) {
+                    0
+                } else {
+                    cursor.u8()? & 0x7F
+                };
+                let beat = beats(tick);
+                let message = match status & 0xF0 {
+                    0x90 if d2 > 0 => {
+                        held.entry((channel, d1)).or_default().push_back((tick, d2));
+                        None
+                    }
+                    0x80 | 0x90 => {
+                        if let Some((on, velocity)) =
+                            held.get_mut(&(channel, d1)).and_then(|q| q.pop_front())
+                        {
+                            track.notes.push(
+                                MidiSequenceNote::new(d1, velocity, beats(on), beats(tick - on))
+                                    .on_channel(channel),
+                            );
+                        }
+                        None
+                    }
+                    0xA0 => Some(MidiSequenceMessage::PolyPressure {
+                        note: d1,
+                        pressure: d2,
+                    }),
+                    0xB0 => Some(MidiSequenceMessage::ControlChange {
+                        controller: d1,
+                        value: d2,
+                    }),
+                    0xC0 => Some(MidiSequenceMessage::ProgramChange(d1)),
+                    0xD0 => Some(MidiSequenceMessage::ChannelPressure(d1)),
+                    _ => Some(MidiSequenceMessage::PitchBend(
+                        ((((d2 as i32) << 7) | d1 as i32) - 8192) as i16,
+                    )),
+                };
+                if let Some(message) = message {
+                    track
+                        .events
+                        .push(MidiSequenceEvent::new(beat, channel, message));
+                }
+            }
+            _ => return Err(SmfError::InvalidEvent(base + cursor.pos)),
+        }
+    }
+
+    // Notes never released end with the track.
+    for ((channel, note), queue) in held {
+        for (on, velocity) in queue {
+            track.notes.push(
+                MidiSequenceNote::new(note, velocity, beats(on), beats(tick - on))
+                    .on_channel(channel),
+            );
+        }
+    }
+    track
+        .notes
+        .sort_by(|a, b| a.start.total_cmp(&b.start).then(a.note.cmp(&b.note)));
+    track.length_beats = beats(tick);
+    Ok(track)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence() -> MidiSequence {
+        MidiSequence {
+            target: NodeId::new(),
+            notes: vec![
+                MidiSequenceNote::new(60, 100, 0.0, 1.0),
+                // Back-to-back repeat of the same pitch.
+                MidiSequenceNote::new(60, 80, 1.0, 0.5),
+                MidiSequenceNote::new(36, 127, 0.5, 0.25).on_channel(9),
+                // Zero length: written as a one-tick note.
+                MidiSequenceNote::new(72, 90, 3.0, 0.0),
+            ],
+            events: vec![
+                MidiSequenceEvent::new(0.0, 0, MidiSequenceMessage::ProgramChange(12)),
+                MidiSequenceEvent::new(1.5, 0, MidiSequenceMessage::PitchBend(-4096)),
+                MidiSequenceEvent::new(
+                    2.0,
+                    0,
+                    MidiSequenceMessage::ControlChange {
+                        controller: 64,
+                        value: 127,
+                    },
+                ),
+            ],
+            start_beat: 0.0,
+            duration_beats: 4.0,
+            loop_enabled: false,
+        }
+    }
+
+    #[test]
+    fn round_trips_notes_events_and_tempo() {
+        let seq = sequence();
+        let mut clip = MidiClipAsset::from_sequences([&seq], 96.0);
+        clip.time_signatures.push(MidiTimeSignature {
+            beat: 0.0,
+            numerator: 7,
+            denominator: 8,
+        });
+        let parsed = MidiClipAsset::parse(&clip.to_smf()).expect("parse");
+
+        assert_eq!(parsed.tracks.len(), 2);
+        assert!(parsed.tracks[0].is_empty());
+        assert!((parsed.tempo_bpm().unwrap() - 96.0).abs() < 1e-3);
+        assert_eq!(parsed.time_signature(), Some((7, 8)));
+
+        let out = parsed.sequences(seq.target);
+        assert_eq!(out.len(), 1);
+        let mut expected = seq.notes.clone();
+        // The zero-length note comes back one tick long.
+        expected[3].duration = 1.0 / DEFAULT_TICKS_PER_BEAT as f64;
+        expected.sort_by(|a, b| a.start.total_cmp(&b.start).then(a.note.cmp(&b.note)));
+        assert_eq!(out[0].notes, expected);
+        assert_eq!(out[0].events, seq.events);
+        assert_eq!(out[0].duration_beats, 4.0);
+    }
+
+    #[test]
+    fn parses_running_status_and_zero_velocity_note_off() {
+        #[rustfmt::skip]
+        let track: &[u8] = &[
+            0x00, 0x90, 60, 100,    // note-on
+            0x60, 60, 0,            // running status, velocity 0 = off
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        let mut bytes = b"MThd\0\0\0\x06\0\0\0\x01\0\x60MTrk".to_vec();
+        bytes.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(track);
+
+        let clip = MidiClipAsset::parse(&bytes).expect("parse");
+        assert_eq!(
+            clip.tracks[0].notes,
+            vec![MidiSequenceNote::new(60, 100, 0.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn rejects_smpte_division() {
+        let bytes = b"MThd\0\0\0\x06\0\0\0\x01\xE7\x28";
+        assert!(matches!(
+            MidiClipAsset::parse(bytes),
+            Err(SmfError::SmpteTimeDivision)
+        ));
+    }
+}
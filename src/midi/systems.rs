@@ -41,24 +41,29 @@ pub struct MidiDeviceState {
 /// Sets up the UI observer on the hardware MIDI input port, funneling events
 /// into `MidiInputObserver`'s channel. No-op when `midi-hardware` is disabled
 /// (there's no hardware port to observe).
+///
+/// Runs every frame until the port exists, so an engine that only comes
+/// up after a `RetryAudioEngine` still gets its observer installed.
 #[cfg(feature = "midi")]
 pub(crate) fn midi_observer_setup_system(
     #[cfg(feature = "midi-hardware")] midi_io: Option<Res<crate::MidiIoRes>>,
     mut sender_res: ResMut<MidiObserverSender>,
 ) {
-    let Some(sender) = sender_res.sender.take() else {
+    if sender_res.sender.is_none() {
         return;
-    };
+    }
 
     #[cfg(feature = "midi-hardware")]
     {
         let Some(midi_io) = midi_io else { return };
-        midi_io.0.set_input_observer(sender);
+        if let Some(sender) = sender_res.sender.take() {
+            midi_io.0.set_input_observer(sender);
+        }
     }
 
     #[cfg(not(feature = "midi-hardware"))]
     {
-        let _ = sender;
+        sender_res.sender = None;
     }
 }
 
@@ -194,13 +199,56 @@ pub fn midi_device_poll_system(
 // =========================================================================
 
 #[cfg(feature = "midi")]
-use super::components::MidiSequence;
+use super::components::{MidiSequence, MidiSequenceMessage};
 
-/// Tracks which notes are currently sounding for a [`MidiSequence`].
+/// Shortest lookahead sequence playback schedules, so the first frame
+/// (no measured frame time yet) and unusually fast frames still queue
+/// events ahead of the playhead.
+#[cfg(feature = "midi")]
+const MIN_SEQUENCE_LOOKAHEAD_SECS: f64 = 1.0 / 60.0;
+
+/// How far (in beats) a sequence's schedule may fall behind the playhead
+/// — a long frame — before it counts as a seek rather than lateness.
+#[cfg(feature = "midi")]
+const SEQUENCE_LATE_TOLERANCE_BEATS: f64 = 1.0;
+
+/// Scheduling state for a [`MidiSequence`].
+///
+/// Playback queues events one frame ahead, so "sounding" here means
+/// "note-on queued, note-off not yet queued".
 #[cfg(feature = "midi")]
 #[derive(Component, Default)]
 pub struct MidiSequenceState {
-    active_notes: std::collections::HashSet<u8>,
+    /// Queued-but-not-released count per `(channel, pitch)`. Overlapping
+    /// notes of the same pitch each hold a count.
+    active_notes: std::collections::HashMap<(u8, u8), u32>,
+    /// Transport beat up to which events have been queued, and how far
+    /// ahead of the playhead it was at the time.
+    horizon: Option<(f64, f64)>,
+}
+
+#[cfg(feature = "midi")]
+impl MidiSequenceState {
+    /// `(channel, pitch)` pairs with a queued note-on and no queued
+    /// note-off yet.
+    pub fn active_notes(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        self.active_notes.keys().copied()
+    }
+
+    /// Queue a note-off for every sounding pitch at `offset` and forget
+    /// the schedule.
+    fn release_all(
+        &mut self,
+        midi: &crate::MidiBusRes,
+        unit_id: tutti::core::MidiUnitId,
+        offset: usize,
+    ) {
+        for ((channel, note), _) in self.active_notes.drain() {
+            midi.0
+                .queue(unit_id, &[note_off_event(channel, note, offset)]);
+        }
+        self.horizon = None;
+    }
 }
 
 /// Auto-inserts [`MidiSequenceState`] on entities that have [`MidiSequence`]
@@ -215,14 +263,153 @@ pub fn midi_sequence_setup_system(
     }
 }
 
-/// Ticks all [`MidiSequence`] entities, firing note_on/note_off based on
-/// the transport's current beat position.
+/// A sequence event at a transport beat.
+#[cfg(feature = "midi")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SequenceEvent {
+    beat: f64,
+    channel: u8,
+    kind: SequenceEventKind,
+}
+
+/// Variant order is dispatch order for events at the same instant.
+#[cfg(feature = "midi")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SequenceEventKind {
+    NoteOff { note: u8 },
+    Message(MidiSequenceMessage),
+    NoteOn { note: u8, velocity: u8 },
+}
+
+#[cfg(feature = "midi")]
+impl SequenceEventKind {
+    fn order(&self) -> u8 {
+        match self {
+            Self::NoteOff { .. } => 0,
+            Self::Message(_) => 1,
+            Self::NoteOn { .. } => 2,
+        }
+    }
+}
+
+/// Collects the events of `seq` that fall in transport beats
+/// `[t0, t1)`, which must not span a transport loop wrap.
+///
+/// Looped sequences repeat every `duration_beats` from `start_beat`; a
+/// note running past the sequence end releases at its own end time in
+/// the next pass. Non-looped sequences play once; notes starting after
+/// `duration_beats` never sound.
+#[cfg(feature = "midi")]
+fn collect_sequence_events(seq: &MidiSequence, t0: f64, t1: f64, out: &mut Vec<SequenceEvent>) {
+    let (l0, l1) = (t0 - seq.start_beat, t1 - seq.start_beat);
+    if l1 <= 0.0 || l1 <= l0 {
+        return;
+    }
+    let looped = seq.loop_enabled && seq.duration_beats > 0.0;
+
+    let mut push_pass = |pass_start: f64, u0: f64, u1: f64, first_pass: bool| {
+        let d = seq.duration_beats;
+        for n in &seq.notes {
+            let on = SequenceEventKind::NoteOn {
+                note: n.note,
+                velocity: n.velocity,
+            };
+            let off = SequenceEventKind::NoteOff { note: n.note };
+            let mut push = |at: f64, kind| {
+                out.push(SequenceEvent {
+                    beat: seq.start_beat + pass_start + at,
+                    channel: n.channel,
+                    kind,
+                })
+            };
+            if n.start >= u0 && n.start < u1 && n.start < d {
+                push(n.start, on);
+            }
+            let end = n.start + n.duration;
+            if end >= u0 && end < u1 && (!looped || end <= d) {
+                push(end, off);
+            }
+            // Tail of a note from the previous pass that ran past the end.
+            if looped && !first_pass && end > d && end - d >= u0 && end - d < u1 {
+                push(end - d, off);
+            }
+        }
+        for e in &seq.events {
+            if e.beat >= u0 && e.beat < u1 && e.beat < d {
+                out.push(SequenceEvent {
+                    beat: seq.start_beat + pass_start + e.beat,
+                    channel: e.channel,
+                    kind: SequenceEventKind::Message(e.message),
+                });
+            }
+        }
+    };
+
+    if looped {
+        let d = seq.duration_beats;
+        let l0 = l0.max(0.0);
+        let mut pass = (l0 / d).floor();
+        while pass * d < l1 {
+            let pass_start = pass * d;
+            let u0 = (l0 - pass_start).max(0.0);
+            let u1 = (l1 - pass_start).min(d);
+            if u1 > u0 {
+                push_pass(pass_start, u0, u1, pass >= 1.0);
+            }
+            pass += 1.0;
+        }
+    } else {
+        push_pass(0.0, l0.max(0.0), l1, true);
+    }
+}
+
+/// Splits the window's lookahead `[ahead_from, length_beats)` into
+/// wrap-free transport spans `(t0, t1, ahead_at_t0)`.
+#[cfg(feature = "midi")]
+fn transport_spans(window: &crate::BeatWindow, ahead_from: f64) -> Vec<(f64, f64, f64)> {
+    let (from, to) = (window.beat + ahead_from, window.beat + window.length_beats);
+    if to <= from {
+        return Vec::new();
+    }
+    match window.loop_range {
+        Some((start, end)) if window.beat < end && to > end => {
+            let mut spans = Vec::with_capacity(2);
+            if from < end {
+                spans.push((from, end, ahead_from));
+            }
+            let wrapped_from = start + (from - end).max(0.0);
+            let wrapped_ahead = ahead_from.max(end - window.beat);
+            spans.push((wrapped_from, start + (to - end), wrapped_ahead));
+            spans
+        }
+        _ => vec![(from, to, ahead_from)],
+    }
+}
+
+/// Plays every [`MidiSequence`] against the transport, one frame ahead.
+///
+/// Each frame queues the note-ons / note-offs due before the next frame
+/// (a [`BeatWindow`](crate::BeatWindow) as long as the last frame) with
+/// their sample offset from the next block, continuing from where the
+/// previous frame stopped so short notes aren't skipped. Note-offs sort
+/// before note-ons at the same instant, so back-to-back notes of one
+/// pitch retrigger. Transport loops and looped sequences wrap inside
+/// the window.
+///
+/// Stopping the transport, or a seek, releases every sounding note.
 #[cfg(feature = "midi")]
 pub fn midi_sequence_tick_system(
     transport: Option<Res<crate::TransportRes>>,
     midi: Option<Res<crate::MidiBusRes>>,
+    config: Option<Res<crate::AudioConfig>>,
+    tempo_map: Option<Res<crate::TempoMap>>,
+    mut last_tick: Local<Option<std::time::Instant>>,
     mut query: Query<(&MidiSequence, &mut MidiSequenceState)>,
 ) {
+    let now = std::time::Instant::now();
+    let dt = last_tick.map_or(0.0, |last| now.duration_since(last).as_secs_f64());
+    *last_tick = Some(now);
+
     let Some(transport) = transport else { return };
     let Some(midi) = midi else { return };
 
@@ -230,72 +417,224 @@ pub fn midi_sequence_tick_system(
         // All-notes-off when transport is not rolling
         for (seq, mut state) in query.iter_mut() {
             let unit_id = tutti::core::MidiUnitId::new(seq.target.value());
-            for note in state.active_notes.drain() {
-                let event = note_off_event(note);
-                midi.0.queue(unit_id, &[event]);
-            }
+            state.release_all(&midi, unit_id, 0);
         }
         return;
     }
 
-    let beat = transport.0.current_beat();
+    let sample_rate = config.map_or(tutti::dsp::DEFAULT_SR, |c| c.sample_rate);
+    let window = crate::BeatWindow::from_transport(
+        &transport.0,
+        sample_rate,
+        dt.max(MIN_SEQUENCE_LOOKAHEAD_SECS),
+    );
+    let window = match &tempo_map {
+        Some(map) => window.with_tempo_map(map),
+        None => window,
+    };
+    let mut events = Vec::new();
 
     for (seq, mut state) in query.iter_mut() {
         let unit_id = tutti::core::MidiUnitId::new(seq.target.value());
-        let local_beat = if seq.loop_enabled && seq.duration_beats > 0.0 {
-            let offset = beat - seq.start_beat;
-            ((offset % seq.duration_beats) + seq.duration_beats) % seq.duration_beats
-        } else {
-            beat - seq.start_beat
-        };
-
-        // Outside range (non-looped)
-        if !seq.loop_enabled && (local_beat < 0.0 || local_beat > seq.duration_beats) {
-            for note in state.active_notes.drain() {
-                let event = note_off_event(note);
-                midi.0.queue(unit_id, &[event]);
-            }
-            continue;
-        }
 
-        // Determine which notes should be active at this beat
-        let mut should_be_active = std::collections::HashSet::new();
-        for n in &seq.notes {
-            if local_beat >= n.start && local_beat < n.start + n.duration {
-                should_be_active.insert(n.note);
-            }
-        }
+        let ahead_from = match state.horizon {
+            None => 0.0,
+            Some((horizon, was_ahead)) => match window.beats_until(horizon) {
+                Some(ahead) if ahead <= was_ahead + 1e-9 => ahead,
+                _ if window.beat >= horizon
+                    && window.beat - horizon <= SEQUENCE_LATE_TOLERANCE_BEATS =>
+                {
+                    // Long frame: catch up, late events go out at offset 0.
+                    horizon - window.beat
+                }
+                _ => {
+                    // Seek or loop jump: drop the old schedule.
+                    state.release_all(&midi, unit_id, 0);
+                    0.0
+                }
+            },
+        };
 
-        // Note-off for notes that ended
-        for &note in &state.active_notes {
-            if !should_be_active.contains(&note) {
-                let event = note_off_event(note);
-                midi.0.queue(unit_id, &[event]);
+        events.clear();
+        for (t0, t1, base) in transport_spans(&window, ahead_from) {
+            let first = events.len();
+            collect_sequence_events(seq, t0, t1, &mut events);
+            for event in &mut events[first..] {
+                // Re-express as "beats ahead of the playhead".
+                event.beat = base + (event.beat - t0);
             }
         }
-
-        // Note-on for newly active notes
-        for n in &seq.notes {
-            if should_be_active.contains(&n.note) && !state.active_notes.contains(&n.note) {
-                let event = note_on_event(n.note, n.velocity);
-                midi.0.queue(unit_id, &[event]);
+        events.sort_by(|a, b| {
+            a.beat
+                .total_cmp(&b.beat)
+                .then(a.kind.order().cmp(&b.kind.order()))
+        });
+
+        for event in &events {
+            let offset = window.samples_ahead(event.beat);
+            let channel = event.channel;
+            match event.kind {
+                SequenceEventKind::NoteOn { note, velocity } => {
+                    *state.active_notes.entry((channel, note)).or_default() += 1;
+                    midi.0
+                        .queue(unit_id, &[note_on_event(channel, note, velocity, offset)]);
+                }
+                SequenceEventKind::NoteOff { note } => {
+                    let Some(count) = state.active_notes.get_mut(&(channel, note)) else {
+                        continue;
+                    };
+                    *count -= 1;
+                    if *count == 0 {
+                        state.active_notes.remove(&(channel, note));
+                    }
+                    midi.0
+                        .queue(unit_id, &[note_off_event(channel, note, offset)]);
+                }
+                SequenceEventKind::Message(message) => {
+                    midi.0
+                        .queue(unit_id, &[message_event(channel, message, offset)]);
+                }
             }
         }
 
-        state.active_notes = should_be_active;
+        state.horizon = Some((window.beat_at(window.length_beats), window.length_beats));
     }
 }
 
-/// Channel-0 MIDI 2.0 note-on event with a 7-bit MIDI 1 velocity
-/// (upconverted to the 16-bit MIDI 2 velocity range).
+/// MIDI 2.0 note-on event with a 7-bit MIDI 1 velocity (upconverted to
+/// the 16-bit MIDI 2 velocity range), `offset` samples into the next
+/// block.
+#[cfg(feature = "midi")]
+fn note_on_event(
+    channel: u8,
+    note: u8,
+    velocity_midi1: u8,
+    offset: usize,
+) -> tutti::midi::MidiEvent {
+    tutti::midi::MidiEvent::note_on(offset, channel, note, (velocity_midi1 as u16) << 9)
+}
+
 #[cfg(feature = "midi")]
-fn note_on_event(note: u8, velocity_midi1: u8) -> tutti::midi::MidiEvent {
-    tutti::midi::MidiEvent::note_on(0, 0, note, (velocity_midi1 as u16) << 9)
+fn note_off_event(channel: u8, note: u8, offset: usize) -> tutti::midi::MidiEvent {
+    tutti::midi::MidiEvent::note_off(offset, channel, note, 0)
 }
 
+/// MIDI 2.0 channel-voice event for a sequence message. 7-bit values are
+/// upconverted to 32 bits and pitch bend from 14 to 32, by shifting, as
+/// for velocities.
 #[cfg(feature = "midi")]
-fn note_off_event(note: u8) -> tutti::midi::MidiEvent {
-    tutti::midi::MidiEvent::note_off(0, 0, note, 0)
+fn message_event(
+    channel: u8,
+    message: MidiSequenceMessage,
+    offset: usize,
+) -> tutti::midi::MidiEvent {
+    use tutti::midi::MidiEvent;
+    let u7 = |v: u8| ((v & 0x7f) as u32) << 25;
+    match message {
+        MidiSequenceMessage::ControlChange { controller, value } => {
+            MidiEvent::control_change(offset, channel, controller, u7(value))
+        }
+        MidiSequenceMessage::PitchBend(bend) => {
+            let raw = (bend.clamp(-8192, 8191) as i32 + 8192) as u32;
+            MidiEvent::pitch_bend(offset, channel, raw << 18)
+        }
+        MidiSequenceMessage::ChannelPressure(pressure) => {
+            MidiEvent::channel_pressure(offset, channel, u7(pressure))
+        }
+        MidiSequenceMessage::PolyPressure { note, pressure } => {
+            MidiEvent::poly_pressure(offset, channel, note, u7(pressure))
+        }
+        MidiSequenceMessage::ProgramChange(program) => {
+            MidiEvent::program_change(offset, channel, program)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::components::{MidiSequenceEvent, MidiSequenceNote};
+
+    fn seq(notes: &[(u8, f64, f64)], loop_enabled: bool) -> MidiSequence {
+        MidiSequence {
+            target: tutti::NodeId::new(),
+            notes: notes
+                .iter()
+                .map(|&(note, start, duration)| MidiSequenceNote::new(note, 100, start, duration))
+                .collect(),
+            events: Vec::new(),
+            start_beat: 0.0,
+            duration_beats: 4.0,
+            loop_enabled,
+        }
+    }
+
+    fn collect(seq: &MidiSequence, t0: f64, t1: f64) -> Vec<(f64, u8, bool)> {
+        let mut out = Vec::new();
+        collect_sequence_events(seq, t0, t1, &mut out);
+        out.iter()
+            .filter_map(|e| match e.kind {
+                SequenceEventKind::NoteOn { note, .. } => Some((e.beat, note, true)),
+                SequenceEventKind::NoteOff { note } => Some((e.beat, note, false)),
+                SequenceEventKind::Message(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn short_note_inside_one_window_yields_on_and_off() {
+        let s = seq(&[(60, 1.0, 0.01)], false);
+        let events = collect(&s, 0.9, 1.1);
+        assert_eq!(events.len(), 2);
+        assert!(events.contains(&(1.0, 60, true)));
+    }
+
+    #[test]
+    fn looped_sequence_wraps_and_releases_overhanging_tail() {
+        // Note runs from 3.5 to 4.5: its off lands 0.5 into the next pass.
+        let s = seq(&[(64, 3.5, 1.0)], true);
+        let events = collect(&s, 3.0, 4.75);
+        assert!(events.contains(&(3.5, 64, true)));
+        assert!(events.contains(&(4.5, 64, false)));
+        // The first pass has no tail to release.
+        assert!(collect(&s, 0.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn messages_keep_channel_and_sort_between_off_and_on() {
+        let mut s = seq(&[(60, 0.0, 1.0), (62, 1.0, 1.0)], false);
+        s.notes[1].channel = 9;
+        s.events.push(MidiSequenceEvent::new(
+            1.0,
+            9,
+            MidiSequenceMessage::ProgramChange(5),
+        ));
+        let mut out = Vec::new();
+        collect_sequence_events(&s, 0.5, 1.5, &mut out);
+        out.sort_by(|a, b| {
+            a.beat
+                .total_cmp(&b.beat)
+                .then(a.kind.order().cmp(&b.kind.order()))
+        });
+        let kinds: Vec<(u8, u8)> = out.iter().map(|e| (e.channel, e.kind.order())).collect();
+        assert_eq!(kinds, vec![(0, 0), (9, 1), (9, 2)]);
+    }
+
+    #[test]
+    fn window_spans_split_at_transport_loop_end() {
+        let window = crate::BeatWindow {
+            beat: 3.9,
+            length_beats: 0.2,
+            tempo: 120.0,
+            sample_rate: 48_000.0,
+            loop_range: Some((0.0, 4.0)),
+        };
+        let spans = transport_spans(&window, 0.0);
+        assert_eq!(spans.len(), 2);
+        assert!((spans[0].1 - 4.0).abs() < 1e-9);
+        assert_eq!(spans[1].0, 0.0);
+        assert!((spans[1].2 - 0.1).abs() < 1e-9);
+    }
 }
 
 /// Live per-note MPE expression state, wrapping an
@@ -0,0 +1,276 @@
+//! Headless engine mode: a manually-clocked driver with no audio device.
+//!
+//! [`TuttiPlugin::headless`](crate::TuttiPlugin::headless) builds the
+//! engine without opening a CPAL stream. Nothing pulls audio through the
+//! graph on its own, so [`TuttiOfflineClock`] takes the place of the
+//! device callback: [`offline_clock_system`] renders `blocks_per_update`
+//! blocks each frame (plus any blocks requested on demand) and leaves the
+//! interleaved output in a buffer that tests and servers can read back.
+//!
+//! The clock runs after [`GraphReconcileSystems::Commit`], so the blocks
+//! rendered on a given frame already reflect that frame's component edits.
+
+use bevy_ecs::prelude::*;
+
+use crate::resources::TuttiDriverRes;
+
+/// Manual clock for a headless engine.
+///
+/// Inserted by `TuttiPlugin` in headless mode. Each `Update`,
+/// [`offline_clock_system`] renders `blocks_per_update` blocks of
+/// `block_size` frames plus any blocks queued via
+/// [`TuttiOfflineClock::request_blocks`]. The interleaved output of that
+/// frame's render replaces the previous contents of [`Self::buffer`].
+///
+/// Set `blocks_per_update` to `0` to drive rendering purely on demand.
+#[derive(Resource, Debug, Clone)]
+pub struct TuttiOfflineClock {
+    pub sample_rate: f64,
+    pub block_size: usize,
+    pub channels: usize,
+    pub blocks_per_update: usize,
+    pending_blocks: usize,
+    frames_rendered: u64,
+    buffer: Vec<f32>,
+}
+
+impl TuttiOfflineClock {
+    pub fn new(sample_rate: f64, block_size: usize, channels: usize) -> Self {
+        Self {
+            sample_rate,
+            block_size,
+            channels,
+            blocks_per_update: 1,
+            pending_blocks: 0,
+            frames_rendered: 0,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn with_blocks_per_update(mut self, blocks: usize) -> Self {
+        self.blocks_per_update = blocks;
+        self
+    }
+
+    /// Queue `blocks` extra blocks for the next `Update`, on top of
+    /// `blocks_per_update`.
+    pub fn request_blocks(&mut self, blocks: usize) {
+        self.pending_blocks += blocks;
+    }
+
+    /// Interleaved output rendered during the most recent `Update`.
+    pub fn buffer(&self) -> &[f32] {
+        &self.buffer
+    }
+
+    /// De-interleaved view of one output channel from [`Self::buffer`].
+    pub fn channel(&self, channel: usize) -> impl Iterator<Item = f32> + '_ {
+        self.buffer
+            .iter()
+            .skip(channel)
+            .step_by(self.channels.max(1))
+            .copied()
+    }
+
+    /// Peak absolute sample in [`Self::buffer`] across all channels.
+    pub fn peak(&self) -> f32 {
+        self.buffer.iter().fold(0.0f32, |acc, s| acc.max(s.abs()))
+    }
+
+    /// Total frames rendered since the engine was built.
+    pub fn frames_rendered(&self) -> u64 {
+        self.frames_rendered
+    }
+
+    /// Engine time in seconds, derived from [`Self::frames_rendered`].
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.frames_rendered as f64 / self.sample_rate
+    }
+}
+
+/// Renders the blocks scheduled on [`TuttiOfflineClock`] through the
+/// headless driver.
+///
+/// Pinned to the main thread by `NonSendMut<TuttiDriverRes>` (the driver
+/// is non-send; see [`TuttiDriverRes`]). A no-op when either the driver
+/// or the clock is missing, or when nothing is scheduled this frame.
+pub fn offline_clock_system(
+    driver: Option<NonSendMut<TuttiDriverRes>>,
+    clock: Option<ResMut<TuttiOfflineClock>>,
+) {
+    let Some(mut driver) = driver else { return };
+    let Some(mut clock) = clock else { return };
+
+    let blocks = clock.blocks_per_update + std::mem::take(&mut clock.pending_blocks);
+    if blocks == 0 {
+        return;
+    }
+
+    let block_len = clock.block_size * clock.channels;
+    let mut buffer = std::mem::take(&mut clock.buffer);
+    buffer.clear();
+    buffer.resize(blocks * block_len, 0.0);
+
+    for block in buffer.chunks_exact_mut(block_len) {
+        driver.0.render_block(block);
+    }
+
+    clock.frames_rendered += (blocks * clock.block_size) as u64;
+    clock.buffer = buffer;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::reconcile::{
+        commit_graph, GraphDirty, GraphReconcileSystems, SpawnAudioNode,
+    };
+    use bevy_app::App;
+    use tutti::core::ecs::{AudioNode, NodeKind};
+    use tutti::dsp::sine_hz;
+    use tutti::TuttiEngine;
+
+    const BLOCK: usize = 64;
+
+    fn test_app() -> App {
+        let engine = TuttiEngine::builder()
+            .inputs(0)
+            .outputs(2)
+            .sample_rate(48_000.0)
+            .block_size(BLOCK)
+            .headless()
+            .build()
+            .expect("build headless engine");
+        let channels = engine.channels;
+        let TuttiEngine { graph, driver, .. } = engine;
+
+        let mut app = App::new();
+        app.insert_resource(crate::resources::TuttiGraphRes(graph));
+        app.insert_non_send_resource(TuttiDriverRes::new(driver));
+        app.insert_resource(TuttiOfflineClock::new(48_000.0, BLOCK, channels));
+        app.init_resource::<GraphDirty>();
+        app.configure_sets(
+            bevy_app::Update,
+            (
+                GraphReconcileSystems::Spawn,
+                GraphReconcileSystems::Params,
+                GraphReconcileSystems::Despawn,
+                GraphReconcileSystems::Commit,
+            )
+                .chain(),
+        );
+        app.add_systems(
+            bevy_app::Update,
+            (
+                commit_graph.in_set(GraphReconcileSystems::Commit),
+                offline_clock_system.after(GraphReconcileSystems::Commit),
+            ),
+        );
+        app
+    }
+
+    #[test]
+    fn renders_blocks_per_update() {
+        let mut app = test_app();
+        app.world_mut()
+            .resource_mut::<TuttiOfflineClock>()
+            .blocks_per_update = 3;
+        app.update();
+
+        let clock = app.world().resource::<TuttiOfflineClock>();
+        assert_eq!(clock.buffer().len(), 3 * BLOCK * clock.channels);
+        assert_eq!(clock.frames_rendered(), 3 * BLOCK as u64);
+    }
+
+    #[test]
+    fn on_demand_blocks_only_render_once() {
+        let mut app = test_app();
+        {
+            let mut clock = app.world_mut().resource_mut::<TuttiOfflineClock>();
+            clock.blocks_per_update = 0;
+            clock.request_blocks(2);
+        }
+        app.update();
+        assert_eq!(
+            app.world()
+                .resource::<TuttiOfflineClock>()
+                .frames_rendered(),
+            2 * BLOCK as u64
+        );
+
+        app.update();
+        assert_eq!(
+            app.world()
+                .resource::<TuttiOfflineClock>()
+                .frames_rendered(),
+            2 * BLOCK as u64
+        );
+    }
+
+    #[test]
+    fn spawned_generator_is_audible_in_buffer() {
+        let mut app = test_app();
+        let entity = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
+            .id();
+        app.update();
+
+        let node = app.world().get::<AudioNode>(entity).expect("AudioNode").0;
+        {
+            let mut graph = app
+                .world_mut()
+                .resource_mut::<crate::resources::TuttiGraphRes>();
+            graph.0.pipe_output(node);
+            graph.0.commit();
+        }
+        app.world_mut()
+            .resource_mut::<TuttiOfflineClock>()
+            .blocks_per_update = 8;
+        app.update();
+
+        assert!(app.world().resource::<TuttiOfflineClock>().peak() > 0.0);
+    }
+
+    #[test]
+    fn mute_silences_a_piped_generator_through_its_strip() {
+        use crate::graph::strip::{ensure_channel_strips, reconcile_strip_levels};
+        use tutti::core::ecs::Mute;
+
+        let mut app = test_app();
+        app.add_systems(
+            bevy_app::Update,
+            (
+                ensure_channel_strips.in_set(GraphReconcileSystems::Spawn),
+                reconcile_strip_levels.in_set(GraphReconcileSystems::Params),
+            ),
+        );
+        let entity = app
+            .world_mut()
+            .commands()
+            .spawn_audio_node(sine_hz::<f32>(440.0), NodeKind::Generator)
+            .id();
+        app.update();
+
+        let node = app.world().get::<AudioNode>(entity).expect("AudioNode").0;
+        {
+            let mut graph = app
+                .world_mut()
+                .resource_mut::<crate::resources::TuttiGraphRes>();
+            graph.0.pipe_output(node);
+            graph.0.commit();
+        }
+        // ~64 ms per update: the strip's 5 ms mute ramp settles within one.
+        app.world_mut()
+            .resource_mut::<TuttiOfflineClock>()
+            .blocks_per_update = 48;
+        app.update();
+        assert!(app.world().resource::<TuttiOfflineClock>().peak() > 0.0);
+
+        app.world_mut().entity_mut(entity).insert(Mute(true));
+        app.update();
+        app.update();
+        assert!(app.world().resource::<TuttiOfflineClock>().peak() < 1e-3);
+    }
+}
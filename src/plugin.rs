@@ -5,16 +5,22 @@
 //! it into per-subsystem resources, configures the engine-wide system state,
 //! and adds the sub-plugins for the currently enabled features.
 
-use bevy_app::{App, Plugin, Startup, Update};
+use bevy_app::{App, Plugin, PreUpdate, Startup, Update};
+use bevy_ecs::prelude::*;
 use bevy_log::{error, info};
 
 use tutti::TuttiEngine;
 
+use crate::beat_trigger;
 use crate::device_state;
-use crate::graph::TuttiGraphPlugin;
+use crate::engine_status::{self, AudioEngineStatus, RetryAudioEngine};
+use crate::graph::{GraphReconcileSystems, ParamSmoothing, TuttiGraphPlugin};
 use crate::metering;
+use crate::metronome;
+use crate::offline::{offline_clock_system, TuttiOfflineClock};
 use crate::playback::TuttiPlaybackPlugin;
 use crate::resources::*;
+use crate::tempo_map;
 use crate::transport;
 
 #[cfg(feature = "midi")]
@@ -40,7 +46,9 @@ use crate::export::TuttiExportPlugin;
 #[cfg(feature = "plugin")]
 use crate::plugin_host::TuttiHostingPlugin;
 use crate::dsp::TuttiDspPlugin;
-use crate::prelude::{AudioDeviceState, MasterMeterLevels, TransportState};
+use crate::prelude::{
+    AudioDeviceState, MasterLoudness, MasterMeterLevels, TempoMap, TransportState,
+};
 #[cfg(feature = "sampler")]
 use crate::prelude::ContentBounds;
 
@@ -52,10 +60,21 @@ pub struct TuttiPlugin {
     pub inputs: usize,
     pub outputs: usize,
     pub enable_midi: bool,
+    /// `Some` = build without an audio device; see [`TuttiPlugin::headless`].
+    pub headless: Option<HeadlessConfig>,
+    /// Seeds [`DefaultParamSmoothing`](crate::graph::DefaultParamSmoothing).
+    pub default_smoothing: ParamSmoothing,
     #[cfg(feature = "mpe")]
     pub mpe_mode: Option<tutti::midi::MpeMode>,
 }
 
+/// Sample rate and block size for a headless engine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeadlessConfig {
+    pub sample_rate: f64,
+    pub block_size: usize,
+}
+
 impl Default for TuttiPlugin {
     fn default() -> Self {
         Self {
@@ -63,6 +82,8 @@ impl Default for TuttiPlugin {
             inputs: 0,
             outputs: 2,
             enable_midi: cfg!(feature = "midi"),
+            headless: None,
+            default_smoothing: ParamSmoothing::OFF,
             #[cfg(feature = "mpe")]
             mpe_mode: None,
         }
@@ -78,11 +99,33 @@ impl TuttiPlugin {
         }
     }
 
+    /// Build the engine without opening an audio device.
+    ///
+    /// The graph, transport, metering and sampler are created as usual,
+    /// but nothing is clocked by hardware: a [`TuttiOfflineClock`]
+    /// resource is inserted and renders blocks of `block_size` frames
+    /// every `Update`. Intended for CI, dedicated servers and
+    /// deterministic integration tests.
+    pub fn headless(sample_rate: f64, block_size: usize) -> Self {
+        Self {
+            headless: Some(HeadlessConfig {
+                sample_rate,
+                block_size,
+            }),
+            ..Default::default()
+        }
+    }
+
     pub fn with_midi(mut self) -> Self {
         self.enable_midi = true;
         self
     }
 
+    pub fn with_default_smoothing(mut self, smoothing: ParamSmoothing) -> Self {
+        self.default_smoothing = smoothing;
+        self
+    }
+
     pub fn with_output_device(mut self, index: usize) -> Self {
         self.output_device = Some(index);
         self
@@ -97,15 +140,48 @@ impl TuttiPlugin {
     }
 }
 
-impl Plugin for TuttiPlugin {
-    fn build(&self, app: &mut App) {
-        info!("Initializing Tutti Audio Plugin");
+/// Engine build parameters captured from [`TuttiPlugin`].
+///
+/// Kept as a resource so [`crate::engine_status::audio_engine_restart_system`]
+/// can rebuild the engine with the same settings after a failed start.
+#[derive(Resource, Debug, Clone)]
+pub(crate) struct EngineBuildSettings {
+    pub(crate) output_device: Option<usize>,
+    pub(crate) inputs: usize,
+    pub(crate) outputs: usize,
+    pub(crate) enable_midi: bool,
+    pub(crate) headless: Option<HeadlessConfig>,
+    #[cfg(feature = "mpe")]
+    pub(crate) mpe_mode: Option<tutti::midi::MpeMode>,
+}
 
+impl From<&TuttiPlugin> for EngineBuildSettings {
+    fn from(plugin: &TuttiPlugin) -> Self {
+        Self {
+            output_device: plugin.output_device,
+            inputs: plugin.inputs,
+            outputs: plugin.outputs,
+            enable_midi: plugin.enable_midi,
+            headless: plugin.headless,
+            #[cfg(feature = "mpe")]
+            mpe_mode: plugin.mpe_mode,
+        }
+    }
+}
+
+impl EngineBuildSettings {
+    /// Build (and, unless headless, start) a `TuttiEngine`.
+    pub(crate) fn build(&self) -> Result<TuttiEngine, String> {
         let mut builder = TuttiEngine::builder()
             .inputs(self.inputs)
             .outputs(self.outputs);
 
-        if let Some(device) = self.output_device {
+        if let Some(headless) = self.headless {
+            builder = builder
+                .sample_rate(headless.sample_rate)
+                .block_size(headless.block_size)
+                .headless();
+        } else if let Some(device) = self.output_device {
             builder = builder.output_device(device);
         }
 
@@ -119,89 +195,166 @@ impl Plugin for TuttiPlugin {
             builder = builder.mpe(*mode);
         }
 
-        match builder.build() {
+        builder.build().map_err(|e| e.to_string())
+    }
+}
+
+/// Destructure a freshly built engine into the per-subsystem resources.
+///
+/// Shared by `TuttiPlugin::build` and the retry path, so resources land
+/// the same way whether the engine came up at startup or later.
+pub(crate) fn insert_engine_resources(
+    world: &mut World,
+    engine: TuttiEngine,
+    settings: &EngineBuildSettings,
+) {
+    info!(
+        "Tutti Audio Engine started ({}Hz, {}ch{})",
+        engine.sample_rate,
+        settings.outputs,
+        if settings.headless.is_some() {
+            ", headless"
+        } else {
+            ""
+        }
+    );
+
+    // Enable amplitude, loudness + CPU metering by default (used by the
+    // metering_sync_system and loudness_sync_system).
+    engine.metering.inner().enable_amp();
+    engine.metering.inner().enable_lufs();
+    engine.metering.inner().cpu().enable();
+
+    let sample_rate = engine.sample_rate;
+    let channels = engine.channels;
+
+    world.insert_resource(AudioConfig {
+        sample_rate,
+        channels,
+        block_size: settings.headless.map(|headless| headless.block_size),
+    });
+
+    if let Some(headless) = settings.headless {
+        world.insert_resource(TuttiOfflineClock::new(
+            sample_rate,
+            headless.block_size,
+            channels,
+        ));
+    }
+
+    let TuttiEngine {
+        graph,
+        driver,
+        transport,
+        metering,
+        #[cfg(feature = "midi")]
+        midi,
+        #[cfg(feature = "midi")]
+        midi_io,
+        #[cfg(feature = "sampler")]
+        sampler,
+        #[cfg(feature = "soundfont")]
+        soundfont,
+        #[cfg(feature = "analysis")]
+        analysis,
+        ..
+    } = engine;
+
+    world.insert_resource(TuttiGraphRes(graph));
+    world.insert_non_send_resource(TuttiDriverRes::new(driver));
+    world.insert_resource(TransportRes(transport));
+    world.insert_resource(MeteringRes(metering));
+
+    #[cfg(feature = "midi")]
+    world.insert_resource(MidiBusRes(midi));
+    #[cfg(feature = "midi-hardware")]
+    if let Some(io) = midi_io {
+        world.insert_resource(MidiIoRes(io));
+    }
+    #[cfg(all(feature = "midi", not(feature = "midi-hardware")))]
+    {
+        let _ = midi_io;
+    }
+
+    #[cfg(feature = "sampler")]
+    world.insert_resource(SamplerRes(sampler));
+
+    #[cfg(feature = "soundfont")]
+    world.insert_resource(SoundFontRes(soundfont));
+
+    #[cfg(feature = "analysis")]
+    world.insert_resource(AnalysisRes(analysis));
+}
+
+impl Plugin for TuttiPlugin {
+    fn build(&self, app: &mut App) {
+        info!("Initializing Tutti Audio Plugin");
+
+        let settings = EngineBuildSettings::from(self);
+
+        match settings.build() {
             Ok(engine) => {
-                info!(
-                    "Tutti Audio Engine started ({}Hz, {}ch)",
-                    engine.sample_rate, self.outputs
-                );
-
-                // Enable amplitude + CPU metering by default (used by the
-                // metering_sync_system).
-                engine.metering.inner().enable_amp();
-                engine.metering.inner().cpu().enable();
-
-                let sample_rate = engine.sample_rate;
-                let channels = engine.channels;
-
-                app.insert_resource(AudioConfig {
-                    sample_rate,
-                    channels,
-                });
-
-                let TuttiEngine {
-                    graph,
-                    driver,
-                    transport,
-                    metering,
-                    #[cfg(feature = "midi")]
-                    midi,
-                    #[cfg(feature = "midi")]
-                    midi_io,
-                    #[cfg(feature = "sampler")]
-                    sampler,
-                    #[cfg(feature = "soundfont")]
-                    soundfont,
-                    #[cfg(feature = "analysis")]
-                    analysis,
-                    ..
-                } = engine;
-
-                app.insert_resource(TuttiGraphRes(graph));
-                app.insert_non_send_resource(TuttiDriverRes::new(driver));
-                app.insert_resource(TransportRes(transport));
-                app.insert_resource(MeteringRes(metering));
-
-                #[cfg(feature = "midi")]
-                app.insert_resource(MidiBusRes(midi));
-                #[cfg(feature = "midi-hardware")]
-                if let Some(io) = midi_io {
-                    app.insert_resource(MidiIoRes(io));
-                }
-                #[cfg(all(feature = "midi", not(feature = "midi-hardware")))]
-                {
-                    let _ = midi_io;
-                }
-
-                #[cfg(feature = "sampler")]
-                app.insert_resource(SamplerRes(sampler));
-
-                #[cfg(feature = "soundfont")]
-                app.insert_resource(SoundFontRes(soundfont));
-
-                #[cfg(feature = "analysis")]
-                app.insert_resource(AnalysisRes(analysis));
+                insert_engine_resources(app.world_mut(), engine, &settings);
+                app.insert_resource(AudioEngineStatus::Running);
             }
-            Err(e) => {
-                error!("Failed to start Tutti Audio Engine: {}", e);
+            Err(error) => {
+                error!("Failed to start Tutti Audio Engine: {}", error);
+                app.insert_resource(AudioEngineStatus::Failed { error });
             }
         }
+        app.insert_resource(settings);
+
+        app.register_type::<AudioEngineStatus>()
+            .register_type::<RetryAudioEngine>();
+        app.add_systems(PreUpdate, engine_status::audio_engine_restart_system);
+        app.add_systems(Update, engine_status::retry_audio_engine_system);
 
         // Engine-wide state + per-frame syncs that don't fit any one duty.
         app.init_resource::<TransportState>();
+        app.init_resource::<OutputLatency>();
         app.init_resource::<MasterMeterLevels>();
+        app.init_resource::<MasterLoudness>();
         app.init_resource::<AudioDeviceState>();
         app.register_type::<TransportState>()
+            .register_type::<TempoMap>()
+            .register_type::<OutputLatency>()
+            .register_type::<beat_trigger::BeatTrigger>()
+            .register_type::<metronome::Metronome>()
             .register_type::<MasterMeterLevels>()
+            .register_type::<MasterLoudness>()
+            .register_type::<metering::ResetLoudness>()
             .register_type::<AudioDeviceState>()
             .register_type::<crate::resources::AudioConfig>();
+        app.register_type::<device_state::SelectOutputDevice>()
+            .register_type::<device_state::OutputDeviceSelector>();
+        app.add_message::<device_state::AudioDeviceEvent>();
+        app.add_message::<transport::TransportCommand>()
+            .add_message::<transport::TransportEvent>()
+            .add_message::<beat_trigger::BeatTriggered>();
         app.add_systems(Startup, device_state::device_state_init_system);
         app.add_systems(
             Update,
             (
-                transport::transport_sync_system,
+                (
+                    transport::transport_command_system,
+                    tempo_map::tempo_map_follow_system,
+                    transport::transport_sync_system,
+                    beat_trigger::beat_trigger_system,
+                )
+                    .chain(),
+                metronome::metronome_system
+                    .after(transport::transport_sync_system)
+                    .before(GraphReconcileSystems::Commit),
+                beat_trigger::beat_anchor_system.before(GraphReconcileSystems::Commit),
                 metering::metering_sync_system,
+                (
+                    metering::reset_loudness_system,
+                    metering::loudness_sync_system,
+                )
+                    .chain(),
                 device_state::device_state_sync_system,
+                device_state::select_output_device_system,
+                device_state::device_hotplug_poll_system,
             ),
         );
 
@@ -215,7 +368,15 @@ impl Plugin for TuttiPlugin {
         // Sub-plugins. Order matters: TuttiGraphPlugin first (configures
         // GraphReconcileSystems that other plugins schedule against), then
         // duty plugins.
-        app.add_plugins(TuttiGraphPlugin);
+        app.add_plugins(TuttiGraphPlugin {
+            default_smoothing: self.default_smoothing,
+        });
+        if self.headless.is_some() {
+            app.add_systems(
+                Update,
+                offline_clock_system.after(GraphReconcileSystems::Commit),
+            );
+        }
         app.add_plugins(TuttiPlaybackPlugin);
         app.add_plugins(TuttiDspPlugin);
 
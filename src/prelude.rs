@@ -27,10 +27,22 @@ pub use crate::spatial::{
 };
 
 #[cfg(feature = "midi")]
-pub use crate::midi::components::{MidiReceiver, MidiSequence, MidiSequenceNote};
+pub use crate::midi::components::{
+    MidiReceiver, MidiSequence, MidiSequenceEvent, MidiSequenceMessage, MidiSequenceNote,
+};
 #[cfg(feature = "midi")]
 pub use crate::midi::events::MidiInputEvent;
 #[cfg(feature = "midi")]
+pub use crate::midi::recording::{
+    midi_recording_capture_system, midi_recording_start_system, midi_recording_stop_system,
+    MidiRecordMode, MidiRecordingActive, StartMidiRecording, StopMidiRecording,
+};
+#[cfg(feature = "midi")]
+pub use crate::midi::smf::{
+    MidiClipAsset, MidiClipTrack, MidiTempoChange, MidiTimeSignature, SmfError,
+    DEFAULT_TICKS_PER_BEAT,
+};
+#[cfg(feature = "midi")]
 pub use crate::midi::systems::{
     midi_input_event_system, midi_routing_sync_system, midi_sequence_setup_system,
     midi_sequence_tick_system, MidiSequenceState,
@@ -53,10 +65,36 @@ pub use tutti::midi::{MpeMode, MpeZone, MpeZoneConfig};
 #[cfg(feature = "soundfont")]
 pub use crate::soundfont::{soundfont_playback_system, PlaySoundFont, TuttiSoundFontPlugin};
 
-pub use crate::metering::{metering_sync_system, MasterMeterLevels};
-pub use crate::transport::{transport_sync_system, TransportState};
+pub use crate::beat_trigger::{
+    beat_anchor_system, beat_trigger_system, BeatAnchor, BeatTrigger, BeatTriggered, Subdivision,
+};
+pub use crate::metering::{
+    loudness_sync_system, metering_sync_system, reset_loudness_system, LoudnessTarget,
+    MasterLoudness, MasterMeterLevels, ResetLoudness,
+};
+pub use crate::metronome::{metronome_system, Metronome, MetronomeClick};
+pub use crate::offline::{offline_clock_system, TuttiOfflineClock};
+pub use crate::tempo_map::{
+    tempo_map_follow_system, BarBeatTick, MeterChange, TempoMap, TempoPoint, TempoRamp,
+};
+pub use crate::transport::{
+    transport_command_system, transport_sync_system, BeatWindow, TransportCommand, TransportEvent,
+    TransportState,
+};
 
-pub use crate::device_state::{device_state_sync_system, AudioDeviceState};
+pub use crate::device_state::{
+    device_hotplug_poll_system, device_state_sync_system, select_output_device_system,
+    AudioDeviceEvent, AudioDeviceState, OutputDeviceSelector, SelectOutputDevice,
+};
+pub use crate::engine_status::{
+    audio_engine_restart_system, retry_audio_engine_system, AudioEngineStatus, RetryAudioEngine,
+};
+
+#[cfg(feature = "diagnostics")]
+pub use crate::diagnostics::{
+    engine_diagnostics_system, node_cpu_load_system, reconcile_diagnostics_system, NodeCpuLoad,
+    TuttiDiagnosticsPlugin,
+};
 
 #[cfg(feature = "sampler")]
 pub use crate::content_bounds::{content_bounds_sync_system, ContentBounds};
@@ -77,9 +115,33 @@ pub use tutti::{
 pub use tutti::{SamplerLooping, SamplerSpeed};
 
 pub use crate::graph::{
-    commit_graph, crossfade_audio_node, reconcile_audio_routing, reconcile_node_despawn,
-    reconcile_params, reconcile_sidechain_links, AudioFedBy, AudioFeedsTo, GraphDirty,
+    sync_param_ramps, DefaultParamSmoothing, ParamRamps, ParamSmoother, ParamSmoothing,
+    ParamTarget, SmoothedParam, SmoothingCurve,
+};
+pub use crate::graph::{
+    ensure_audio_buses, reconcile_send_levels, reconcile_sends, AudioBus, BusSends, SendTo,
+    SendToNode,
+};
+pub use crate::graph::{
+    reconcile_feedback_edges, AudioRoutingError, FeedbackEdge, FeedbackSources, RoutedEdges,
+    MAX_FEEDBACK_DELAY, MIN_FEEDBACK_DELAY,
+};
+pub use crate::graph::{
+    ensure_meter_taps, reconcile_meter_tap_despawn, sync_node_meter_levels, MeterTap,
+    MeterTapNode, NodeMeterLevels,
+};
+pub use crate::graph::{
+    collect_scheduled_param_changes, dispatch_scheduled_param_changes, ScheduleParamChange,
+    ScheduledParamChange, ScheduledParamQueue,
+};
+pub use crate::graph::{
+    commit_graph, crossfade_audio_node, ensure_channel_strips, output_node, pan_gains,
+    pipe_audio_node_output, reconcile_audio_connections, reconcile_audio_routing,
+    reconcile_node_despawn, reconcile_params, reconcile_sidechain_links, reconcile_strip_despawn,
+    reconcile_strip_levels, rehydrate_audio_nodes, AudioConnection, AudioConnections, AudioFedBy,
+    AudioFeedsTo, AudioNodeRecipe, ChannelStrip, FeedPorts, FilterMode, GraphDirty,
     GraphReconcileSystems, SidechainOf, SidechainSources, SpawnAudioNode, TuttiGraphPlugin,
+    WithGainStage,
 };
 #[cfg(feature = "sampler")]
 pub use crate::graph::{
@@ -87,9 +149,9 @@ pub use crate::graph::{
     WaveImportQueue,
 };
 #[cfg(feature = "midi")]
-pub use crate::graph::{tick_scheduled_midi, MidiSynthMarker, ScheduledMidi};
+pub use crate::graph::{tick_scheduled_midi, MidiSynthMarker, MidiTiming, ScheduledMidi};
 #[cfg(feature = "plugin")]
-pub use crate::graph::reconcile_plugin_params;
+pub use crate::graph::{reconcile_plugin_params, refresh_plugin_recipe_state};
 
 #[cfg(all(feature = "plugin", feature = "vst2"))]
 pub use crate::vst2_load::{process_pending_vst2_builds, PendingVst2Build};
@@ -126,8 +188,8 @@ pub use tutti::plugin::metadata::{ParameterFlags, ParameterInfo};
 
 #[cfg(feature = "sampler")]
 pub use crate::recording::{
-    recording_start_system, recording_stop_system, RecordingActive, TuttiRecordingPlugin,
-    RecordingResult, StartRecording, StopRecording,
+    recording_count_in_system, recording_start_system, recording_stop_system, CountingIn,
+    RecordingActive, TuttiRecordingPlugin, RecordingResult, StartRecording, StopRecording,
 };
 #[cfg(feature = "sampler")]
 pub use tutti::sampler::capture::{
@@ -178,7 +240,7 @@ pub use tutti::units::{
 
 // Resource newtypes (defined in `crate::resources`).
 pub use crate::resources::{
-    AudioConfig, MeteringRes, TransportRes, TuttiDriverRes, TuttiGraphRes,
+    AudioConfig, MeteringRes, OutputLatency, TransportRes, TuttiDriverRes, TuttiGraphRes,
 };
 #[cfg(feature = "midi")]
 pub use crate::resources::MidiBusRes;
@@ -29,6 +29,18 @@ impl StartRecording {
     }
 }
 
+/// A `StartRecording` waiting out the [`Metronome`](crate::Metronome)
+/// count-in.
+///
+/// Inserted by `recording_start_system` in place of the trigger when a
+/// count-in applies; `recording_count_in_system` starts the recording at
+/// `at_beat` once the count-in has started the transport.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CountingIn {
+    pub start: StartRecording,
+    pub at_beat: f64,
+}
+
 /// Trigger component: spawn or insert on an entity to stop recording on a channel.
 ///
 /// The `recording_stop_system` processes entities with `Added<StopRecording>`,
@@ -1,9 +1,10 @@
 use bevy_ecs::prelude::*;
 
+use crate::metronome::{Metronome, MetronomeClick};
 use crate::resources::SamplerRes;
 use crate::transport::TransportState;
 
-use super::components::{RecordingActive, StartRecording, StopRecording};
+use super::components::{CountingIn, RecordingActive, StartRecording, StopRecording};
 
 /// Holds the recorded data after a recording session completes.
 ///
@@ -16,46 +17,102 @@ pub struct RecordingResult(pub tutti::sampler::capture::Recorded);
 ///
 /// Calls `sampler.recording().start_recording()` with the current transport beat,
 /// replaces the trigger with `RecordingActive`.
+///
+/// With a [`Metronome`] whose `count_in_bars` is non-zero and the transport
+/// stopped, the trigger is replaced with [`CountingIn`] instead and the
+/// click node counts in; `recording_count_in_system` takes it from there.
 pub fn recording_start_system(
     mut commands: Commands,
     sampler: Option<Res<SamplerRes>>,
     transport: Res<TransportState>,
+    metronome: Option<Res<Metronome>>,
+    click: Option<Res<MetronomeClick>>,
     query: Query<(Entity, &StartRecording), Added<StartRecording>>,
 ) {
     let Some(sampler) = sampler else { return };
+    let count_in_bars = metronome.map_or(0, |m| m.count_in_bars);
 
     for (entity, start) in query.iter() {
-        match sampler.0.recording().start_recording(
-            start.channel_index,
-            start.source,
-            start.mode,
-            transport.beat,
-        ) {
-            Ok(()) => {
-                commands
-                    .entity(entity)
-                    .remove::<StartRecording>()
-                    .insert(RecordingActive {
-                        channel_index: start.channel_index,
-                        source: start.source,
-                        mode: start.mode,
-                    });
-                bevy_log::info!(
-                    "Recording started on channel {} ({:?}, {:?})",
-                    start.channel_index,
-                    start.source,
-                    start.mode
-                );
-            }
-            Err(e) => {
-                bevy_log::error!(
-                    "Failed to start recording on channel {}: {}",
-                    start.channel_index,
-                    e
-                );
-                commands.entity(entity).remove::<StartRecording>();
+        if count_in_bars > 0 && !transport.is_playing {
+            if let Some(click) = &click {
+                if click.count_in(count_in_bars, transport.tempo) {
+                    commands
+                        .entity(entity)
+                        .remove::<StartRecording>()
+                        .insert(CountingIn {
+                            start: *start,
+                            at_beat: transport.beat,
+                        });
+                    bevy_log::info!(
+                        "Counting in {} bar(s) before recording on channel {}",
+                        count_in_bars,
+                        start.channel_index
+                    );
+                    continue;
+                }
             }
         }
+        commands.entity(entity).remove::<StartRecording>();
+        start_recording(&mut commands, &sampler, entity, start, transport.beat);
+    }
+}
+
+/// Starts recordings whose [`CountingIn`] has finished, i.e. once the
+/// count-in has started the transport.
+pub fn recording_count_in_system(
+    mut commands: Commands,
+    sampler: Option<Res<SamplerRes>>,
+    transport: Res<TransportState>,
+    query: Query<(Entity, &CountingIn)>,
+) {
+    let Some(sampler) = sampler else { return };
+    if !transport.is_playing {
+        return;
+    }
+    for (entity, counting) in query.iter() {
+        commands.entity(entity).remove::<CountingIn>();
+        start_recording(
+            &mut commands,
+            &sampler,
+            entity,
+            &counting.start,
+            counting.at_beat,
+        );
+    }
+}
+
+fn start_recording(
+    commands: &mut Commands,
+    sampler: &SamplerRes,
+    entity: Entity,
+    start: &StartRecording,
+    beat: f64,
+) {
+    match sampler
+        .0
+        .recording()
+        .start_recording(start.channel_index, start.source, start.mode, beat)
+    {
+        Ok(()) => {
+            commands.entity(entity).insert(RecordingActive {
+                channel_index: start.channel_index,
+                source: start.source,
+                mode: start.mode,
+            });
+            bevy_log::info!(
+                "Recording started on channel {} ({:?}, {:?})",
+                start.channel_index,
+                start.source,
+                start.mode
+            );
+        }
+        Err(e) => {
+            bevy_log::error!(
+                "Failed to start recording on channel {}: {}",
+                start.channel_index,
+                e
+            );
+        }
     }
 }
 
@@ -67,12 +124,31 @@ pub fn recording_start_system(
 pub fn recording_stop_system(
     mut commands: Commands,
     sampler: Option<Res<SamplerRes>>,
+    click: Option<Res<MetronomeClick>>,
     query: Query<(Entity, &StopRecording), Added<StopRecording>>,
     active_query: Query<(Entity, &RecordingActive)>,
+    counting_query: Query<(Entity, &CountingIn)>,
 ) {
     let Some(sampler) = sampler else { return };
 
     for (entity, stop) in query.iter() {
+        // Stopped during the count-in: nothing was recorded yet.
+        let counting: Vec<Entity> = counting_query
+            .iter()
+            .filter(|(_, c)| c.start.channel_index == stop.channel_index)
+            .map(|(e, _)| e)
+            .collect();
+        if !counting.is_empty() {
+            for counting_entity in counting {
+                commands.entity(counting_entity).remove::<CountingIn>();
+            }
+            if let Some(click) = &click {
+                click.cancel_count_in();
+            }
+            commands.entity(entity).remove::<StopRecording>();
+            continue;
+        }
+
         match sampler.0.recording().stop_recording(stop.channel_index) {
             Ok(data) => {
                 bevy_log::info!(
@@ -27,6 +27,22 @@ use tutti::{TuttiDriver, TuttiGraph};
 pub struct AudioConfig {
     pub sample_rate: f64,
     pub channels: usize,
+    /// Frames per render block. Known for headless engines, which render
+    /// fixed blocks; `None` for a live stream, whose device doesn't
+    /// report its callback size.
+    pub block_size: Option<usize>,
+}
+
+/// Time from the transport's playhead to the listener's ears.
+///
+/// tutti doesn't report device latency, so this is set by the host —
+/// typically from a calibration screen. Audio-clock consumers such as
+/// `BeatTrigger` subtract it to fire when a beat is heard rather than
+/// when it is rendered. Defaults to zero.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource, Default, Clone)]
+pub struct OutputLatency {
+    pub seconds: f64,
 }
 
 /// Owns the editable DSP graph. `&mut` edits; call `commit()` once per frame
@@ -6,6 +6,9 @@ use bevy_ecs::prelude::*;
 
 use bevy_reflect::prelude::*;
 
+use tutti::core::ecs::{AudioNode, NodeKind};
+
+use crate::graph::rehydrate::AudioNodeRecipe;
 use crate::loader::TuttiLoader;
 use crate::playback::AudioEmitter;
 use crate::resources::{AudioConfig, TuttiGraphRes};
@@ -14,9 +17,10 @@ use crate::resources::MidiBusRes;
 
 /// Trigger component: spawn an entity with this to create a SoundFont instrument.
 ///
-/// The `soundfont_playback_system` processes entities with `Added<PlaySoundFont>`,
-/// creates a `SoundFontUnit` in tutti's graph with MIDI routing, attaches
-/// `AudioEmitter`, and removes this component.
+/// The `soundfont_playback_system` processes entities with `PlaySoundFont`
+/// once the asset has loaded, creates a `SoundFontUnit` in tutti's graph
+/// with MIDI routing, attaches `AudioNode` + `AudioEmitter`, and removes
+/// this component.
 ///
 /// # Examples
 ///
@@ -54,24 +58,37 @@ impl PlaySoundFont {
 }
 
 /// Processes `PlaySoundFont` trigger components, creates `SoundFontUnit` nodes
-/// in tutti's graph with MIDI routing, and attaches `AudioEmitter` to the entity.
+/// in tutti's graph with MIDI routing, and attaches `AudioNode` +
+/// `AudioEmitter` to the entity.
+///
+/// Triggers whose asset is still loading (always the case right after a
+/// scene load) are parked and retried each frame until it resolves.
 pub fn soundfont_playback_system(
     mut commands: Commands,
+    mut waiting: Local<std::collections::HashSet<Entity>>,
     sf_assets: Res<Assets<tutti::synth::SoundFontAsset>>,
     graph: Option<ResMut<TuttiGraphRes>>,
     config: Option<Res<AudioConfig>>,
     #[cfg(feature = "midi")] midi: Option<Res<MidiBusRes>>,
-    query: Query<(Entity, &PlaySoundFont), Added<PlaySoundFont>>,
+    added: Query<Entity, Added<PlaySoundFont>>,
+    query: Query<&PlaySoundFont>,
 ) {
+    waiting.extend(added.iter());
     let Some(mut graph) = graph else { return };
     let Some(config) = config else { return };
 
     let mut edited = false;
 
-    for (entity, play) in query.iter() {
+    for entity in waiting.iter().copied().collect::<Vec<_>>() {
+        let Ok(play) = query.get(entity) else {
+            // Despawned or trigger removed before the asset arrived.
+            waiting.remove(&entity);
+            continue;
+        };
         let Some(source) = sf_assets.get(&play.source) else {
             continue;
         };
+        waiting.remove(&entity);
 
         let settings = tutti::synth::SynthesizerSettings::new(config.sample_rate as i32);
         let mut unit = match tutti::synth::SoundFontUnit::new(source.0.clone(), &settings) {
@@ -95,10 +112,19 @@ pub fn soundfont_playback_system(
         graph.0.pipe_output(id);
         edited = true;
 
-        commands
-            .entity(entity)
-            .remove::<PlaySoundFont>()
-            .insert(AudioEmitter { node_id: id });
+        let mut e = commands.entity(entity);
+        e.remove::<PlaySoundFont>().insert((
+            AudioNode(id),
+            NodeKind::Generator,
+            AudioEmitter { node_id: id },
+        ));
+        if let Some(path) = play.source.path() {
+            e.insert(AudioNodeRecipe::SoundFont {
+                path: path.to_string(),
+                preset: play.preset,
+                channel: play.channel,
+            });
+        }
     }
 
     if edited {
@@ -0,0 +1,378 @@
+//! Tempo and meter over time.
+//!
+//! tutti's transport runs at one tempo, and `TransportState` used to
+//! report a fixed 4/4. Insert a [`TempoMap`] resource to describe tempo
+//! changes (stepped or linearly ramped) and meter changes along the
+//! timeline; [`tempo_map_follow_system`] then drives the transport's
+//! tempo from it every frame and `transport_sync_system` reports the
+//! meter at the playhead.
+//!
+//! Beats are quarter notes throughout, the same unit as the transport,
+//! [`MidiSequence`](crate::MidiSequence) and `StartExport`. Bars and
+//! beat-within-bar ([`BarBeatTick`]) follow the meter: in 6/8 a bar is
+//! three quarter notes and holds six BBT beats.
+//!
+//! Following happens once per frame, so inside a ramp the transport's
+//! tempo steps at frame rate. Conversions on the map itself
+//! ([`TempoMap::seconds_at`] etc.) are exact.
+
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+
+use crate::TransportRes;
+
+/// BBT ticks per quarter note used when a map doesn't say otherwise.
+pub const DEFAULT_TICKS_PER_BEAT: u32 = 960;
+
+/// How tempo moves from one [`TempoPoint`] to the next.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum TempoRamp {
+    /// Hold this point's tempo until the next point.
+    #[default]
+    Step,
+    /// Move linearly (per beat) to the next point's tempo.
+    Linear,
+}
+
+/// A tempo at a beat.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct TempoPoint {
+    pub beat: f64,
+    pub bpm: f64,
+    pub ramp: TempoRamp,
+}
+
+/// A time signature starting at a bar (0-based).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct MeterChange {
+    pub bar: u32,
+    pub numerator: u8,
+    pub denominator: u8,
+}
+
+impl MeterChange {
+    /// Length of one bar of this meter, in quarter-note beats.
+    pub fn bar_beats(&self) -> f64 {
+        self.numerator.max(1) as f64 * 4.0 / self.denominator.max(1) as f64
+    }
+}
+
+/// A musical position: 1-based bar and beat (in meter units), plus
+/// ticks into the beat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct BarBeatTick {
+    pub bar: u32,
+    pub beat: u32,
+    pub tick: u32,
+}
+
+impl std::fmt::Display for BarBeatTick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{:03}", self.bar, self.beat, self.tick)
+    }
+}
+
+/// Tempo and meter changes along the timeline.
+///
+/// Always has a tempo at beat 0 and a meter at bar 0. Build it with the
+/// `with_*` methods; points are kept sorted.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource, Default, Clone)]
+pub struct TempoMap {
+    tempo: Vec<TempoPoint>,
+    meter: Vec<MeterChange>,
+    pub ticks_per_beat: u32,
+}
+
+impl Default for TempoMap {
+    fn default() -> Self {
+        Self::new(120.0)
+    }
+}
+
+impl TempoMap {
+    /// A constant `bpm`, 4/4 map.
+    pub fn new(bpm: f64) -> Self {
+        Self {
+            tempo: vec![TempoPoint {
+                beat: 0.0,
+                bpm,
+                ramp: TempoRamp::Step,
+            }],
+            meter: vec![MeterChange {
+                bar: 0,
+                numerator: 4,
+                denominator: 4,
+            }],
+            ticks_per_beat: DEFAULT_TICKS_PER_BEAT,
+        }
+    }
+
+    /// Jump to `bpm` at `beat`.
+    pub fn with_tempo(mut self, beat: f64, bpm: f64) -> Self {
+        self.set_tempo(beat, bpm, TempoRamp::Step);
+        self
+    }
+
+    /// Ramp linearly from the tempo at `from_beat` to `bpm` at `to_beat`.
+    pub fn with_ramp(mut self, from_beat: f64, to_beat: f64, bpm: f64) -> Self {
+        let start = self.tempo_at(from_beat);
+        self.set_tempo(from_beat, start, TempoRamp::Linear);
+        self.set_tempo(to_beat, bpm, TempoRamp::Step);
+        self
+    }
+
+    /// Switch to `numerator/denominator` at `bar` (0-based).
+    pub fn with_meter(mut self, bar: u32, numerator: u8, denominator: u8) -> Self {
+        self.set_meter(bar, numerator, denominator);
+        self
+    }
+
+    /// Insert or replace the tempo point at `beat`.
+    pub fn set_tempo(&mut self, beat: f64, bpm: f64, ramp: TempoRamp) {
+        let beat = beat.max(0.0);
+        let point = TempoPoint {
+            beat,
+            bpm: bpm.max(1e-3),
+            ramp,
+        };
+        match self.tempo.iter().position(|p| p.beat >= beat) {
+            Some(i) if self.tempo[i].beat == beat => self.tempo[i] = point,
+            Some(i) => self.tempo.insert(i, point),
+            None => self.tempo.push(point),
+        }
+    }
+
+    /// Insert or replace the meter change at `bar`.
+    pub fn set_meter(&mut self, bar: u32, numerator: u8, denominator: u8) {
+        let change = MeterChange {
+            bar,
+            numerator: numerator.max(1),
+            denominator: denominator.max(1),
+        };
+        match self.meter.iter().position(|m| m.bar >= bar) {
+            Some(i) if self.meter[i].bar == bar => self.meter[i] = change,
+            Some(i) => self.meter.insert(i, change),
+            None => self.meter.push(change),
+        }
+    }
+
+    pub fn tempo_points(&self) -> &[TempoPoint] {
+        &self.tempo
+    }
+
+    pub fn meter_changes(&self) -> &[MeterChange] {
+        &self.meter
+    }
+
+    /// Index of the tempo segment containing `beat`.
+    fn segment(&self, beat: f64) -> usize {
+        self.tempo.iter().rposition(|p| p.beat <= beat).unwrap_or(0)
+    }
+
+    /// `(start bpm, bpm slope per beat)` of segment `i`.
+    fn segment_curve(&self, i: usize) -> (f64, f64) {
+        let p = self.tempo[i];
+        match (p.ramp, self.tempo.get(i + 1)) {
+            (TempoRamp::Linear, Some(next)) if next.beat > p.beat => {
+                (p.bpm, (next.bpm - p.bpm) / (next.beat - p.beat))
+            }
+            _ => (p.bpm, 0.0),
+        }
+    }
+
+    /// Seconds to play `beats` from the start of segment `i`.
+    fn segment_seconds(&self, i: usize, beats: f64) -> f64 {
+        let (bpm, slope) = self.segment_curve(i);
+        if slope.abs() < 1e-12 {
+            60.0 * beats / bpm
+        } else {
+            60.0 / slope * ((bpm + slope * beats) / bpm).ln()
+        }
+    }
+
+    /// Beats played in `secs` from the start of segment `i`.
+    fn segment_beats(&self, i: usize, secs: f64) -> f64 {
+        let (bpm, slope) = self.segment_curve(i);
+        if slope.abs() < 1e-12 {
+            secs * bpm / 60.0
+        } else {
+            bpm / slope * ((slope * secs / 60.0).exp() - 1.0)
+        }
+    }
+
+    /// Tempo in BPM at `beat`.
+    pub fn tempo_at(&self, beat: f64) -> f64 {
+        let i = self.segment(beat);
+        let (bpm, slope) = self.segment_curve(i);
+        bpm + slope * (beat - self.tempo[i].beat).max(0.0)
+    }
+
+    /// Seconds from beat 0 to `beat`.
+    pub fn seconds_at(&self, beat: f64) -> f64 {
+        let beat = beat.max(0.0);
+        let mut secs = 0.0;
+        for i in 0..self.tempo.len() {
+            let start = self.tempo[i].beat;
+            let end = self.tempo.get(i + 1).map_or(f64::INFINITY, |p| p.beat);
+            if beat <= start {
+                break;
+            }
+            secs += self.segment_seconds(i, beat.min(end) - start);
+        }
+        secs
+    }
+
+    /// Seconds between two beats.
+    pub fn seconds_between(&self, from_beat: f64, to_beat: f64) -> f64 {
+        self.seconds_at(to_beat) - self.seconds_at(from_beat)
+    }
+
+    /// Beat reached `secs` seconds after beat 0.
+    pub fn beat_at_seconds(&self, secs: f64) -> f64 {
+        let mut remaining = secs.max(0.0);
+        for i in 0..self.tempo.len() {
+            let start = self.tempo[i].beat;
+            match self.tempo.get(i + 1) {
+                Some(next) => {
+                    let span = self.segment_seconds(i, next.beat - start);
+                    if remaining < span {
+                        return start + self.segment_beats(i, remaining);
+                    }
+                    remaining -= span;
+                }
+                None => return start + self.segment_beats(i, remaining),
+            }
+        }
+        0.0
+    }
+
+    /// Meter in force at `beat`.
+    pub fn meter_at(&self, beat: f64) -> MeterChange {
+        let (index, _, _) = self.locate_bar(beat);
+        self.meter[index]
+    }
+
+    /// `(meter index, bar number, beat where that bar starts)` for `beat`.
+    fn locate_bar(&self, beat: f64) -> (usize, u32, f64) {
+        let beat = beat.max(0.0);
+        let mut bar_start_beat = 0.0;
+        for i in 0..self.meter.len() {
+            let m = self.meter[i];
+            let bar_beats = m.bar_beats();
+            let next = self.meter.get(i + 1);
+            let change_beat = next.map(|n| bar_start_beat + (n.bar - m.bar) as f64 * bar_beats);
+            if change_beat.is_none_or(|c| beat < c) {
+                let bars_in = ((beat - bar_start_beat) / bar_beats).floor().max(0.0);
+                return (
+                    i,
+                    m.bar + bars_in as u32,
+                    bar_start_beat + bars_in * bar_beats,
+                );
+            }
+            bar_start_beat = change_beat.unwrap_or(bar_start_beat);
+        }
+        (0, 0, 0.0)
+    }
+
+    /// Beat where `bar` (0-based) starts.
+    pub fn bar_start_beat(&self, bar: u32) -> f64 {
+        let mut beat = 0.0;
+        for i in 0..self.meter.len() {
+            let m = self.meter[i];
+            let end_bar = self.meter.get(i + 1).map_or(u32::MAX, |n| n.bar);
+            if bar < end_bar {
+                return beat + (bar - m.bar) as f64 * m.bar_beats();
+            }
+            beat += (end_bar - m.bar) as f64 * m.bar_beats();
+        }
+        beat
+    }
+
+    /// Musical position of `beat`.
+    pub fn bbt_at(&self, beat: f64) -> BarBeatTick {
+        let (index, bar, bar_start) = self.locate_bar(beat);
+        let unit = 4.0 / self.meter[index].denominator as f64;
+        let into_bar = (beat.max(0.0) - bar_start) / unit;
+        let whole = into_bar.floor();
+        let ticks_per_unit = self.ticks_per_beat as f64 * unit;
+        BarBeatTick {
+            bar: bar + 1,
+            beat: whole as u32 + 1,
+            tick: ((into_bar - whole) * ticks_per_unit)
+                .round()
+                .min(ticks_per_unit - 1.0) as u32,
+        }
+    }
+
+    /// Beat of a musical position (inverse of [`bbt_at`](Self::bbt_at)).
+    pub fn beat_at_bbt(&self, bbt: BarBeatTick) -> f64 {
+        let bar = bbt.bar.saturating_sub(1);
+        let start = self.bar_start_beat(bar);
+        let unit = 4.0 / self.meter_at(start).denominator as f64;
+        start
+            + bbt.beat.saturating_sub(1) as f64 * unit
+            + bbt.tick as f64 / self.ticks_per_beat as f64
+    }
+}
+
+/// Drives the transport's tempo from [`TempoMap`] at the playhead.
+///
+/// No-op without a `TempoMap`, so hosts that set the tempo directly on
+/// `TransportRes` keep doing so.
+pub fn tempo_map_follow_system(map: Option<Res<TempoMap>>, transport: Option<Res<TransportRes>>) {
+    let (Some(map), Some(transport)) = (map, transport) else {
+        return;
+    };
+    let bpm = map.tempo_at(transport.current_beat());
+    if (transport.get_tempo().get() - bpm).abs() > 1e-6 {
+        transport.tempo(bpm);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbt(bar: u32, beat: u32, tick: u32) -> BarBeatTick {
+        BarBeatTick { bar, beat, tick }
+    }
+
+    #[test]
+    fn constant_tempo_converts_both_ways() {
+        let map = TempoMap::new(120.0);
+        assert_eq!(map.seconds_at(8.0), 4.0);
+        assert_eq!(map.beat_at_seconds(4.0), 8.0);
+    }
+
+    #[test]
+    fn ramp_round_trips_and_steps_after() {
+        // 60 → 120 BPM over beats 4..8, then hold.
+        let map = TempoMap::new(60.0).with_ramp(4.0, 8.0, 120.0);
+        assert!((map.tempo_at(6.0) - 90.0).abs() < 1e-9);
+        assert_eq!(map.tempo_at(10.0), 120.0);
+        for beat in [2.0, 5.0, 7.5, 12.0] {
+            let secs = map.seconds_at(beat);
+            assert!(
+                (map.beat_at_seconds(secs) - beat).abs() < 1e-9,
+                "beat {beat}"
+            );
+        }
+        // The ramp is faster than 60 BPM throughout.
+        assert!(map.seconds_between(4.0, 8.0) < 4.0);
+    }
+
+    #[test]
+    fn bbt_follows_meter_changes() {
+        // Two bars of 4/4, then 6/8 (three quarter notes per bar).
+        let map = TempoMap::new(120.0).with_meter(2, 6, 8);
+        assert_eq!(map.bbt_at(0.0), bbt(1, 1, 0));
+        assert_eq!(map.bbt_at(8.0), bbt(3, 1, 0));
+        // 6/8 beats are eighth notes.
+        assert_eq!(map.bbt_at(9.5), bbt(3, 4, 0));
+        assert_eq!(map.bbt_at(11.0), bbt(4, 1, 0));
+        assert_eq!(map.meter_at(11.0).numerator, 6);
+        let pos = bbt(4, 2, 240);
+        assert_eq!(map.bbt_at(map.beat_at_bbt(pos)), pos);
+    }
+}
@@ -1,6 +1,8 @@
+use bevy_ecs::message::{Message, MessageReader, MessageWriter};
 use bevy_ecs::prelude::*;
 use bevy_reflect::prelude::*;
 
+use crate::tempo_map::TempoMap;
 use crate::TransportRes;
 
 /// Transport state synced from Tutti every frame via lock-free atomics.
@@ -58,11 +60,109 @@ impl TransportState {
     }
 }
 
+/// Transport control as a message, for systems that would rather not
+/// hold [`TransportRes`].
+///
+/// Applied in order by [`transport_command_system`], which runs just
+/// before [`transport_sync_system`] so the same frame's
+/// [`TransportState`] and [`TransportEvent`]s already reflect them.
+#[derive(Event, Message, Clone, Copy, Debug, PartialEq)]
+pub enum TransportCommand {
+    Play,
+    Stop,
+    Pause,
+    /// Move the playhead to a beat. Beats jumped over don't emit
+    /// crossing events.
+    Seek(f64),
+    /// Overridden by a [`TempoMap`] on the next frame when one is inserted.
+    SetTempo(f64),
+    /// `Some((start, end))` loops that range, `None` turns looping off.
+    SetLoop(Option<(f64, f64)>),
+    /// Start recording (and playback).
+    Record,
+}
+
+/// Musical events derived from the transport each frame by
+/// [`transport_sync_system`].
+///
+/// Bar and beat numbers are 0-based transport positions: `BeatCrossed(n)`
+/// fires as the playhead reaches quarter-note beat `n`, `BarCrossed(n)`
+/// as it reaches the start of bar `n` (following the [`TempoMap`]'s meter
+/// when one is inserted, `TransportState`'s time signature otherwise).
+/// A boundary exactly at the playhead when playback starts counts as
+/// crossed. Within a frame events come in timeline order, a bar before
+/// the beat it starts on.
+///
+/// These are frame-rate events for UI and gameplay, not sample-accurate
+/// triggers.
+#[derive(Event, Message, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransportEvent {
+    Started,
+    Stopped,
+    /// The playhead wrapped from the loop end back to the loop start.
+    Looped,
+    BarCrossed(u32),
+    BeatCrossed(u64),
+}
+
+/// Applies [`TransportCommand`] messages to the transport.
+pub fn transport_command_system(
+    transport: Option<Res<TransportRes>>,
+    mut state: ResMut<TransportState>,
+    mut commands: MessageReader<TransportCommand>,
+) {
+    let Some(transport) = transport else {
+        commands.clear();
+        return;
+    };
+    for command in commands.read() {
+        match *command {
+            TransportCommand::Play => {
+                transport.play();
+            }
+            TransportCommand::Stop => {
+                transport.stop();
+            }
+            TransportCommand::Pause => {
+                transport.pause();
+            }
+            TransportCommand::Seek(beat) => {
+                transport.seek(beat);
+                // Sync compares against the previous beat; starting from
+                // the seek target keeps the jump from reading as crossings
+                // or a loop wrap.
+                state.beat = beat;
+            }
+            TransportCommand::SetTempo(bpm) => {
+                transport.tempo(bpm);
+            }
+            TransportCommand::SetLoop(Some((start, end))) => {
+                transport.loop_range(start, end).enable_loop();
+            }
+            TransportCommand::SetLoop(None) => {
+                transport.disable_loop();
+            }
+            TransportCommand::Record => {
+                transport.record();
+            }
+        }
+    }
+}
+
+/// Mirrors the transport into [`TransportState`] and emits
+/// [`TransportEvent`]s for what changed since the last frame. The time
+/// signature comes from [`TempoMap`] at the playhead when one is
+/// inserted.
 pub fn transport_sync_system(
     transport: Option<Res<TransportRes>>,
+    tempo_map: Option<Res<TempoMap>>,
     mut state: ResMut<TransportState>,
+    mut events: MessageWriter<TransportEvent>,
 ) {
     let Some(transport) = transport else { return };
+    let was_playing = state.is_playing;
+    let previous_beat = state.beat;
+
     state.beat = transport.current_beat();
     state.is_playing = transport.is_playing();
     state.is_recording = transport.is_recording();
@@ -72,4 +172,320 @@ pub fn transport_sync_system(
         state.loop_start = start;
         state.loop_end = end;
     }
+    if let Some(map) = &tempo_map {
+        let meter = map.meter_at(state.beat);
+        state.time_sig_numerator = meter.numerator;
+        state.time_sig_denominator = meter.denominator;
+    }
+
+    match (was_playing, state.is_playing) {
+        (false, true) => {
+            events.write(TransportEvent::Started);
+        }
+        (true, false) => {
+            events.write(TransportEvent::Stopped);
+            return;
+        }
+        (false, false) => return,
+        (true, true) => {}
+    }
+
+    let meter = Meter::new(tempo_map.as_deref(), &state);
+    let loop_range = (state.is_looping && state.loop_end > state.loop_start)
+        .then_some((state.loop_start, state.loop_end));
+    events.write_batch(transport_crossings(
+        previous_beat,
+        state.beat,
+        !was_playing,
+        loop_range,
+        &meter,
+    ));
+}
+
+/// Bar boundaries, from the [`TempoMap`] or a fixed time signature.
+pub(crate) struct Meter<'a> {
+    map: Option<&'a TempoMap>,
+    bar_beats: f64,
+}
+
+impl<'a> Meter<'a> {
+    pub(crate) fn new(map: Option<&'a TempoMap>, state: &TransportState) -> Self {
+        let bar_beats =
+            state.time_sig_numerator.max(1) as f64 * 4.0 / state.time_sig_denominator.max(1) as f64;
+        Self { map, bar_beats }
+    }
+
+    pub(crate) fn bar_at(&self, beat: f64) -> u32 {
+        match self.map {
+            Some(map) => map.bbt_at(beat).bar - 1,
+            None => (beat.max(0.0) / self.bar_beats).floor() as u32,
+        }
+    }
+
+    pub(crate) fn bar_start(&self, bar: u32) -> f64 {
+        match self.map {
+            Some(map) => map.bar_start_beat(bar),
+            None => bar as f64 * self.bar_beats,
+        }
+    }
+}
+
+/// Events for the playhead moving from `from` to `to`. Boundaries at
+/// `from` count only when `include_from`. Backwards motion is a loop
+/// wrap when `from` and `to` are both inside `loop_range`, and emits
+/// nothing otherwise.
+fn transport_crossings(
+    from: f64,
+    to: f64,
+    include_from: bool,
+    loop_range: Option<(f64, f64)>,
+    meter: &Meter,
+) -> Vec<TransportEvent> {
+    let mut events = Vec::new();
+    if to >= from {
+        push_crossings(from, to, include_from, meter, &mut events);
+    } else if let Some((start, end)) = loop_range {
+        if from >= start && from <= end && to >= start {
+            // The loop end is the loop start again: stop just short of it.
+            push_crossings(from, end - 1e-9, include_from, meter, &mut events);
+            events.push(TransportEvent::Looped);
+            push_crossings(start, to, true, meter, &mut events);
+        }
+    }
+    events
+}
+
+fn push_crossings(
+    from: f64,
+    to: f64,
+    include_from: bool,
+    meter: &Meter,
+    events: &mut Vec<TransportEvent>,
+) {
+    if to < from {
+        return;
+    }
+    let crossed = |at: f64| at <= to && (at > from || (include_from && at == from));
+    let mut found: Vec<(f64, TransportEvent)> = Vec::new();
+
+    let first_bar = meter.bar_at(from);
+    for bar in first_bar..=meter.bar_at(to) {
+        let at = meter.bar_start(bar);
+        if crossed(at) {
+            found.push((at, TransportEvent::BarCrossed(bar)));
+        }
+    }
+    let mut beat = from.max(0.0).ceil();
+    while beat <= to {
+        if crossed(beat) {
+            found.push((beat, TransportEvent::BeatCrossed(beat as u64)));
+        }
+        beat += 1.0;
+    }
+    // Stable: bars were pushed first, so they stay ahead of beats at the
+    // same position.
+    found.sort_by(|a, b| a.0.total_cmp(&b.0));
+    events.extend(found.into_iter().map(|(_, event)| event));
+}
+
+/// The stretch of transport time the next frame is expected to cover.
+///
+/// Systems that hand timed events to the audio thread (scheduled MIDI,
+/// sequence playback) can't wait until the playhead has passed a beat —
+/// by then the event is up to a frame late. Instead they look one frame
+/// ahead: anything due within [`length_beats`](Self::length_beats) of
+/// the playhead is queued now, with a sample offset from the next block.
+///
+/// Beat distances are loop-aware: with looping enabled, a beat inside the
+/// loop that lies behind the playhead is reached again after the wrap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeatWindow {
+    /// Playhead position when the window was taken.
+    pub beat: f64,
+    /// How far ahead of `beat` to schedule.
+    pub length_beats: f64,
+    pub tempo: f64,
+    pub sample_rate: f64,
+    /// `(start, end)` when the transport is looping.
+    pub loop_range: Option<(f64, f64)>,
+}
+
+impl BeatWindow {
+    /// Snapshot the transport, looking `lookahead_secs` ahead.
+    pub fn from_transport(
+        transport: &tutti::TransportHandle,
+        sample_rate: f64,
+        lookahead_secs: f64,
+    ) -> Self {
+        let tempo = transport.get_tempo().get();
+        let loop_range = if transport.is_loop_enabled() {
+            transport
+                .get_loop_range()
+                .filter(|(start, end)| end > start)
+        } else {
+            None
+        };
+        Self {
+            beat: transport.current_beat(),
+            length_beats: lookahead_secs * tempo / 60.0,
+            tempo,
+            sample_rate,
+            loop_range,
+        }
+    }
+
+    /// Re-derive the window length and tempo from `map`, so a lookahead
+    /// that straddles a tempo change or ramp covers the right number of
+    /// beats. `tempo` becomes the window's average tempo.
+    pub fn with_tempo_map(mut self, map: &TempoMap) -> Self {
+        if self.tempo <= 0.0 || self.length_beats <= 0.0 {
+            return self;
+        }
+        let lookahead_secs = self.length_beats * 60.0 / self.tempo;
+        let end = map.beat_at_seconds(map.seconds_at(self.beat) + lookahead_secs);
+        self.length_beats = (end - self.beat).max(0.0);
+        self.tempo = self.length_beats * 60.0 / lookahead_secs;
+        self
+    }
+
+    /// Beats of playback from the playhead until `at_beat`, or `None` if
+    /// the playhead will never reach it (behind it and not looping back).
+    pub fn beats_until(&self, at_beat: f64) -> Option<f64> {
+        if at_beat >= self.beat {
+            if let Some((start, end)) = self.loop_range {
+                if self.beat < end && at_beat >= end && start < end {
+                    // Past the loop end: never reached while looping.
+                    return None;
+                }
+            }
+            return Some(at_beat - self.beat);
+        }
+        match self.loop_range {
+            Some((start, end)) if at_beat >= start && self.beat <= end => {
+                Some((end - self.beat) + (at_beat - start))
+            }
+            _ => None,
+        }
+    }
+
+    /// Transport beat `beats_ahead` of the playhead, wrapped into the
+    /// loop when it runs past the loop end.
+    pub fn beat_at(&self, beats_ahead: f64) -> f64 {
+        let beat = self.beat + beats_ahead;
+        match self.loop_range {
+            Some((start, end)) if self.beat < end && beat >= end => {
+                start + (beat - end) % (end - start)
+            }
+            _ => beat,
+        }
+    }
+
+    /// Beats the playhead advanced from `previous` to now, counting a
+    /// loop wrap as forward motion.
+    pub fn advanced_since(&self, previous: f64) -> f64 {
+        if self.beat >= previous {
+            return self.beat - previous;
+        }
+        match self.loop_range {
+            Some((start, end)) => ((end - previous) + (self.beat - start)).max(0.0),
+            None => 0.0,
+        }
+    }
+
+    /// Whether something `beats_ahead` of the playhead is due this frame.
+    pub fn is_due(&self, beats_ahead: f64) -> bool {
+        beats_ahead < self.length_beats
+    }
+
+    /// Sample offset, from the next block, of a point `beats_ahead` of
+    /// the playhead.
+    pub fn samples_ahead(&self, beats_ahead: f64) -> usize {
+        if self.tempo <= 0.0 {
+            return 0;
+        }
+        (beats_ahead.max(0.0) * 60.0 / self.tempo * self.sample_rate).round() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(beat: f64, loop_range: Option<(f64, f64)>) -> BeatWindow {
+        BeatWindow {
+            beat,
+            length_beats: 0.5,
+            tempo: 120.0,
+            sample_rate: 48_000.0,
+            loop_range,
+        }
+    }
+
+    #[test]
+    fn beats_until_wraps_inside_loop() {
+        let w = window(3.5, Some((0.0, 4.0)));
+        assert_eq!(w.beats_until(3.75), Some(0.25));
+        assert_eq!(w.beats_until(0.25), Some(0.75));
+        assert_eq!(w.beats_until(5.0), None);
+        assert_eq!(window(3.5, None).beats_until(1.0), None);
+    }
+
+    fn four_four() -> Meter<'static> {
+        Meter {
+            map: None,
+            bar_beats: 4.0,
+        }
+    }
+
+    #[test]
+    fn crossings_count_bars_and_beats_in_order() {
+        use TransportEvent::*;
+        let meter = four_four();
+        assert_eq!(
+            transport_crossings(3.5, 5.2, false, None, &meter),
+            vec![BarCrossed(1), BeatCrossed(4), BeatCrossed(5)]
+        );
+        // Starting exactly on a boundary counts it only when asked.
+        assert_eq!(
+            transport_crossings(0.0, 0.4, true, None, &meter),
+            vec![BarCrossed(0), BeatCrossed(0)]
+        );
+        assert!(transport_crossings(0.0, 0.4, false, None, &meter).is_empty());
+    }
+
+    #[test]
+    fn crossings_wrap_at_loop_end() {
+        use TransportEvent::*;
+        let meter = four_four();
+        assert_eq!(
+            transport_crossings(7.8, 4.1, false, Some((4.0, 8.0)), &meter),
+            vec![Looped, BarCrossed(1), BeatCrossed(4)]
+        );
+        // Moving backwards outside the loop is a seek, not a wrap.
+        assert!(transport_crossings(7.8, 1.0, false, Some((4.0, 8.0)), &meter).is_empty());
+    }
+
+    #[test]
+    fn crossings_follow_tempo_map_meter() {
+        use TransportEvent::*;
+        // 7/8 bars are 3.5 quarter notes long.
+        let map = TempoMap::new(120.0).with_meter(0, 7, 8);
+        let meter = Meter {
+            map: Some(&map),
+            bar_beats: 4.0,
+        };
+        assert_eq!(
+            transport_crossings(3.2, 4.0, false, None, &meter),
+            vec![BarCrossed(1), BeatCrossed(4)]
+        );
+    }
+
+    #[test]
+    fn samples_ahead_follows_tempo() {
+        // 120 BPM: one beat is half a second.
+        assert_eq!(window(0.0, None).samples_ahead(1.0), 24_000);
+        assert_eq!(window(3.9, Some((0.0, 4.0))).advanced_since(3.9), 0.0);
+        let wrapped = window(0.1, Some((0.0, 4.0)));
+        assert!((wrapped.advanced_since(3.9) - 0.2).abs() < 1e-9);
+    }
 }
@@ -23,6 +23,7 @@ use bevy_ecs::prelude::*;
 use tutti::core::ecs::{AudioNode, NodeKind};
 
 use crate::graph::reconcile::GraphDirty;
+use crate::graph::rehydrate::AudioNodeRecipe;
 use crate::plugin_host::{OpenPluginEditor, PluginEmitter};
 use crate::resources::{PluginEditorMainThread, TuttiGraphRes};
 
@@ -99,6 +100,10 @@ pub fn process_pending_vst2_builds(
                 e.insert((
                     AudioNode(node_id),
                     NodeKind::Plugin,
+                    AudioNodeRecipe::Plugin {
+                        path: load.path.clone(),
+                        state: load.state.clone().unwrap_or_default(),
+                    },
                     PluginEmitter { handle },
                 ));
                 if load.open_editor_after {